@@ -1,12 +1,14 @@
 // #![allow(unused)]
 
-use ammber_components::{LoadingModal, Spinner2, SuggestChains, WalletMenu};
+use ammber_components::{LoadingModal, Spinner2, SuggestChains, TxApproval, WalletMenu};
 use ammber_core::{
+    account::{handle_key_store_change, Account},
     constants::{contracts::LB_FACTORY, CHAIN_ID, NODE, TOKEN_MAP},
     prelude::SYMBOL_TO_ADDR,
-    state::{ChainId, Endpoint, KeplrSignals, TokenMap},
+    price::{PriceService, Prices},
+    state::{Balances, ChainId, Endpoint, TokenMap, WalletSignals},
     support::{chain_batch_query, chain_query},
-    Error,
+    Error, TxManager,
 };
 use ammber_pool::PoolRoutes;
 use ammber_sdk::contract_interfaces::{
@@ -15,7 +17,7 @@ use ammber_sdk::contract_interfaces::{
 };
 use ammber_swap::SwapRoutes;
 use batch_query::{BatchItemResponseStatus, BatchQueryParams, BatchQueryParsedResponse};
-use keplr::{Keplr, Key};
+use keplr::Key;
 use leptos::{
     ev,
     html::{Dialog, Input},
@@ -32,11 +34,13 @@ use lucide_leptos::{
     Wrench, X,
 };
 use tracing::{debug, error, info, warn};
-use web_sys::{js_sys, wasm_bindgen::JsValue};
 
 mod routes;
 
-use routes::nav::Nav;
+use routes::{
+    allowances::Allowances, history::TxHistory, nav::Nav, portfolio::Portfolio,
+    viewing_keys::ViewingKeys,
+};
 
 pub const BASE_URL: &str = "/liquidity-book-leptos";
 
@@ -54,21 +58,22 @@ pub fn App() -> impl IntoView {
 
     provide_context(Endpoint::new(NODE));
     provide_context(ChainId::new(CHAIN_ID));
-    provide_context(KeplrSignals::new());
+    provide_context(WalletSignals::new());
     provide_context(TokenMap::new(TOKEN_MAP.clone()));
+    provide_context(Balances::new());
+    provide_context(Account::new());
 
     let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
+    let balances = use_context::<Balances>().expect("balances context missing!");
+    provide_context(TxManager::new(endpoint, balances));
     let chain_id = use_context::<ChainId>().expect("chain id context missing!");
-    let keplr = use_context::<KeplrSignals>().expect("keplr signals context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
     let token_map = use_context::<TokenMap>().expect("tokens context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
+    let account = use_context::<Account>().expect("account context missing!");
 
-    let (keplr_enabled, set_keplr_enabled, _) =
-        use_local_storage::<bool, FromToStringCodec>("is_keplr_enabled");
-
-    // TODO: Enable this later. I keep it off during development to prevent needless queries.
-    // if keplr_enabled.get() {
-    //     keplr.enabled.set(true)
-    // }
+    // Reconnect silently if the user was connected last time.
+    keplr.reconnect();
 
     // NOTE: For any method on Keplr that returns a promise (almost all of them), if it's Ok,
     // that means keplr is enabled. We can use this fact to update any UI that needs to
@@ -257,8 +262,23 @@ pub fn App() -> impl IntoView {
             .collect()
     }
 
+    // USD prices, derived from the pairs. Use `PriceService::with_feed` to add an external feed.
+    let price_service = PriceService::default();
+    let prices: LocalResource<Prices> = LocalResource::new(move || {
+        let price_service = price_service.clone();
+        async move {
+            let lb_pairs = all_lb_pairs.await;
+            price_service
+                .prices(&lb_pairs)
+                .await
+                .inspect_err(|error| error!("{error}"))
+                .unwrap_or_default()
+        }
+    });
+
     provide_context(number_of_lb_pairs);
     provide_context(all_lb_pairs);
+    provide_context(prices);
 
     Effect::new(move |_| {
         let enabled = keplr.enabled.get();
@@ -270,12 +290,18 @@ pub fn App() -> impl IntoView {
 
     // Event Listeners
 
-    // Whenever the key store changes, this will re-set 'keplr.enabled' to true, triggering a
-    // reload of everything subscribed to that signal
+    // Whenever the key store changes, re-fetch the key and reset everything that belonged to
+    // the previous account
     let keplr_keystorechange_handle =
         window_event_listener_untyped("keplr_keystorechange", move |_| {
-            warn!("Key store in Keplr is changed. You may need to refetch the account info.");
-            keplr.enabled.set(true);
+            warn!("Key store in Keplr is changed. Refetching the account info.");
+            spawn_local(async move {
+                if let Err(error) =
+                    handle_key_store_change(keplr, account, balances, tx_manager).await
+                {
+                    error!("{error}");
+                }
+            });
         });
 
     on_cleanup(move || {
@@ -290,26 +316,22 @@ pub fn App() -> impl IntoView {
     // of an odd behavior.
     let enable_keplr_action: Action<(), bool, SyncStorage> =
         Action::new_unsync_with_value(Some(false), move |_: &()| async move {
-            let keplr_extension = js_sys::Reflect::get(&window(), &JsValue::from_str("keplr"))
-                .expect("unable to check for `keplr` property");
+            let wallet = keplr.wallet.get_untracked().wallet();
 
-            if keplr_extension.is_undefined() || keplr_extension.is_null() {
+            if !wallet.is_available() {
                 window()
-                    .alert_with_message("keplr not found")
+                    .alert_with_message(&format!("{} not found", wallet.name()))
                     .expect("alert failed");
                 keplr.enabled.set(false);
                 false
             } else {
-                debug!("Trying to enable Keplr...");
-                match Keplr::enable(vec![CHAIN_ID.to_string()]).await {
+                debug!("Trying to enable {}...", wallet.name());
+                match keplr.connect().await {
                     Ok(_) => {
-                        keplr.enabled.set(true);
-                        set_keplr_enabled.set(true);
-                        debug!("Keplr is enabled");
+                        debug!("{} is enabled", wallet.name());
                         true
                     }
                     Err(e) => {
-                        keplr.enabled.set(false);
                         error!("{e}");
                         false
                     }
@@ -324,8 +346,7 @@ pub fn App() -> impl IntoView {
     };
 
     let disable_keplr = move |_: ev::MouseEvent| {
-        Keplr::disable(CHAIN_ID);
-        keplr.enabled.set(false);
+        keplr.disconnect();
     };
 
     // Node references
@@ -463,9 +484,14 @@ pub fn App() -> impl IntoView {
                     // <Route path=path!("/liquidity-book-leptos") view=|| view! { "Nothing to see here" } />
                     <SwapRoutes />
                     <PoolRoutes />
+                    <Route path=path!("/liquidity-book-leptos/portfolio") view=Portfolio />
+                    <Route path=path!("/liquidity-book-leptos/history") view=TxHistory />
+                    <Route path=path!("/liquidity-book-leptos/viewing-keys") view=ViewingKeys />
+                    <Route path=path!("/liquidity-book-leptos/allowances") view=Allowances />
                 </Routes>
             </main>
             <LoadingModal when=enable_keplr_action.pending() message="Requesting Connection" />
+            <TxApproval />
             <SettingsMenu dialog_ref=options_dialog_ref toggle_menu=toggle_options_menu />
         </Router>
     }
@@ -483,11 +509,10 @@ pub fn SettingsMenu(
 
     let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
     let chain_id = use_context::<ChainId>().expect("chain id context missing!");
-    let keplr = use_context::<KeplrSignals>().expect("keplr signals context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
 
     let disable_keplr = move |_: ev::MouseEvent| {
-        Keplr::disable(CHAIN_ID);
-        keplr.enabled.set(false);
+        keplr.disconnect();
         // keplr.key.set(None);
     };
 
@@ -562,7 +587,7 @@ fn Modal() -> impl IntoView {
         info!("cleaning up <Modal/>");
     });
 
-    let keplr = use_context::<KeplrSignals>().expect("keplr signals context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
 
     let is_keplr_enabled = move || keplr.enabled.get();
     let my_address = move || {
@@ -1,4 +1,8 @@
+pub mod allowances;
+pub mod history;
 pub mod home;
 pub mod nav;
 pub mod pool;
+pub mod portfolio;
 pub mod trade;
+pub mod viewing_keys;
@@ -0,0 +1,135 @@
+use ammber_components::ErrorMessage;
+use ammber_core::{
+    allowance::{self, Allowance},
+    prelude::*,
+    state::*,
+    Account, TxManager, TxRecord,
+};
+use leptos::prelude::*;
+use lucide_leptos::Ban;
+use tracing::info;
+
+#[component]
+pub fn Allowances() -> impl IntoView {
+    info!("rendering <Allowances/>");
+
+    on_cleanup(move || {
+        info!("cleaning up <Allowances/>");
+    });
+
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
+    let account = use_context::<Account>().expect("account context missing!");
+
+    let revoke = Action::new_local(move |allowance: &Allowance| {
+        let allowance = allowance.clone();
+
+        async move {
+            let key = keplr.key.await?;
+            allowance::revoke(tx_manager, &key.bech32_address, &allowance).await
+        }
+    });
+
+    let allowances = LocalResource::new(move || {
+        // reload after a revoke settles
+        revoke.version().track();
+        account.track();
+
+        async move {
+            match keplr.key.await {
+                Ok(key) => allowance::outstanding(&key.bech32_address).await,
+                Err(_) => vec![],
+            }
+        }
+    });
+
+    let rows = move || {
+        Suspend::new(async move {
+            let allowances = allowances.await;
+
+            if allowances.is_empty() {
+                return view! {
+                    <p class="text-sm text-muted-foreground">"No outstanding allowances."</p>
+                }
+                .into_any();
+            }
+
+            allowances
+                .into_iter()
+                .map(|allowance| view! { <AllowanceRow allowance revoke /> })
+                .collect_view()
+                .into_any()
+        })
+    };
+
+    view! {
+        <div class="max-w-3xl mx-auto space-y-4">
+            <div>
+                <div class="text-3xl font-bold">"Allowances"</div>
+                <p class="text-sm text-muted-foreground">
+                    "Tokens the LB contracts can still spend on your behalf. Deposits only add
+                    what is missing, so anything left here is unused."
+                </p>
+            </div>
+            <Show
+                when=move || keplr.enabled.get()
+                fallback=|| {
+                    view! {
+                        <p class="text-sm text-muted-foreground">
+                            "Connect your wallet to manage allowances."
+                        </p>
+                    }
+                }
+            >
+                <Suspense fallback=|| view! { <div>"Loading..."</div> }>
+                    <ul class="m-0 p-0 list-none space-y-2">{rows}</ul>
+                </Suspense>
+                {move || {
+                    revoke
+                        .value()
+                        .get()
+                        .and_then(Result::err)
+                        .map(|error| {
+                            view! { <ErrorMessage error /> }
+                        })
+                }}
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn AllowanceRow(
+    allowance: Allowance,
+    revoke: Action<Allowance, Result<TxRecord, Error>, LocalStorage>,
+) -> impl IntoView {
+    let token_address = allowance.token.address.to_string();
+    let symbol = get_token_symbol(&token_address);
+    let decimals = get_token_decimals(&token_address).unwrap_or(0);
+
+    let spender = allowance::lb_spenders()
+        .into_iter()
+        .find(|(_, spender)| spender.address == allowance.spender.address)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| shorten_address(allowance.spender.address.as_str()));
+
+    let amount = display_token_amount(allowance.amount.u128(), decimals);
+    let allowance = StoredValue::new(allowance);
+
+    view! {
+        <li class="p-4 bg-card rounded-lg border border-solid space-y-1">
+            <div class="flex items-center justify-between gap-2">
+                <span class="font-semibold">{format!("{amount} {symbol}")}</span>
+                <button
+                    class="inline-flex gap-1 items-center py-1 px-2 text-xs bg-secondary text-secondary-foreground rounded-md"
+                    disabled=move || revoke.pending().get()
+                    on:click=move |_| _ = revoke.dispatch(allowance.get_value())
+                >
+                    <Ban size=12 />
+                    "Revoke"
+                </button>
+            </div>
+            <div class="text-xs text-muted-foreground">{format!("Spender: {spender}")}</div>
+        </li>
+    }
+}
@@ -0,0 +1,239 @@
+use ammber_components::ErrorMessage;
+use ammber_core::{
+    constants::contracts::LB_ROUTER, history, prelude::*, state::*, Account, TxManager, TxRecord,
+    TxStatus,
+};
+use leptos::prelude::*;
+use tracing::{debug, info};
+use web_sys::js_sys::Date;
+
+#[component]
+pub fn TxHistory() -> impl IntoView {
+    info!("rendering <TxHistory/>");
+
+    on_cleanup(move || {
+        info!("cleaning up <TxHistory/>");
+    });
+
+    let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
+    let account = use_context::<Account>().expect("account context missing!");
+
+    let (refresh, set_refresh) = signal(());
+
+    let records = LocalResource::new(move || {
+        // reload whenever a transaction settles
+        tx_manager.records().track();
+        refresh.track();
+        account.track();
+
+        async move {
+            let key = keplr.key.await?;
+            history::load(&key.bech32_address).await
+        }
+    });
+
+    let backfill = Action::new_local(move |_: &()| {
+        let url = endpoint.get();
+        async move {
+            let key = keplr.key.await?;
+            let added =
+                history::backfill(url, &key.bech32_address, LB_ROUTER.address.as_str()).await?;
+            debug!("added {added} transactions from the chain");
+            set_refresh.set(());
+            Ok::<usize, Error>(added)
+        }
+    });
+
+    let clear = Action::new_local(move |_: &()| async move {
+        let key = keplr.key.await?;
+        history::clear(&key.bech32_address).await?;
+        set_refresh.set(());
+        Ok::<(), Error>(())
+    });
+
+    view! {
+        <div class="max-w-3xl mx-auto space-y-4">
+            <div class="flex items-center justify-between">
+                <div>
+                    <div class="text-3xl font-bold">"History"</div>
+                    <p class="text-sm text-muted-foreground">
+                        "Transactions submitted from this browser."
+                    </p>
+                </div>
+                <div class="flex gap-2">
+                    <button
+                        class="py-1.5 px-3 text-sm bg-secondary text-secondary-foreground rounded-md"
+                        disabled=move || !keplr.enabled.get() || backfill.pending().get()
+                        on:click=move |_| _ = backfill.dispatch(())
+                    >
+                        {move || {
+                            if backfill.pending().get() { "Searching..." } else { "Search chain" }
+                        }}
+                    </button>
+                    <button
+                        class="py-1.5 px-3 text-sm bg-secondary text-secondary-foreground rounded-md"
+                        disabled=move || !keplr.enabled.get()
+                        on:click=move |_| _ = clear.dispatch(())
+                    >
+                        "Clear"
+                    </button>
+                </div>
+            </div>
+            {move || {
+                backfill
+                    .value()
+                    .get()
+                    .map(|result| match result {
+                        Ok(added) => {
+                            view! {
+                                <p class="text-sm text-muted-foreground">
+                                    {format!("Found {added} new transaction(s).")}
+                                </p>
+                            }
+                                .into_any()
+                        }
+                        Err(error) => {
+                            view! { <ErrorMessage error /> }
+                                .into_any()
+                        }
+                    })
+            }}
+            <Suspense fallback=|| view! { <div>"Loading..."</div> }>
+                {move || Suspend::new(async move {
+                    match records.await {
+                        Ok(records) if records.is_empty() => {
+                            view! {
+                                <p class="text-sm text-muted-foreground">"No transactions yet."</p>
+                            }
+                                .into_any()
+                        }
+                        Ok(records) => {
+                            view! {
+                                <ul class="m-0 p-0 list-none space-y-2">
+                                    {records
+                                        .into_iter()
+                                        .map(|record| view! { <HistoryItem record /> })
+                                        .collect_view()}
+                                </ul>
+                            }
+                                .into_any()
+                        }
+                        Err(Error::KeplrDisabled) => {
+                            view! {
+                                <p class="text-sm text-muted-foreground">
+                                    "Connect your wallet to see your history."
+                                </p>
+                            }
+                                .into_any()
+                        }
+                        Err(error) => {
+                            view! { <ErrorMessage error /> }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn HistoryItem(record: TxRecord) -> impl IntoView {
+    let tokens = record
+        .tokens
+        .iter()
+        .map(|token| {
+            let symbol = get_token_symbol(&token.address);
+            let decimals = get_token_decimals(&token.address).unwrap_or(0);
+
+            if token.amount.is_zero() {
+                symbol
+            } else {
+                format!("{} {symbol}", display_token_amount(token.amount, decimals))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" → ");
+
+    let (status, status_class) = match &record.status {
+        TxStatus::Simulating => ("Simulating", "text-muted-foreground"),
+        TxStatus::AwaitingApproval => ("Awaiting approval", "text-muted-foreground"),
+        TxStatus::Broadcasting => ("Broadcasting", "text-muted-foreground"),
+        TxStatus::Pending => ("Pending", "text-gold"),
+        TxStatus::Included => ("Confirmed", "text-foam"),
+        TxStatus::TimedOut => ("Timed out", "text-gold"),
+        TxStatus::Failed(_) => ("Failed", "text-destructive"),
+    };
+
+    let error = match &record.status {
+        TxStatus::Failed(error) => Some(error.to_string()),
+        _ => None,
+    };
+
+    let time = Date::new(&record.submitted_at.into())
+        .to_locale_string("default", &Default::default())
+        .as_string()
+        .unwrap_or_default();
+
+    view! {
+        <li class="p-4 bg-card rounded-lg border border-solid space-y-1">
+            <div class="flex items-center justify-between">
+                <span class="font-semibold">{record.kind.to_string()}</span>
+                <span class=format!("text-sm {status_class}")>{status}</span>
+            </div>
+            <div class="text-sm">{tokens}</div>
+            {record
+                .fee
+                .clone()
+                .map(|fee| {
+                    view! {
+                        <div class="text-xs text-muted-foreground">
+                            {format!("Fee {} SCRT ({}), {}", fee.scrt(), fee.tier, fee.payer())}
+                        </div>
+                    }
+                })}
+            {error.map(|error| view! { <ErrorMessage error /> })}
+            <div class="flex items-center justify-between text-xs text-muted-foreground">
+                <span>{time}</span>
+                {record
+                    .hash
+                    .clone()
+                    .map(|hash| {
+                        let label = format!(
+                            "{}{}",
+                            shorten_address(&hash),
+                            record.height.map(|height| format!(" @ {height}")).unwrap_or_default(),
+                        );
+                        match *EXPLORER {
+                            Some(explorer) => {
+                                view! {
+                                    <a
+                                        href=format!("{explorer}/tx/{hash}")
+                                        target="_blank"
+                                        rel="noopener"
+                                    >
+                                        {label}
+                                    </a>
+                                }
+                                    .into_any()
+                            }
+                            None => view! { <span>{label}</span> }.into_any(),
+                        }
+                    })}
+            </div>
+            {record
+                .logs
+                .clone()
+                .map(|logs| {
+                    view! {
+                        <details class="text-xs text-muted-foreground">
+                            <summary>"Logs"</summary>
+                            <pre class="whitespace-pre-wrap break-all">{logs}</pre>
+                        </details>
+                    }
+                })}
+        </li>
+    }
+}
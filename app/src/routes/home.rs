@@ -1,5 +1,4 @@
-use ammber_core::{state::*, types::Coin, Error, CHAIN_ID};
-use keplr::Keplr;
+use ammber_core::{state::*, types::Coin, Account, Error};
 use leptos::prelude::*;
 use rsecret::query::bank::BankQuerier;
 use send_wrapper::SendWrapper;
@@ -11,52 +10,11 @@ pub fn Home() -> impl IntoView {
     info!("rendering <Home/>");
 
     let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
-    let keplr = use_context::<KeplrSignals>().expect("keplr signals context missing!");
-    let token_map = use_context::<TokenMap>().expect("tokens context missing!");
-
-    let viewing_keys = LocalResource::new(move || {
-        let tokens = token_map.clone();
-        async move {
-            if keplr.enabled.get_untracked() {
-                debug!("gathering viewing_keys");
-                let mut keys = Vec::new();
-                for (_, token) in tokens.iter() {
-                    let key_result =
-                        Keplr::get_secret_20_viewing_key(CHAIN_ID, &token.contract_address).await;
-
-                    if let Ok(key) = key_result {
-                        keys.push((token.name.clone(), token.contract_address.clone(), key));
-                    }
-                }
-                debug!("Found {} viewing keys.", keys.len());
-                keys
-            } else {
-                vec![]
-            }
-        }
-    });
-
-    let viewing_keys_list = move || {
-        Suspend::new(async move {
-            viewing_keys
-                .await
-                .into_iter()
-                .map(|(name, address, key)| {
-                    view! {
-                        <li>
-                            <strong>{name}</strong>
-                            ", "
-                            {address}
-                            ": "
-                            {key}
-                        </li>
-                    }
-                })
-                .collect_view()
-        })
-    };
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let account = use_context::<Account>().expect("account context missing!");
 
     let user_balance = LocalResource::new(move || {
+        account.track();
         let client = Client::new(endpoint.get().to_string());
         async move {
             let bank = BankQuerier::new(client);
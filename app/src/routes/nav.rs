@@ -19,6 +19,12 @@ pub fn Nav() -> impl IntoView {
             >
                 "Pool"
             </A>
+            <A
+                href="/liquidity-book-leptos/portfolio"
+                attr:class="text-muted-foreground px-3 py-1.5 no-underline leading-none"
+            >
+                "Portfolio"
+            </A>
             <a
                 href="https://kent-3.github.io/liquidity-book/docs/"
                 target="_blank"
@@ -0,0 +1,402 @@
+use ammber_components::ErrorMessage;
+use ammber_core::{
+    position::{self, Position},
+    prelude::*,
+    price::{display_usd, Prices},
+    state::*,
+    support::chain_batch_query,
+    Account, TxManager, BASE_URL,
+};
+use ammber_pool::{remove_liquidity_from_pools, Withdrawal};
+use ammber_sdk::{
+    contract_interfaces::lb_pair::{self, ActiveIdResponse, LbPair, ReservesResponse},
+    utils::get_price_from_id,
+};
+use batch_query::{BatchItemResponseStatus, BatchQueryParams};
+use codee::string::FromToStringCodec;
+use futures::future::join_all;
+use leptos::prelude::*;
+use leptos_router::components::A;
+use leptos_use::storage::use_local_storage;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// A position in one pool, with what is needed to value it.
+#[derive(Clone)]
+struct Holding {
+    lb_pair: LbPair,
+    active_id: u32,
+    position: Position,
+    reserves: ReservesResponse,
+}
+
+impl Holding {
+    /// Raw units of token Y per raw unit of token X, at the active bin.
+    fn price(&self) -> f64 {
+        get_price_from_id(self.active_id.to_string(), self.lb_pair.bin_step)
+    }
+
+    /// The USD value of the position, if both tokens have a price.
+    fn usd_value(&self, prices: &Prices) -> Option<f64> {
+        let value_x = prices.value(
+            self.lb_pair.token_x.address().as_str(),
+            self.position.amount_x.u128(),
+        )?;
+        let value_y = prices.value(
+            self.lb_pair.token_y.address().as_str(),
+            self.position.amount_y.u128(),
+        )?;
+
+        Some(value_x + value_y)
+    }
+
+    /// The share of the pool's reserves that belongs to the position, between 0 and 1.
+    fn share(&self) -> f64 {
+        let price = self.price();
+        let total =
+            self.reserves.reserve_x.u128() as f64 * price + self.reserves.reserve_y.u128() as f64;
+
+        if total == 0.0 {
+            0.0
+        } else {
+            self.position.value_in_y(price) / total
+        }
+    }
+
+    /// Withdraws the whole position, accepting up to `slippage` basis points less of each token
+    /// than it holds now.
+    fn withdrawal(&self, slippage: u16) -> Withdrawal {
+        let kept = 10_000 - slippage.min(10_000);
+
+        Withdrawal {
+            lb_pair: self.lb_pair.clone(),
+            ids: self.position.ids.clone(),
+            amounts: self.position.amounts.clone(),
+            amount_x_min: self.position.amount_x.multiply_ratio(kept, 10_000u16),
+            amount_y_min: self.position.amount_y.multiply_ratio(kept, 10_000u16),
+        }
+    }
+}
+
+/// The positions found across every pair.
+struct Holdings {
+    holdings: Vec<Holding>,
+    /// How many pairs couldn't be read, and so might hold positions that aren't shown.
+    failed: usize,
+}
+
+/// Queries the active id and reserves of every pair in one batch. Pairs that fail to answer are
+/// left out, and counted.
+async fn pair_states(
+    lb_pairs: &[LbPair],
+) -> Result<(Vec<(LbPair, u32, ReservesResponse)>, usize), Error> {
+    let queries = lb_pairs
+        .iter()
+        .enumerate()
+        .flat_map(|(index, lb_pair)| {
+            [
+                ("active", lb_pair::QueryMsg::GetActiveId {}),
+                ("reserves", lb_pair::QueryMsg::GetReserves {}),
+            ]
+            .map(|(kind, query_msg)| BatchQueryParams {
+                id: format!("{index}:{kind}"),
+                contract: lb_pair.contract.clone(),
+                query_msg,
+            })
+        })
+        .collect();
+
+    let responses: HashMap<String, String> = chain_batch_query(queries)
+        .await?
+        .items
+        .into_iter()
+        .filter(|item| item.status == BatchItemResponseStatus::SUCCESS)
+        .map(|item| (item.id, item.response))
+        .collect();
+
+    let states: Vec<_> = lb_pairs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, lb_pair)| {
+            let active: ActiveIdResponse =
+                serde_json::from_str(responses.get(&format!("{index}:active"))?).ok()?;
+            let reserves: ReservesResponse =
+                serde_json::from_str(responses.get(&format!("{index}:reserves"))?).ok()?;
+            Some((lb_pair.clone(), active.active_id, reserves))
+        })
+        .collect();
+    let failed = lb_pairs.len() - states.len();
+
+    Ok((states, failed))
+}
+
+/// Finds the positions of `owner` in every pair. The balances of all the pairs are searched
+/// together, so each step of the search is one batch and one permit covers every pair.
+async fn holdings(owner: String, lb_pairs: Vec<LbPair>) -> Result<Holdings, Error> {
+    let (pairs, mut failed) = pair_states(&lb_pairs).await?;
+
+    let balances = position::discover_all(
+        &owner,
+        pairs
+            .iter()
+            .map(|(lb_pair, active_id, _)| (lb_pair.contract.clone(), *active_id))
+            .collect(),
+    )
+    .await;
+
+    let holdings = join_all(pairs.into_iter().zip(balances).map(
+        |((lb_pair, active_id, reserves), balances)| async move {
+            let (ids, amounts) = balances?;
+            if ids.is_empty() {
+                return Ok(None);
+            }
+            let position = position::from_balances(&lb_pair.contract, ids, amounts).await?;
+
+            Ok::<_, Error>(Some(Holding {
+                lb_pair,
+                active_id,
+                position,
+                reserves,
+            }))
+        },
+    ))
+    .await;
+
+    let holdings = holdings
+        .into_iter()
+        .filter_map(|holding| {
+            // one unreadable pool shouldn't hide the others
+            holding
+                .inspect_err(|error| {
+                    warn!("skipping a pool in the portfolio: {error}");
+                    failed += 1;
+                })
+                .ok()
+                .flatten()
+        })
+        .collect();
+
+    Ok(Holdings { holdings, failed })
+}
+
+#[component]
+pub fn Portfolio() -> impl IntoView {
+    info!("rendering <Portfolio/>");
+
+    on_cleanup(move || {
+        info!("cleaning up <Portfolio/>");
+    });
+
+    let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
+    let account = use_context::<Account>().expect("account context missing!");
+    let all_lb_pairs = use_context::<LocalResource<Vec<LbPair>>>()
+        .expect("missing the all_lb_pairs resource context");
+    let prices =
+        use_context::<LocalResource<Prices>>().expect("missing the prices resource context");
+
+    let holdings = LocalResource::new(move || {
+        account.track();
+
+        async move {
+            let key = keplr.key.await?;
+            let lb_pairs = all_lb_pairs.await;
+            holdings(key.bech32_address, lb_pairs).await
+        }
+    });
+
+    // the same amount slippage the pool pages use, and their default
+    let (amount_slippage, _, _) = use_local_storage::<u16, FromToStringCodec>("amount_slippage");
+
+    // every position comes out in one transaction
+    let withdraw_all = Action::new_local(move |positions: &Vec<Holding>| {
+        let url = endpoint.get();
+        let slippage = match amount_slippage.get_untracked() {
+            0 => 50,
+            slippage => slippage,
+        };
+        let withdrawals = positions
+            .iter()
+            .map(|holding| holding.withdrawal(slippage))
+            .collect();
+
+        async move {
+            remove_liquidity_from_pools(tx_manager, url, withdrawals).await?;
+            holdings.refetch();
+            Ok::<(), Error>(())
+        }
+    });
+
+    let rows = move || {
+        Suspend::new(async move {
+            let Holdings { holdings, failed } = match holdings.await {
+                Ok(holdings) => holdings,
+                Err(error) => {
+                    return view! { <ErrorMessage error /> }.into_any();
+                }
+            };
+
+            let notice = (failed > 0).then(|| {
+                view! {
+                    <li class="text-sm text-gold">
+                        {format!(
+                            "{failed} of the pools could not be loaded, so some positions may be missing.",
+                        )}
+                    </li>
+                }
+            });
+
+            if holdings.is_empty() {
+                return view! {
+                    {notice}
+                    <p class="text-sm text-muted-foreground">"You have no liquidity in any pool."</p>
+                }
+                .into_any();
+            }
+
+            let prices = prices.await;
+            let values: Vec<Option<f64>> = holdings
+                .iter()
+                .map(|holding| holding.usd_value(&prices))
+                .collect();
+            let total: f64 = values.iter().flatten().sum();
+            let unpriced = values.iter().filter(|value| value.is_none()).count();
+            let total = if unpriced > 0 {
+                format!(
+                    "Total value: {} (partial, {unpriced} of the positions have no price)",
+                    display_usd(total),
+                )
+            } else {
+                format!("Total value: {}", display_usd(total))
+            };
+
+            let all = holdings.clone();
+
+            view! {
+                {notice}
+                <li class="flex items-center justify-between gap-2 text-sm text-muted-foreground">
+                    <span>{total}</span>
+                    <button
+                        class="py-1 px-3 bg-secondary text-secondary-foreground rounded-md"
+                        disabled=move || withdraw_all.pending().get()
+                        on:click=move |_| _ = withdraw_all.dispatch(all.clone())
+                    >
+                        {move || {
+                            if withdraw_all.pending().get() { "Withdrawing..." } else { "Withdraw all" }
+                        }}
+                    </button>
+                </li>
+                {move || {
+                    withdraw_all
+                        .value()
+                        .get()
+                        .and_then(Result::err)
+                        .map(|error| view! { <ErrorMessage error /> })
+                }}
+                {holdings
+                    .into_iter()
+                    .map(|holding| {
+                        let prices = prices.clone();
+                        view! { <HoldingRow holding prices /> }
+                    })
+                    .collect_view()}
+            }
+            .into_any()
+        })
+    };
+
+    view! {
+        <div class="max-w-3xl mx-auto space-y-4">
+            <div>
+                <div class="text-3xl font-bold">"Portfolio"</div>
+                <p class="text-sm text-muted-foreground">"Your liquidity across every pool."</p>
+            </div>
+            <Show
+                when=move || keplr.enabled.get()
+                fallback=|| {
+                    view! {
+                        <p class="text-sm text-muted-foreground">
+                            "Connect your wallet to see your positions."
+                        </p>
+                    }
+                }
+            >
+                <Suspense fallback=|| view! { <div>"Loading..."</div> }>
+                    <ul class="m-0 p-0 list-none space-y-2">{rows}</ul>
+                </Suspense>
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn HoldingRow(holding: Holding, prices: Prices) -> impl IntoView {
+    let address_x = holding.lb_pair.token_x.address().to_string();
+    let address_y = holding.lb_pair.token_y.address().to_string();
+    let symbol_x = get_token_symbol(&address_x);
+    let symbol_y = get_token_symbol(&address_y);
+    let decimals_x = get_token_decimals(&address_x).unwrap_or(0);
+    let decimals_y = get_token_decimals(&address_y).unwrap_or(0);
+    let bin_step = holding.lb_pair.bin_step;
+
+    let position = &holding.position;
+    let bins = format!(
+        "Bins {} - {} ({} with liquidity)",
+        position.ids.first().unwrap(),
+        position.ids.last().unwrap(),
+        position.ids.len(),
+    );
+    let status = if position.is_in_range(holding.active_id) {
+        "In range"
+    } else {
+        "Out of range"
+    };
+    let amount_x = display_token_amount(position.amount_x.u128(), decimals_x);
+    let amount_y = display_token_amount(position.amount_y.u128(), decimals_y);
+    // without a USD price, the value is shown in token Y
+    let value = holding
+        .usd_value(&prices)
+        .map(display_usd)
+        .unwrap_or_else(|| {
+            let value = position.value_in_y(holding.price()) as u128;
+            format!("{} {symbol_y}", display_token_amount(value, decimals_y))
+        });
+    let share = format!("{:.4}%", holding.share() * 100.0);
+
+    let manage = format!("{BASE_URL}/pool/{address_x}/{address_y}/{bin_step}/manage");
+
+    view! {
+        <li class="p-4 bg-card rounded-lg border border-solid space-y-2">
+            <div class="flex items-center justify-between gap-2">
+                <span class="font-semibold">
+                    {format!("{symbol_x} / {symbol_y} ({bin_step} bps)")}
+                </span>
+                <span class="text-xs text-muted-foreground">{status}</span>
+            </div>
+            <div class="text-xs text-muted-foreground">{bins}</div>
+            <div class="grid grid-cols-3 gap-2 text-sm">
+                <div>
+                    <div class="text-xs text-muted-foreground">"Deposited"</div>
+                    <div>{format!("{amount_x} {symbol_x}")}</div>
+                    <div>{format!("{amount_y} {symbol_y}")}</div>
+                </div>
+                <div>
+                    <div class="text-xs text-muted-foreground">"Value"</div>
+                    <div>{value}</div>
+                </div>
+                <div>
+                    <div class="text-xs text-muted-foreground">"Pool share"</div>
+                    <div>{share}</div>
+                </div>
+            </div>
+            <div class="flex gap-2 text-xs">
+                <A href=format!("{manage}/add")>"Add"</A>
+                <A href=format!("{manage}/remove")>"Remove"</A>
+                <A href=format!("{BASE_URL}/pool/{address_x}/{address_y}/{bin_step}/analytics")>
+                    "Analytics"
+                </A>
+            </div>
+        </li>
+    }
+}
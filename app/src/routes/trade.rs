@@ -1,4 +1,4 @@
-use ammber_core::{Endpoint, KeplrSignals, TokenMap};
+use ammber_core::{Endpoint, TokenMap, WalletSignals};
 use ammber_swap::Swap;
 use leptos::prelude::*;
 use tracing::{debug, info};
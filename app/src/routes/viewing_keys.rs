@@ -0,0 +1,185 @@
+use ammber_components::ErrorMessage;
+use ammber_core::{
+    prelude::*,
+    state::*,
+    viewing_key::{self, TokenStandard},
+    Account, TxManager,
+};
+use ammber_sdk::contract_interfaces::lb_pair::LbPair;
+use cosmwasm_std::ContractInfo;
+use leptos::prelude::*;
+use leptos_use::{use_clipboard, UseClipboardReturn};
+use lucide_leptos::{Copy, RefreshCw};
+use tracing::info;
+
+#[component]
+pub fn ViewingKeys() -> impl IntoView {
+    info!("rendering <ViewingKeys/>");
+
+    on_cleanup(move || {
+        info!("cleaning up <ViewingKeys/>");
+    });
+
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let all_lb_pairs = use_context::<LocalResource<Vec<LbPair>>>()
+        .expect("missing the all_lb_pairs resource context");
+
+    let tokens = move || {
+        TOKEN_MAP
+            .values()
+            .map(|token| {
+                view! {
+                    <KeyRow
+                        name=get_token_symbol(&token.contract_address)
+                        contract=ContractInfo::from(token)
+                        standard=TokenStandard::Snip20
+                    />
+                }
+            })
+            .collect_view()
+    };
+
+    let lb_pairs = move || {
+        Suspend::new(async move {
+            all_lb_pairs
+                .await
+                .into_iter()
+                .map(|lb_pair| {
+                    let name = format!(
+                        "{} / {} ({} bps)",
+                        get_token_symbol(lb_pair.token_x.address().as_str()),
+                        get_token_symbol(lb_pair.token_y.address().as_str()),
+                        lb_pair.bin_step,
+                    );
+                    view! {
+                        <KeyRow
+                            name
+                            contract=lb_pair.contract
+                            standard=TokenStandard::Snip1155
+                        />
+                    }
+                })
+                .collect_view()
+        })
+    };
+
+    view! {
+        <div class="max-w-3xl mx-auto space-y-4">
+            <div>
+                <div class="text-3xl font-bold">"Viewing Keys"</div>
+                <p class="text-sm text-muted-foreground">
+                    "Balances are read with query permits where possible. Tokens without permit
+                    support need a viewing key."
+                </p>
+            </div>
+            <Show
+                when=move || keplr.enabled.get()
+                fallback=|| {
+                    view! {
+                        <p class="text-sm text-muted-foreground">
+                            "Connect your wallet to manage viewing keys."
+                        </p>
+                    }
+                }
+            >
+                <div class="text-base font-semibold">"Tokens"</div>
+                <ul class="m-0 p-0 list-none space-y-2">{tokens}</ul>
+                <div class="text-base font-semibold">"LB Pairs"</div>
+                <Suspense fallback=|| view! { <div>"Loading..."</div> }>
+                    <ul class="m-0 p-0 list-none space-y-2">{lb_pairs}</ul>
+                </Suspense>
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn KeyRow(name: String, contract: ContractInfo, standard: TokenStandard) -> impl IntoView {
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
+    let account = use_context::<Account>().expect("account context missing!");
+
+    let UseClipboardReturn { copy, .. } = use_clipboard();
+
+    let contract = StoredValue::new(contract);
+
+    let create = Action::new_local(move |_: &()| async move {
+        let key = keplr.key.await?;
+        viewing_key::create(
+            tx_manager,
+            &key.bech32_address,
+            &contract.get_value(),
+            standard,
+        )
+        .await
+    });
+
+    let viewing_key = LocalResource::new(move || {
+        // reload after a key is created or rotated
+        create.version().track();
+        account.track();
+
+        async move {
+            let key = keplr.key.await.ok()?;
+            viewing_key::get(&key.bech32_address, contract.read_value().address.as_str()).await
+        }
+    });
+
+    view! {
+        <li class="p-4 bg-card rounded-lg border border-solid space-y-1">
+            <div class="flex items-center justify-between gap-2">
+                <span class="font-semibold">{name}</span>
+                <Suspense fallback=|| view! { <span class="text-sm">"Loading..."</span> }>
+                    {move || {
+                        let copy = copy.clone();
+                        Suspend::new(async move {
+                            let key = viewing_key.await;
+                            let has_key = key.is_some();
+
+                            view! {
+                                <div class="flex items-center gap-2">
+                                    {key
+                                        .map(|key| {
+                                            let short_key = shorten_address(&key);
+                                            view! {
+                                                <code class="text-xs text-muted-foreground">
+                                                    {short_key}
+                                                </code>
+                                                <button
+                                                    title="Copy viewing key"
+                                                    class="p-1 bg-transparent border-none text-muted-foreground hover:text-foreground"
+                                                    on:click=move |_| copy(&key)
+                                                >
+                                                    <Copy size=14 />
+                                                </button>
+                                            }
+                                        })}
+                                    <button
+                                        class="inline-flex gap-1 items-center py-1 px-2 text-xs bg-secondary text-secondary-foreground rounded-md"
+                                        disabled=move || create.pending().get()
+                                        on:click=move |_| _ = create.dispatch(())
+                                    >
+                                        <RefreshCw size=12 />
+                                        {if has_key { "Rotate" } else { "Create" }}
+                                    </button>
+                                </div>
+                            }
+                        })
+                    }}
+                </Suspense>
+            </div>
+            <div class="text-xs text-muted-foreground">
+                {move || shorten_address(contract.read_value().address.as_str())}
+            </div>
+            {move || {
+                create
+                    .value()
+                    .get()
+                    .and_then(Result::err)
+                    .map(|error| {
+                        view! { <ErrorMessage error /> }
+                    })
+            }}
+        </li>
+    }
+}
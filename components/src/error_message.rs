@@ -0,0 +1,15 @@
+use leptos::prelude::*;
+use std::fmt::Display;
+
+/// Shows an error from a failed query or action.
+#[component]
+pub fn ErrorMessage(
+    error: impl Display,
+    /// Extra classes, e.g. for spacing.
+    #[prop(optional)]
+    class: &'static str,
+) -> impl IntoView {
+    let class = format!("{class} text-sm text-destructive");
+
+    view! { <p class=class.trim_start().to_string()>{error.to_string()}</p> }
+}
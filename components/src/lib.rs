@@ -1,17 +1,21 @@
 // #![allow(unused)]
 
+mod error_message;
 mod loading_modal;
 mod secret20_balance;
 mod secret_query;
 mod spinner;
 mod spinner2;
 mod suggest_chains;
+mod tx_approval;
 mod user_menu;
 
+pub use error_message::ErrorMessage;
 pub use loading_modal::LoadingModal;
 pub use secret20_balance::Secret20Balance;
 pub use secret_query::SecretQuery;
 pub use spinner::Spinner;
 pub use spinner2::Spinner2;
 pub use suggest_chains::SuggestChains;
+pub use tx_approval::TxApproval;
 pub use user_menu::WalletMenu;
@@ -2,11 +2,14 @@
 
 use ammber_core::{
     constants::{self, CHAIN_ID, NODE, TOKEN_MAP},
-    state::{ChainId, Endpoint, KeplrSignals, TokenMap},
+    permit,
+    state::{Balances, ChainId, Endpoint, TokenMap, WalletSignals},
     utils::alert,
+    viewing_key::{self, TokenStandard},
+    TxManager,
 };
 use cosmwasm_std::Uint128;
-use keplr::Keplr;
+use keplr::wallet;
 use leptos::{either::EitherOf4, logging::*, prelude::*};
 use lucide_leptos::{Eye, EyeClosed};
 use rsecret::query::compute::ComputeQuerier;
@@ -16,13 +19,13 @@ use tonic_web_wasm_client::Client as WebWasmClient;
 use tracing::{debug, trace};
 use web_sys::MouseEvent;
 
-// TODO: add a way to "suggest_token" if "there is no matched secret20"
-
 #[component]
 pub fn Secret20Balance(#[prop(into)] token_address: Signal<Option<String>>) -> impl IntoView {
     let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
     let chain_id = use_context::<ChainId>().expect("chain_id context missing!");
-    let keplr = use_context::<KeplrSignals>().expect("keplr context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let balances = use_context::<Balances>().expect("balances context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
 
     let token_symbol = move || {
         token_address
@@ -31,16 +34,25 @@ pub fn Secret20Balance(#[prop(into)] token_address: Signal<Option<String>>) -> i
             .map(|t| t.symbol)
     };
 
-    let suggest_token = Action::new_local(move |contract_address: &String| {
-        let chain_id = chain_id.chain_id.get();
+    // For tokens without permit support that the wallet has no viewing key for. The new key is
+    // registered with the wallet, so this only needs to happen once.
+    let create_viewing_key = Action::new_local(move |contract_address: &String| {
         let contract_address = contract_address.clone();
-        // This still doesn't work right. It only works if the user already has the token in their wallet
-        // let viewing_key = Some("hola");
 
         async move {
-            Keplr::suggest_token(&chain_id, &contract_address, None)
-                .await
-                .is_ok()
+            let key = wallet::active().get_key(CHAIN_ID).await?;
+            let token = TOKEN_MAP
+                .get(&contract_address)
+                .ok_or(ammber_core::Error::UnknownToken)?;
+
+            viewing_key::create(
+                tx_manager,
+                &key.bech32_address,
+                &token.into(),
+                TokenStandard::Snip20,
+            )
+            .await
+            .map(|_| ())
         }
     });
 
@@ -50,12 +62,11 @@ pub fn Secret20Balance(#[prop(into)] token_address: Signal<Option<String>>) -> i
                 keplr.enabled.get(),
                 keplr.key.get(),
                 token_address.get(),
-                suggest_token.value().get(), // this will reload the resource after the token is added
+                create_viewing_key.value().get(), // this will reload the resource after the key is created
+                token_address.get().map(|address| balances.track(&address)),
             )
         },
-        move |(enabled, maybe_key, maybe_contract_address, token_suggested)| {
-            let endpoint = endpoint.get();
-            let chain_id = chain_id.get();
+        move |(enabled, maybe_key, maybe_contract_address, key_created, _)| {
             SendWrapper::new({
                 async move {
                     if !enabled {
@@ -67,12 +78,13 @@ pub fn Secret20Balance(#[prop(into)] token_address: Signal<Option<String>>) -> i
                     let token = TOKEN_MAP
                         .get(&contract_address)
                         .ok_or(Error::UnknownToken)?;
-                    let vk = Keplr::get_secret_20_viewing_key(&chain_id, &contract_address)
+                    // Uses a query permit, or the wallet's viewing key if the token has no
+                    // permit support.
+                    permit::snip20_balance(&key.bech32_address, &token.into())
                         .await
+                        .map(|amount| amount.humanize(token.decimals))
                         .inspect_err(|err| error!("{err:?}"))
-                        .map_err(|err| Error::Generic(err.to_string()))?;
-                    debug!("Found viewing key for {}: {}", token.symbol, vk);
-                    query_snip20_balance(key, token.clone(), vk, endpoint.to_string()).await
+                        .map_err(Error::from)
                 }
             })
         },
@@ -125,19 +137,25 @@ pub fn Secret20Balance(#[prop(into)] token_address: Signal<Option<String>>) -> i
                                     <div class="group relative leading-none">
                                         <button
                                             on:click=move |_| {
-                                                _ = suggest_token
+                                                _ = create_viewing_key
                                                     .dispatch_local(token_address.get().unwrap_or_default());
                                             }
                                             class="p-0 border-none inline-flex items-center gap-1 cursor-default text-ellipsis text-sm text-muted-foreground"
                                         >
                                             <EyeClosed size=16 />
-                                            "View Balance"
+                                            {move || {
+                                                if create_viewing_key.pending().get() {
+                                                    "Creating key..."
+                                                } else {
+                                                    "View Balance"
+                                                }
+                                            }}
                                         </button>
                                         <div class="absolute bottom-full left-1/2 -translate-x-1/2 mb-0 px-2 py-1
                                         invisible group-hover:visible opacity-0 group-hover:opacity-100 transition-opacity duration-100 ease-in
                                         border border-solid border-border
                                         bg-popover text-popover-foreground text-xs font-semibold rounded-md whitespace-nowrap">
-                                            "Add " {token_symbol()} " to wallet"
+                                            "Create a viewing key for " {token_symbol()}
                                         </div>
                                     </div>
                                 },
@@ -201,7 +219,7 @@ pub async fn query_snip20_balance(
 ) -> Result<String, Error> {
     let compute = ComputeQuerier::new(
         WebWasmClient::new(endpoint),
-        Keplr::get_enigma_utils(CHAIN_ID).into(),
+        wallet::active().get_enigma_utils(CHAIN_ID)?.into(),
     );
 
     // TODO: make rsecret do this part?
@@ -282,6 +300,24 @@ impl From<SerdeJsonError> for Error {
     }
 }
 
+impl From<ammber_core::Error> for Error {
+    fn from(err: ammber_core::Error) -> Self {
+        match err {
+            ammber_core::Error::KeplrDisabled => Error::KeplrDisabled,
+            ammber_core::Error::UnknownToken => Error::UnknownToken,
+            // Keep Keplr's own message, so "There is no matched secret20" can be recognized.
+            ammber_core::Error::Keplr(msg) => Error::Generic(msg),
+            err => Error::SecretClient(err.to_string()),
+        }
+    }
+}
+
+impl From<keplr::Error> for Error {
+    fn from(err: keplr::Error) -> Self {
+        Error::Generic(err.to_string())
+    }
+}
+
 impl From<SecretError> for Error {
     fn from(err: SecretError) -> Self {
         Error::SecretClient(err.to_string())
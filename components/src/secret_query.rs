@@ -2,10 +2,10 @@
 
 use ammber_core::{
     constants::{CHAIN_ID, NODE, TOKEN_MAP},
-    state::{ChainId, Endpoint, KeplrSignals, TokenMap},
+    state::{ChainId, Endpoint, TokenMap, WalletSignals},
     Error,
 };
-use keplr::Keplr;
+use keplr::wallet;
 use leptos::either::Either;
 use leptos::prelude::*;
 use rsecret::query::compute::ComputeQuerier;
@@ -63,7 +63,7 @@ async fn do_query<T: Serialize + Send + Sync>(
 ) -> Result<String, Error> {
     let compute = ComputeQuerier::new(
         WebWasmClient::new(endpoint),
-        Keplr::get_enigma_utils(CHAIN_ID).into(),
+        wallet::active().get_enigma_utils(CHAIN_ID)?.into(),
     );
 
     // TODO: make rsecret do this part internally?
@@ -1,89 +1,9 @@
+use ammber_core::constants::{DEVNET_CHAIN_INFO, PULSAR_CHAIN_INFO};
 use keplr::{experimental::*, Keplr};
 use leptos::{logging::*, prelude::*};
-use std::sync::LazyLock;
 use tracing::{debug, trace};
 use web_sys::{js_sys, wasm_bindgen::JsValue, MouseEvent};
 
-static DEVNET: LazyLock<ChainInfo> = LazyLock::new(|| ChainInfo {
-    chain_id: "secretdev-1".to_string(),
-    chain_name: "localsecret".to_string(),
-    rpc: "http://127.0.0.1:26657".to_string(),
-    rest: "http://127.0.0.1:1317".to_string(),
-    bip44: Bip44 { coin_type: 529 },
-    bech32_config: Bech32Config {
-        bech32_prefix_acc_addr: "secret".to_string(),
-        bech32_prefix_acc_pub: "secretpub".to_string(),
-        bech32_prefix_val_addr: "secretvaloper".to_string(),
-        bech32_prefix_val_pub: "secretvaloperpub".to_string(),
-        bech32_prefix_cons_addr: "secretvalcons".to_string(),
-        bech32_prefix_cons_pub: "secretvalconspub".to_string(),
-    },
-    currencies: vec![Currency {
-        coin_denom: "SCRT".to_string(),
-        coin_minimal_denom: "uscrt".to_string(),
-        coin_decimals: 6,
-        coin_gecko_id: "secret".to_string(),
-    }],
-    fee_currencies: vec![FeeCurrency {
-        coin_denom: "SCRT".to_string(),
-        coin_minimal_denom: "uscrt".to_string(),
-        coin_decimals: 6,
-        coin_gecko_id: "secret".to_string(),
-        gas_price_step: GasPriceStep {
-            low: 0.1,
-            average: 0.25,
-            high: 0.5,
-        },
-    }],
-    stake_currency: Currency {
-        coin_denom: "SCRT".to_string(),
-        coin_minimal_denom: "uscrt".to_string(),
-        coin_decimals: 6,
-        coin_gecko_id: "secret".to_string(),
-    },
-    features: vec!["secretwasm".to_string()],
-});
-
-static TESTNET: LazyLock<ChainInfo> = LazyLock::new(|| ChainInfo {
-    chain_id: "pulsar-3".to_string(),
-    chain_name: "Pulsar".to_string(),
-    rpc: "https://rpc.pulsar.scrttestnet.com".to_string(),
-    rest: "https://api.pulsar.scrttestnet.com".to_string(),
-    bip44: Bip44 { coin_type: 529 },
-    bech32_config: Bech32Config {
-        bech32_prefix_acc_addr: "secret".to_string(),
-        bech32_prefix_acc_pub: "secretpub".to_string(),
-        bech32_prefix_val_addr: "secretvaloper".to_string(),
-        bech32_prefix_val_pub: "secretvaloperpub".to_string(),
-        bech32_prefix_cons_addr: "secretvalcons".to_string(),
-        bech32_prefix_cons_pub: "secretvalconspub".to_string(),
-    },
-    currencies: vec![Currency {
-        coin_denom: "SCRT".to_string(),
-        coin_minimal_denom: "uscrt".to_string(),
-        coin_decimals: 6,
-        coin_gecko_id: "secret".to_string(),
-    }],
-    fee_currencies: vec![FeeCurrency {
-        coin_denom: "SCRT".to_string(),
-        coin_minimal_denom: "uscrt".to_string(),
-        coin_decimals: 6,
-        coin_gecko_id: "secret".to_string(),
-        gas_price_step: GasPriceStep {
-            low: 0.1,
-            average: 0.25,
-            high: 0.5,
-        },
-    }],
-    stake_currency: Currency {
-        coin_denom: "SCRT".to_string(),
-        coin_minimal_denom: "uscrt".to_string(),
-        coin_decimals: 6,
-        coin_gecko_id: "secret".to_string(),
-    },
-    features: vec!["secretwasm".to_string()],
-});
-
 #[component]
 pub fn SuggestChains() -> impl IntoView {
     let suggest_chain_action: Action<ChainInfo, bool, LocalStorage> =
@@ -114,10 +34,10 @@ pub fn SuggestChains() -> impl IntoView {
         });
 
     let suggest_testnet = move |_: MouseEvent| {
-        let _ = suggest_chain_action.dispatch(TESTNET.clone());
+        let _ = suggest_chain_action.dispatch(PULSAR_CHAIN_INFO.clone());
     };
     let suggest_devnet = move |_: MouseEvent| {
-        let _ = suggest_chain_action.dispatch(DEVNET.clone());
+        let _ = suggest_chain_action.dispatch(DEVNET_CHAIN_INFO.clone());
     };
 
     view! {
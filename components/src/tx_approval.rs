@@ -0,0 +1,94 @@
+use ammber_core::{gas::FeeTier, TxManager, TxStatus};
+use leptos::{html::Dialog, prelude::*};
+
+/// Shows the simulated fee of each transaction at every tier, and waits for the user to confirm
+/// it before the wallet is asked to sign.
+#[component]
+pub fn TxApproval() -> impl IntoView {
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
+
+    let dialog_ref = NodeRef::<Dialog>::new();
+    let (tier, set_tier) = signal(FeeTier::default());
+
+    // the oldest transaction waiting goes first
+    let awaiting = Memo::new(move |_| {
+        tx_manager
+            .records()
+            .get()
+            .into_iter()
+            .rev()
+            .find(|record| record.status == TxStatus::AwaitingApproval)
+    });
+
+    Effect::new(move |_| {
+        if let Some(dialog) = dialog_ref.get() {
+            match awaiting.get().and_then(|record| record.fee) {
+                Some(fee) => {
+                    set_tier.set(fee.tier);
+                    let _ = dialog.show_modal();
+                }
+                None => dialog.close(),
+            }
+        }
+    });
+
+    // closing the dialog with Escape cancels the transaction
+    let cancel = move || {
+        if let Some(record) = awaiting.get_untracked() {
+            tx_manager.reject(record.id);
+        }
+    };
+
+    view! {
+        <dialog
+            node_ref=dialog_ref
+            on:cancel=move |_| cancel()
+            class="w-80 px-6 py-4 shadow-md bg-popover text-popover-foreground rounded-lg border border-solid border-border"
+        >
+            {move || {
+                awaiting
+                    .get()
+                    .and_then(|record| Some((record.id, record.kind, record.fee?)))
+                    .map(|(id, kind, estimate)| {
+                        view! {
+                            <div class="space-y-3">
+                                <div class="font-bold">{format!("Confirm {kind}")}</div>
+                                <div class="text-xs text-muted-foreground">
+                                    {format!("Gas limit {}, {}", estimate.gas_limit, estimate.payer())}
+                                </div>
+                                <div class="flex gap-1">
+                                    {FeeTier::ALL
+                                        .into_iter()
+                                        .map(|option| {
+                                            let fee = estimate.with_tier(option);
+                                            view! {
+                                                <button
+                                                    class="flex-1 py-1 px-2 text-xs bg-secondary text-secondary-foreground rounded-md"
+                                                    class=("font-bold", move || tier.get() == option)
+                                                    on:click=move |_| set_tier.set(option)
+                                                >
+                                                    <div>{option.to_string()}</div>
+                                                    <div>{format!("{} SCRT", fee.scrt())}</div>
+                                                </button>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </div>
+                                <div class="flex gap-2">
+                                    <button class="flex-1" on:click=move |_| cancel()>
+                                        "Cancel"
+                                    </button>
+                                    <button
+                                        class="flex-1"
+                                        on:click=move |_| tx_manager.approve(id, tier.get_untracked())
+                                    >
+                                        "Confirm"
+                                    </button>
+                                </div>
+                            </div>
+                        }
+                    })
+            }}
+        </dialog>
+    }
+}
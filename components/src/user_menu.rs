@@ -1,15 +1,23 @@
 #![allow(unused)]
 
+use crate::ErrorMessage;
 use ammber_core::{
+    account::{handle_key_store_change, Account},
     constants::{CHAIN_ID, NODE, TOKEN_MAP},
-    state::{ChainId, Endpoint, KeplrSignals, TokenMap},
+    gas::FeeTier,
+    state::{Balances, ChainId, Endpoint, TokenMap, WalletSignals},
     types::Coin,
     utils::*,
-    Error, BASE_URL,
+    Error, TxManager, TxStatus, BASE_URL,
+};
+#[cfg(feature = "devnet")]
+use keplr::dev;
+use keplr::WalletKind;
+use leptos::{either::Either, html, logging::*, prelude::*, task::spawn_local};
+use lucide_leptos::{
+    ArrowLeft, ChevronRight, History, KeyRound, Power, Settings, ShieldCheck, Wallet, WavesLadder,
+    X,
 };
-use keplr::Keplr;
-use leptos::{either::Either, html, logging::*, prelude::*};
-use lucide_leptos::{ArrowLeft, ChevronRight, History, Power, Settings, Wallet, WavesLadder};
 use rsecret::query::{bank::BankQuerier, compute::ComputeQuerier};
 use send_wrapper::SendWrapper;
 use tonic_web_wasm_client::Client;
@@ -27,14 +35,39 @@ pub fn WalletMenu(
 
     let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
     let chain_id = use_context::<ChainId>().expect("chain id context missing!");
-    let keplr = use_context::<KeplrSignals>().expect("keplr signals context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
     let token_map = use_context::<TokenMap>().expect("tokens context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
+    let balances = use_context::<Balances>().expect("balances context missing!");
+    let account = use_context::<Account>().expect("account context missing!");
 
     let disable_keplr = move |_: MouseEvent| {
-        Keplr::disable(CHAIN_ID);
-        keplr.enabled.set(false);
+        keplr.disconnect();
     };
 
+    // Switching disconnects the current wallet before asking the new one to connect.
+    let switch_wallet = Action::new_local(move |kind: &WalletKind| {
+        let kind = *kind;
+        async move {
+            keplr.select(kind);
+            keplr.connect().await
+        }
+    });
+
+    // the fee tiers are priced with the gas of the last simulated transaction
+    let last_fee = Memo::new(move |_| {
+        tx_manager
+            .records()
+            .get()
+            .into_iter()
+            .find_map(|record| record.fee)
+    });
+
+    #[cfg(feature = "devnet")]
+    let dev_accounts = view! { <DevAccounts /> };
+    #[cfg(not(feature = "devnet"))]
+    let dev_accounts = ();
+
     let key_address = move || {
         keplr
             .key
@@ -43,17 +76,26 @@ pub fn WalletMenu(
             .map(|key| key.bech32_address)
     };
 
-    // Note: this resource is running twice for some reason! at least here I can imagine it's due
-    // to the AsyncDerived signal of keplr.key
+    // look for a fee grant whenever the account changes, so the menu can say who pays
+    Effect::new(move |_| {
+        if let Some(address) = key_address() {
+            spawn_local(async move {
+                tx_manager.check_fee_grant(&address).await;
+            });
+        }
+    });
+
+    // The source is the address rather than the key, so re-deriving the key for the same account
+    // (on reconnect, or a key store change that keeps the account) doesn't refetch.
     let user_balance = Resource::new(
-        move || keplr.key.get(),
-        move |key| {
+        move || (key_address(), balances.track("uscrt")),
+        move |(address, _)| {
             let client = Client::new(endpoint.get().to_string());
             SendWrapper::new(async move {
-                if let Some(Ok(key)) = key {
+                if let Some(address) = address {
                     let bank = BankQuerier::new(client);
 
-                    bank.balance(key.bech32_address, "uscrt")
+                    bank.balance(address, "uscrt")
                         .await
                         .map(|balance| Coin::from(balance.balance.unwrap()))
                         .map_err(Error::from)
@@ -101,6 +143,28 @@ pub fn WalletMenu(
             node_ref=dialog_ref
             class="z-50 mr-0 mt-2 px-0 py-3 w-80 shadow-md bg-popover text-popover-foreground rounded-lg border border-solid border-border"
         >
+            {move || {
+                account
+                    .notice()
+                    .get()
+                    .map(|switch| {
+                        view! {
+                            <div class="flex items-center justify-between gap-2 mx-3 mb-3 py-2 px-3 text-sm bg-secondary rounded-md">
+                                <span>
+                                    "Switched to " {switch.name} " ("
+                                    {shorten_address(switch.to)} ")"
+                                </span>
+                                <button
+                                    title="Dismiss"
+                                    class="p-0 bg-transparent border-none text-muted-foreground hover:text-foreground"
+                                    on:click=move |_| account.dismiss()
+                                >
+                                    <X size=14 />
+                                </button>
+                            </div>
+                        }
+                    })
+            }}
             <Show when=move || contents.get() == "activity">
                 <div class="flex items-center px-2 pb-3">
                     <div
@@ -115,9 +179,85 @@ pub fn WalletMenu(
                     </div>
                 </div>
                 <hr class="m-0 border-border" />
-                <p class="m-0 py-4 px-5 text-sm">"Most recent transactions will appear here..."</p>
+                <Show
+                    when=move || !tx_manager.records().read().is_empty()
+                    fallback=|| {
+                        view! {
+                            <p class="m-0 py-4 px-5 text-sm">
+                                "Most recent transactions will appear here..."
+                            </p>
+                        }
+                    }
+                >
+                    <ul class="m-0 py-2 px-5 list-none space-y-2">
+                        <For
+                            each=move || tx_manager.records().get()
+                            key=|record| (record.id, format!("{:?}", record.status))
+                            let:record
+                        >
+                            <li class="flex justify-between items-center gap-2 text-sm">
+                                <div class="flex flex-col">
+                                    <span>{record.kind.to_string()}</span>
+                                    <span class="text-xs text-muted-foreground">
+                                        {record.hash.clone().map(shorten_address)}
+                                    </span>
+                                    {record
+                                        .fee
+                                        .clone()
+                                        .map(|fee| {
+                                            view! {
+                                                <span class="text-xs text-muted-foreground">
+                                                    {format!("Fee {} SCRT, {}", fee.scrt(), fee.payer())}
+                                                </span>
+                                            }
+                                        })}
+                                </div>
+                                {match record.status {
+                                    TxStatus::Simulating => {
+                                        view! { <span class="text-xs text-muted-foreground">"Simulating"</span> }
+                                            .into_any()
+                                    }
+                                    TxStatus::AwaitingApproval => {
+                                        view! { <span class="text-xs text-muted-foreground">"Awaiting approval"</span> }
+                                            .into_any()
+                                    }
+                                    TxStatus::Broadcasting => {
+                                        view! { <span class="text-xs text-muted-foreground">"Broadcasting"</span> }
+                                            .into_any()
+                                    }
+                                    TxStatus::Pending => {
+                                        view! { <span class="text-xs text-gold">"Pending"</span> }
+                                            .into_any()
+                                    }
+                                    TxStatus::Included => {
+                                        view! { <span class="text-xs text-foam">"Confirmed"</span> }
+                                            .into_any()
+                                    }
+                                    TxStatus::TimedOut => {
+                                        view! { <span class="text-xs text-gold">"Timed out"</span> }
+                                            .into_any()
+                                    }
+                                    TxStatus::Failed(error) => {
+                                        view! {
+                                            <span class="text-xs text-destructive" title=error.to_string()>
+                                                "Failed"
+                                            </span>
+                                        }
+                                            .into_any()
+                                    }
+                                }}
+                            </li>
+                        </For>
+                    </ul>
+                </Show>
                 <hr class="m-0 border-border" />
                 <div class="px-2 pt-3">
+                    <a href=format!("{BASE_URL}/history")>
+                        <div class="menu-button">
+                            <span class="text-sm text-muted-foreground">"View all activity"</span>
+                            <ChevronRight size=20 absolute_stroke_width=true />
+                        </div>
+                    </a>
                     <a
                         href=move || {
                             key_address()
@@ -151,6 +291,100 @@ pub fn WalletMenu(
                     </div>
                 </div>
                 <hr class="m-0 border-border" />
+                <div class="px-6 pt-3 text-xs text-muted-foreground">"Wallet"</div>
+                <ul class="space-y-1 px-1 py-2 list-none">
+                    {WalletKind::available_on(CHAIN_ID)
+                        .map(|kind| {
+                            let available = kind.wallet().is_available();
+                            view! {
+                                <li>
+                                    <button
+                                        class="menu-button w-full bg-transparent border-none"
+                                        disabled=move || !available || switch_wallet.pending().get()
+                                        on:click=move |_| _ = switch_wallet.dispatch(kind)
+                                    >
+                                        <Wallet size=22 />
+                                        {kind.to_string()}
+                                        {move || {
+                                            if keplr.enabled.get() && keplr.wallet.get() == kind {
+                                                "Connected"
+                                            } else if available {
+                                                ""
+                                            } else {
+                                                "Not installed"
+                                            }
+                                        }}
+                                    </button>
+                                </li>
+                            }
+                        })
+                        .collect_view()}
+                </ul>
+                {dev_accounts}
+                {move || {
+                    switch_wallet
+                        .value()
+                        .get()
+                        .and_then(Result::err)
+                        .map(|error| {
+                            view! { <ErrorMessage error class="px-6" /> }
+                        })
+                }}
+                <hr class="m-0 border-border" />
+                <div class="px-6 pt-3 text-xs text-muted-foreground">"Network fee"</div>
+                <div class="flex gap-1 px-6 py-2">
+                    {FeeTier::ALL
+                        .into_iter()
+                        .map(|tier| {
+                            view! {
+                                <button
+                                    class="flex-1 py-1 px-2 text-xs bg-secondary text-secondary-foreground rounded-md"
+                                    class=("font-bold", move || tx_manager.fee_tier().get() == tier)
+                                    title=format!("{} uscrt per unit of gas", tier.gas_price())
+                                    on:click=move |_| tx_manager.set_fee_tier(tier)
+                                >
+                                    <div>{tier.to_string()}</div>
+                                    {move || {
+                                        last_fee
+                                            .get()
+                                            .map(|fee| {
+                                                view! {
+                                                    <div class="text-muted-foreground">
+                                                        {format!("{} SCRT", fee.with_tier(tier).scrt())}
+                                                    </div>
+                                                }
+                                            })
+                                    }}
+                                </button>
+                            }
+                        })
+                        .collect_view()}
+                </div>
+                <div class="px-6 pb-2 text-xs text-muted-foreground">
+                    {move || match tx_manager.fee_grant().get() {
+                        Some(grant) => {
+                            format!("Fees are paid by {}", shorten_address(&grant.granter))
+                        }
+                        None => "Fees are paid by you".to_string(),
+                    }}
+                </div>
+                <hr class="m-0 border-border" />
+                <div class="px-2 pt-3">
+                    <a href=format!("{BASE_URL}/viewing-keys")>
+                        <div class="menu-button">
+                            <KeyRound size=22 />
+                            "Viewing Keys"
+                            <ChevronRight size=20 absolute_stroke_width=true />
+                        </div>
+                    </a>
+                    <a href=format!("{BASE_URL}/allowances")>
+                        <div class="menu-button">
+                            <ShieldCheck size=22 />
+                            "Allowances"
+                            <ChevronRight size=20 absolute_stroke_width=true />
+                        </div>
+                    </a>
+                </div>
             </Show>
             <Show when=move || contents.get() == "main">
                 // <!-- Header -->
@@ -187,7 +421,7 @@ pub fn WalletMenu(
                 // <!-- Menu Items -->
                 <ul class="space-y-1 px-1 py-2 list-none">
                     <li>
-                        <a href="#">
+                        <a href=format!("{BASE_URL}/portfolio")>
                             <div class="menu-button">
                                 <WavesLadder size=22 />
                                 "My Pools"
@@ -273,3 +507,63 @@ pub fn WalletMenu(
         </dialog>
     }
 }
+
+/// Lets the dev wallet switch between the LocalSecret genesis accounts.
+#[cfg(feature = "devnet")]
+#[component]
+fn DevAccounts() -> impl IntoView {
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
+    let balances = use_context::<Balances>().expect("balances context missing!");
+    let account = use_context::<Account>().expect("account context missing!");
+
+    // The dev wallet has no key store events, so the switch is handled here.
+    let switch_dev_account = Action::new_local(move |(name, phrase): &(&str, &str)| {
+        let result = dev::use_mnemonic(name, phrase);
+        async move {
+            result?;
+            if !keplr.enabled.get_untracked() {
+                return keplr.connect().await;
+            }
+            handle_key_store_change(keplr, account, balances, tx_manager)
+                .await
+                .map(|_| ())
+        }
+    });
+
+    view! {
+        <Show when=move || keplr.wallet.get() == WalletKind::Dev>
+            <div class="px-6 text-xs text-muted-foreground">"LocalSecret account"</div>
+            <div class="flex gap-1 px-6 py-2">
+                {dev::LOCALSECRET_ACCOUNTS
+                    .into_iter()
+                    .map(|(name, phrase)| {
+                        view! {
+                            <button
+                                class="py-1 px-2 text-xs bg-secondary text-secondary-foreground rounded-md"
+                                class=("font-bold", move || {
+                                    // the key changes whenever the account does
+                                    keplr.key.track();
+                                    dev::account_name() == name
+                                })
+                                disabled=move || switch_dev_account.pending().get()
+                                on:click=move |_| _ = switch_dev_account.dispatch((name, phrase))
+                            >
+                                {name}
+                            </button>
+                        }
+                    })
+                    .collect_view()}
+            </div>
+        </Show>
+        {move || {
+            switch_dev_account
+                .value()
+                .get()
+                .and_then(Result::err)
+                .map(|error| {
+                    view! { <ErrorMessage error class="px-6" /> }
+                })
+        }}
+    }
+}
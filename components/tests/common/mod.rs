@@ -0,0 +1,107 @@
+//! Shared setup for the headless component tests.
+
+#![allow(dead_code)]
+
+use ammber_core::{
+    constants::{CHAIN_ID, TOKEN_MAP},
+    Account, Balances, ChainId, Endpoint, TokenMap, TxManager, WalletSignals,
+};
+use keplr::{mock::MockKeplr, Key};
+use leptos::{mount::mount_to, prelude::*};
+use std::time::Duration;
+use web_sys::{js_sys::Promise, wasm_bindgen::JsCast, HtmlElement};
+
+/// Nothing listens here, so chain queries fail fast instead of reaching a real node.
+pub const UNREACHABLE_NODE: &str = "http://127.0.0.1:9";
+
+pub const ADDRESS: &str = "secret1ap26qrlp8mcq2pg6r47w43l0y8zkqm8a450s03";
+
+thread_local! {
+    static KEPLR: MockKeplr = {
+        let keplr = MockKeplr::new().with_key(Key {
+            name: "test".to_string(),
+            bech32_address: ADDRESS.to_string(),
+            ..Default::default()
+        });
+        keplr.install().expect("failed to install the mock");
+        keplr
+    };
+}
+
+/// The mock on `window.keplr`. It's installed once per test binary, because keplr-sys caches
+/// the provider on first access.
+pub fn keplr() -> MockKeplr {
+    KEPLR.with(Clone::clone)
+}
+
+/// Provides the contexts the app provides at the root, and returns the wallet signals.
+pub fn provide_app_contexts() -> WalletSignals {
+    provide_context(Endpoint::new(UNREACHABLE_NODE));
+    provide_context(ChainId::new(CHAIN_ID));
+    provide_context(WalletSignals::new());
+    provide_context(TokenMap::new(TOKEN_MAP.clone()));
+    provide_context(Balances::new());
+    provide_context(Account::new());
+
+    let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
+    let balances = use_context::<Balances>().expect("balances context missing!");
+    provide_context(TxManager::new(endpoint, balances));
+
+    use_context::<WalletSignals>().expect("wallet signals context missing!")
+}
+
+/// Mounts `f` into a fresh element on the page, and returns the element. The view stays mounted
+/// for the rest of the test binary.
+pub fn mount<F, N>(f: F) -> HtmlElement
+where
+    F: FnOnce() -> N + 'static,
+    N: IntoView,
+{
+    let parent: HtmlElement = document()
+        .create_element("div")
+        .expect("failed to create an element")
+        .unchecked_into();
+    document()
+        .body()
+        .expect("document has no body")
+        .append_child(&parent)
+        .expect("failed to append an element");
+
+    mount_to(parent.clone(), f).forget();
+
+    parent
+}
+
+/// Gives resources and wallet promises time to settle.
+pub async fn sleep(duration: Duration) {
+    let promise = Promise::new(&mut |resolve, _| {
+        _ = window().set_timeout_with_callback_and_timeout_and_arguments_0(
+            &resolve,
+            duration.as_millis() as i32,
+        );
+    });
+    _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+/// The text of `element`, hidden or not.
+pub fn text(element: &HtmlElement) -> String {
+    element
+        .text_content()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Remembers whether `contract_address` supports permits, so no probe is sent.
+pub fn seed_permit_support(contract_address: &str, supported: bool) {
+    let storage = window()
+        .local_storage()
+        .ok()
+        .flatten()
+        .expect("no local storage");
+    let support = format!(r#"{{"{contract_address}":{supported}}}"#);
+    storage
+        .set_item(&format!("permit_support_{CHAIN_ID}"), &support)
+        .expect("failed to write local storage");
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use ammber_components::Secret20Balance;
+use ammber_core::constants::TOKEN_MAP;
+use common::*;
+use leptos::prelude::*;
+use std::time::Duration;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn any_token() -> String {
+    TOKEN_MAP
+        .keys()
+        .next()
+        .expect("the token map is empty")
+        .clone()
+}
+
+#[wasm_bindgen_test]
+async fn renders_nothing_while_disconnected() {
+    keplr();
+    let token_address = any_token();
+
+    let element = mount(move || {
+        provide_app_contexts();
+        view! { <Secret20Balance token_address=Some(token_address) /> }
+    });
+    sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(text(&element), "");
+}
+
+#[wasm_bindgen_test]
+async fn offers_a_viewing_key_when_the_wallet_has_none() {
+    let keplr = keplr();
+    keplr.clear_calls();
+    let token_address = any_token();
+    // a token without permit support, so the balance needs a viewing key
+    seed_permit_support(&token_address, false);
+
+    let element = mount(move || {
+        let wallet = provide_app_contexts();
+        wallet.enabled.set(true);
+        view! { <Secret20Balance token_address=Some(token_address) /> }
+    });
+    sleep(Duration::from_millis(300)).await;
+
+    assert!(
+        text(&element).contains("View Balance"),
+        "{}",
+        text(&element)
+    );
+    assert!(keplr.calls().contains(&"getSecret20ViewingKey".to_string()));
+    // no permit was signed for it
+    assert!(!keplr.calls().contains(&"signAmino".to_string()));
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use ammber_components::WalletMenu;
+use ammber_core::utils::shorten_address;
+use common::*;
+use leptos::prelude::*;
+use std::time::Duration;
+use wasm_bindgen_test::*;
+use web_sys::{wasm_bindgen::JsCast, HtmlElement};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn click(element: &HtmlElement, label: &str) {
+    let items = element.get_elements_by_class_name("menu-button");
+    let item = (0..items.length())
+        .filter_map(|index| items.item(index))
+        .map(|item| item.unchecked_into::<HtmlElement>())
+        .find(|item| text(item).contains(label))
+        .unwrap_or_else(|| panic!("no {label} item"));
+    item.click();
+}
+
+#[wasm_bindgen_test]
+async fn shows_the_connected_account() {
+    keplr();
+
+    let element = mount(|| {
+        let wallet = provide_app_contexts();
+        wallet.enabled.set(true);
+        view! { <WalletMenu dialog_ref=NodeRef::new() toggle_menu=|_| {} /> }
+    });
+    sleep(Duration::from_millis(200)).await;
+
+    assert!(text(&element).contains(&shorten_address(ADDRESS)));
+}
+
+#[wasm_bindgen_test]
+async fn lists_the_wallets_in_settings() {
+    keplr();
+
+    let element = mount(|| {
+        provide_app_contexts();
+        view! { <WalletMenu dialog_ref=NodeRef::new() toggle_menu=|_| {} /> }
+    });
+    click(&element, "Settings");
+    sleep(Duration::from_millis(50)).await;
+
+    let text = text(&element);
+    assert!(text.contains("Keplr"), "{text}");
+    assert!(text.contains("Network fee"), "{text}");
+}
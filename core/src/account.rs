@@ -0,0 +1,121 @@
+//! Switching accounts in the wallet.
+//!
+//! Wallets fire a key store change event when the user picks another account. Everything that
+//! belongs to an account is then reset: balance versions are bumped, settled activity of the old
+//! account is dropped, and resources that track [`Account`] refetch. Permits, viewing keys and
+//! history are stored per address, so they follow the new key without being cleared.
+
+use crate::{error::Error, state::Balances, tx::TxManager, WalletSignals, CHAIN_ID};
+use leptos::prelude::*;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// How long the notice of a switch stays up.
+const NOTICE_DURATION: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountSwitch {
+    pub from: Option<String>,
+    pub to: String,
+    /// The name of the key in the wallet.
+    pub name: String,
+}
+
+/// Account switches, for resources that cache anything per account.
+#[derive(Copy, Clone)]
+pub struct Account {
+    generation: RwSignal<u32>,
+    notice: RwSignal<Option<AccountSwitch>>,
+}
+
+impl Account {
+    pub fn new() -> Self {
+        Self {
+            generation: RwSignal::new(0),
+            notice: RwSignal::new(None),
+        }
+    }
+
+    /// Subscribes to account switches.
+    pub fn track(&self) {
+        self.generation.track();
+    }
+
+    /// The most recent switch, until its notice times out or is dismissed.
+    pub fn notice(&self) -> Signal<Option<AccountSwitch>> {
+        self.notice.into()
+    }
+
+    pub fn dismiss(&self) {
+        self.notice.set(None);
+    }
+
+    fn switched(&self, switch: AccountSwitch) {
+        self.generation.update(|generation| *generation += 1);
+        self.notice.set(Some(switch));
+
+        // only clear the notice if no other switch happened in the meantime
+        let generation = self.generation.get_untracked();
+        let this = *self;
+        set_timeout(
+            move || {
+                if this.generation.get_untracked() == generation {
+                    this.dismiss();
+                }
+            },
+            NOTICE_DURATION,
+        );
+    }
+}
+
+/// Re-fetches the key after the wallet reports a key store change, and resets all
+/// account-scoped state if the address changed.
+///
+/// Returns the switch, or `None` if the same account is still selected (for example after
+/// renaming it in the wallet).
+pub async fn handle_key_store_change(
+    keplr: WalletSignals,
+    account: Account,
+    balances: Balances,
+    tx_manager: TxManager,
+) -> Result<Option<AccountSwitch>, Error> {
+    if !keplr.enabled.get_untracked() {
+        return Ok(None);
+    }
+
+    let from = keplr
+        .key
+        .get_untracked()
+        .and_then(Result::ok)
+        .map(|key| key.bech32_address);
+
+    // ask the wallet directly, the derived signal may not have caught up yet
+    let key = keplr
+        .wallet
+        .get_untracked()
+        .wallet()
+        .get_key(CHAIN_ID)
+        .await?;
+
+    // re-runs the key derived signal
+    keplr.enabled.set(true);
+
+    if from.as_ref() == Some(&key.bech32_address) {
+        debug!("key store changed, but the account is the same");
+        return Ok(None);
+    }
+
+    info!("switched account to {}", key.bech32_address);
+
+    balances.invalidate_all();
+    tx_manager.clear_settled();
+
+    let switch = AccountSwitch {
+        from,
+        to: key.bech32_address,
+        name: key.name,
+    };
+    account.switched(switch.clone());
+
+    Ok(Some(switch))
+}
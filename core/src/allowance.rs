@@ -0,0 +1,187 @@
+//! SNIP-20 allowances granted to the LB contracts.
+//!
+//! The router pulls tokens with `TransferFrom`, so it needs an allowance for every deposit.
+//! [`ensure`] only adds an `IncreaseAllowance` to a batch when the current allowance falls
+//! short, and [`revoke`] takes an outstanding one back.
+
+use crate::{
+    constants::{contracts::LB_ROUTER, TOKEN_MAP},
+    error::Error,
+    permit,
+    support::chain_query,
+    tx::{TxBatch, TxKind, TxManager, TxRecord, TxSummary},
+    viewing_key, CHAIN_ID,
+};
+use cosmwasm_std::{ContractInfo, Uint128};
+use rsecret::TxOptions;
+use secret_toolkit_snip20::AllowanceResponse;
+use serde_json::json;
+use tracing::{debug, warn};
+use web_sys::js_sys::Date;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Allowance {
+    pub token: ContractInfo,
+    pub spender: ContractInfo,
+    pub amount: Uint128,
+    /// Seconds since the Unix epoch.
+    pub expiration: Option<u64>,
+}
+
+impl Allowance {
+    pub fn is_expired(&self) -> bool {
+        self.expiration
+            .is_some_and(|expiration| expiration as f64 <= Date::now() / 1000.0)
+    }
+
+    /// What the spender can still move. Expired allowances are worth nothing.
+    pub fn remaining(&self) -> Uint128 {
+        if self.is_expired() {
+            Uint128::zero()
+        } else {
+            self.amount
+        }
+    }
+}
+
+/// The LB contracts that are ever granted allowances, with a name for each.
+pub fn lb_spenders() -> Vec<(&'static str, ContractInfo)> {
+    vec![("LB Router", LB_ROUTER.clone())]
+}
+
+/// Queries the allowance `owner` has given `spender` for `token`, using a permit if the token
+/// supports them and a viewing key otherwise.
+pub async fn query(
+    owner: &str,
+    token: &ContractInfo,
+    spender: &ContractInfo,
+) -> Result<Allowance, Error> {
+    let response = permit::query_or_else(
+        owner,
+        token,
+        |permit| {
+            permit::query_with_permit::<AllowanceResponse>(
+                token,
+                permit,
+                json!({
+                    "allowance": {
+                        "owner": owner,
+                        "spender": spender.address,
+                    }
+                }),
+            )
+        },
+        || async move {
+            let key = viewing_key::get(owner, token.address.as_str())
+                .await
+                .ok_or_else(|| Error::generic(format!("no viewing key for {}", token.address)))?;
+
+            chain_query::<AllowanceResponse>(
+                token.code_hash.clone(),
+                token.address.to_string(),
+                secret_toolkit_snip20::QueryMsg::Allowance {
+                    owner: owner.to_string(),
+                    spender: spender.address.to_string(),
+                    key,
+                },
+            )
+            .await
+        },
+    )
+    .await?;
+
+    Ok(Allowance {
+        token: token.clone(),
+        spender: spender.clone(),
+        amount: response.allowance.allowance,
+        expiration: response.allowance.expiration,
+    })
+}
+
+/// Adds an `IncreaseAllowance` to `batch` if `spender` can't already move `amount` of `token`.
+///
+/// Only the shortfall is added. If the allowance can't be read, the whole amount is added, which
+/// at worst leaves the spender with more than it needed. Returns whether a message was added.
+pub async fn ensure(
+    batch: &mut TxBatch,
+    token: &ContractInfo,
+    spender: &ContractInfo,
+    amount: impl Into<Uint128>,
+) -> Result<bool, Error> {
+    let amount = amount.into();
+    if amount.is_zero() {
+        return Ok(false);
+    }
+
+    let remaining = match query(batch.sender(), token, spender).await {
+        Ok(allowance) => allowance.remaining(),
+        Err(error) => {
+            warn!(
+                "could not read the allowance for {}: {error}",
+                token.address
+            );
+            Uint128::zero()
+        }
+    };
+
+    if remaining >= amount {
+        debug!(
+            "{} already has an allowance of {remaining}",
+            spender.address
+        );
+        return Ok(false);
+    }
+
+    batch
+        .increase_allowance(token, spender, amount - remaining)
+        .await?;
+
+    Ok(true)
+}
+
+/// Lists the allowances `owner` has outstanding with [`lb_spenders`], for every known token.
+///
+/// Tokens whose allowance can't be read are skipped.
+pub async fn outstanding(owner: &str) -> Vec<Allowance> {
+    let mut allowances = vec![];
+
+    for token in TOKEN_MAP.values() {
+        let token = ContractInfo::from(token);
+
+        for (_, spender) in lb_spenders() {
+            match query(owner, &token, &spender).await {
+                Ok(allowance) if !allowance.remaining().is_zero() => allowances.push(allowance),
+                Ok(_) => {}
+                Err(error) => debug!("skipping {}: {error}", token.address),
+            }
+        }
+    }
+
+    allowances
+}
+
+/// Sets the allowance back to zero.
+pub async fn revoke(
+    tx_manager: TxManager,
+    owner: &str,
+    allowance: &Allowance,
+) -> Result<TxRecord, Error> {
+    let msg = secret_toolkit_snip20::HandleMsg::DecreaseAllowance {
+        spender: allowance.spender.address.to_string(),
+        amount: allowance.amount,
+        expiration: None,
+        padding: None,
+    };
+
+    debug!(
+        "revoking the {} allowance of {} on {CHAIN_ID}",
+        allowance.token.address, allowance.spender.address
+    );
+
+    let summary =
+        TxSummary::new(TxKind::Allowance).with_token(allowance.token.address.to_string(), 0u128);
+    let mut batch = tx_manager.batch(owner, summary);
+    batch.push(&allowance.token, &msg).await?;
+
+    batch.submit(TxOptions::default()).await
+}
@@ -1,6 +1,9 @@
 use cosmwasm_std::{Addr, ContractInfo};
 use hex_literal::hex;
-use keplr::tokens::KeplrToken;
+use keplr::{
+    experimental::{Bech32Config, Bip44, ChainInfo, Currency, FeeCurrency, GasPriceStep},
+    tokens::KeplrToken,
+};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
@@ -57,11 +60,156 @@ pub const NODE: &'static str = if cfg!(feature = "mainnet") {
     "http://localhost:1317"
 };
 
+// Chain configs, as suggested to the wallet. Fees are priced with their gas price steps.
+
+pub static DEVNET_CHAIN_INFO: LazyLock<ChainInfo> = LazyLock::new(|| ChainInfo {
+    chain_id: "secretdev-1".to_string(),
+    chain_name: "localsecret".to_string(),
+    rpc: "http://127.0.0.1:26657".to_string(),
+    rest: "http://127.0.0.1:1317".to_string(),
+    bip44: Bip44 { coin_type: 529 },
+    bech32_config: Bech32Config {
+        bech32_prefix_acc_addr: "secret".to_string(),
+        bech32_prefix_acc_pub: "secretpub".to_string(),
+        bech32_prefix_val_addr: "secretvaloper".to_string(),
+        bech32_prefix_val_pub: "secretvaloperpub".to_string(),
+        bech32_prefix_cons_addr: "secretvalcons".to_string(),
+        bech32_prefix_cons_pub: "secretvalconspub".to_string(),
+    },
+    currencies: vec![Currency {
+        coin_denom: "SCRT".to_string(),
+        coin_minimal_denom: "uscrt".to_string(),
+        coin_decimals: 6,
+        coin_gecko_id: "secret".to_string(),
+    }],
+    fee_currencies: vec![FeeCurrency {
+        coin_denom: "SCRT".to_string(),
+        coin_minimal_denom: "uscrt".to_string(),
+        coin_decimals: 6,
+        coin_gecko_id: "secret".to_string(),
+        gas_price_step: GasPriceStep {
+            low: 0.1,
+            average: 0.25,
+            high: 0.5,
+        },
+    }],
+    stake_currency: Currency {
+        coin_denom: "SCRT".to_string(),
+        coin_minimal_denom: "uscrt".to_string(),
+        coin_decimals: 6,
+        coin_gecko_id: "secret".to_string(),
+    },
+    features: vec!["secretwasm".to_string()],
+});
+
+pub static PULSAR_CHAIN_INFO: LazyLock<ChainInfo> = LazyLock::new(|| ChainInfo {
+    chain_id: "pulsar-3".to_string(),
+    chain_name: "Pulsar".to_string(),
+    rpc: "https://rpc.pulsar.scrttestnet.com".to_string(),
+    rest: "https://api.pulsar.scrttestnet.com".to_string(),
+    bip44: Bip44 { coin_type: 529 },
+    bech32_config: Bech32Config {
+        bech32_prefix_acc_addr: "secret".to_string(),
+        bech32_prefix_acc_pub: "secretpub".to_string(),
+        bech32_prefix_val_addr: "secretvaloper".to_string(),
+        bech32_prefix_val_pub: "secretvaloperpub".to_string(),
+        bech32_prefix_cons_addr: "secretvalcons".to_string(),
+        bech32_prefix_cons_pub: "secretvalconspub".to_string(),
+    },
+    currencies: vec![Currency {
+        coin_denom: "SCRT".to_string(),
+        coin_minimal_denom: "uscrt".to_string(),
+        coin_decimals: 6,
+        coin_gecko_id: "secret".to_string(),
+    }],
+    fee_currencies: vec![FeeCurrency {
+        coin_denom: "SCRT".to_string(),
+        coin_minimal_denom: "uscrt".to_string(),
+        coin_decimals: 6,
+        coin_gecko_id: "secret".to_string(),
+        gas_price_step: GasPriceStep {
+            low: 0.1,
+            average: 0.25,
+            high: 0.5,
+        },
+    }],
+    stake_currency: Currency {
+        coin_denom: "SCRT".to_string(),
+        coin_minimal_denom: "uscrt".to_string(),
+        coin_decimals: 6,
+        coin_gecko_id: "secret".to_string(),
+    },
+    features: vec!["secretwasm".to_string()],
+});
+
+pub static MAINNET_CHAIN_INFO: LazyLock<ChainInfo> = LazyLock::new(|| ChainInfo {
+    chain_id: "secret-4".to_string(),
+    chain_name: "Secret Network".to_string(),
+    rpc: "https://rpc.mainnet.secretsaturn.net".to_string(),
+    rest: "https://lcd.mainnet.secretsaturn.net".to_string(),
+    bip44: Bip44 { coin_type: 529 },
+    bech32_config: Bech32Config {
+        bech32_prefix_acc_addr: "secret".to_string(),
+        bech32_prefix_acc_pub: "secretpub".to_string(),
+        bech32_prefix_val_addr: "secretvaloper".to_string(),
+        bech32_prefix_val_pub: "secretvaloperpub".to_string(),
+        bech32_prefix_cons_addr: "secretvalcons".to_string(),
+        bech32_prefix_cons_pub: "secretvalconspub".to_string(),
+    },
+    currencies: vec![Currency {
+        coin_denom: "SCRT".to_string(),
+        coin_minimal_denom: "uscrt".to_string(),
+        coin_decimals: 6,
+        coin_gecko_id: "secret".to_string(),
+    }],
+    fee_currencies: vec![FeeCurrency {
+        coin_denom: "SCRT".to_string(),
+        coin_minimal_denom: "uscrt".to_string(),
+        coin_decimals: 6,
+        coin_gecko_id: "secret".to_string(),
+        gas_price_step: GasPriceStep {
+            low: 0.1,
+            average: 0.25,
+            high: 0.5,
+        },
+    }],
+    stake_currency: Currency {
+        coin_denom: "SCRT".to_string(),
+        coin_minimal_denom: "uscrt".to_string(),
+        coin_decimals: 6,
+        coin_gecko_id: "secret".to_string(),
+    },
+    features: vec!["secretwasm".to_string()],
+});
+
+pub fn get_chain_info(chain_id: &str) -> &'static ChainInfo {
+    match chain_id {
+        "secretdev-1" => &DEVNET_CHAIN_INFO,
+        "pulsar-3" => &PULSAR_CHAIN_INFO,
+        "secret-4" => &MAINNET_CHAIN_INFO,
+        _ => panic!("invalid chain id!"),
+    }
+}
+
+pub static CHAIN_INFO: LazyLock<&'static ChainInfo> = LazyLock::new(|| get_chain_info(CHAIN_ID));
+
+/// The block explorer for `chain_id`, if it has one. Transactions are at `{explorer}/tx/{hash}`.
+pub fn get_explorer(chain_id: &str) -> Option<&'static str> {
+    match chain_id {
+        "pulsar-3" => Some("https://testnet.ping.pub/secret"),
+        "secret-4" => Some("https://www.mintscan.io/secret"),
+        _ => None,
+    }
+}
+
+pub static EXPLORER: LazyLock<Option<&'static str>> = LazyLock::new(|| get_explorer(CHAIN_ID));
+
 pub mod contracts {
     use crate::support::{ILbFactory, ILbQuoter};
 
     use super::CHAIN_ID;
-    use ammber_sdk::constants::addrs::get_deployed_contracts;
+    use ammber_sdk::constants::addrs::{get_deployed_contracts, BATCH_QUERY_ROUTER};
+    use batch_query::BatchRouterRegistry;
     use cosmwasm_std::ContractInfo;
     use std::sync::LazyLock;
 
@@ -77,6 +225,13 @@ pub mod contracts {
 
     pub static LB_FACTORY: LazyLock<ILbFactory> =
         LazyLock::new(|| ILbFactory(get_deployed_contracts(CHAIN_ID).lb_factory.clone()));
+
+    pub static BATCH_ROUTERS: LazyLock<BatchRouterRegistry> = LazyLock::new(|| {
+        BATCH_QUERY_ROUTER
+            .iter()
+            .map(|(chain_id, router)| (chain_id.as_str(), router.clone()))
+            .collect()
+    });
 }
 
 // TODO:
@@ -149,6 +304,19 @@ pub fn get_token_map(chain_id: &str) -> Arc<HashMap<String, Token>> {
 pub static TOKEN_MAP: LazyLock<Arc<HashMap<String, Token>>> =
     LazyLock::new(|| get_token_map(CHAIN_ID));
 
+/// The address of the token that is worth one dollar, which USD prices are derived from.
+pub fn get_reference_stablecoin(chain_id: &str) -> &'static str {
+    match chain_id {
+        "secretdev-1" => "secret1q44572encmrutq4k7e9h05td2kxd34zw3k7rqr",
+        "pulsar-3" => "secret13yp52g7r2llz5w49wv9p9pymamn9j8690p27ag",
+        "secret-4" => "secret1chsejpk9kfj4vt9ec6xvyguw539gsdtr775us2",
+        _ => panic!("invalid chain id!"),
+    }
+}
+
+pub static REFERENCE_STABLECOIN: LazyLock<&'static str> =
+    LazyLock::new(|| get_reference_stablecoin(CHAIN_ID));
+
 // For each token we know about at compile time, map from symbol to address
 pub static SYMBOL_TO_ADDR: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
     get_token_map(CHAIN_ID)
@@ -0,0 +1,420 @@
+//! Decoding of Secret contract error strings into typed errors.
+//!
+//! Contract errors reach the frontend wrapped in a few layers of text, depending on where they
+//! came from:
+//!
+//! - query: `query contract failed: Generic error: <message>`
+//! - tx log: `failed to execute message; message index: 0: <message>: execute wasm contract failed`
+//! - query response: `{"generic_err":{"msg":"<message>"}}` or `{"viewing_key_error":{"msg":"..."}}`
+//!
+//! Encrypted errors (`encrypted: <base64>`) must be decrypted before decoding. If one slips
+//! through, it decodes as [`ContractError::Encrypted`].
+
+use serde::{Deserialize, Serialize};
+
+#[derive(thiserror::Error, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ContractError {
+    // LB Router
+    #[error("The transaction deadline passed. Try again, or allow more time in the settings.")]
+    DeadlineExceeded {
+        deadline: Option<u64>,
+        current_timestamp: Option<u64>,
+    },
+
+    #[error("The price moved beyond your slippage tolerance. Get a new quote, or increase the slippage tolerance.")]
+    InsufficientAmountOut {
+        amount_out_min: Option<u128>,
+        amount_out: Option<u128>,
+    },
+
+    #[error("The required input is more than your maximum. Get a new quote, or increase the slippage tolerance.")]
+    MaxAmountInExceeded,
+
+    #[error("The tokens are in the wrong order for this pool. Switch token X and token Y and try again.")]
+    WrongTokenOrder,
+
+    #[error("The active bin moved beyond your price slippage. Refresh the pool price, or increase the price slippage.")]
+    IdSlippage { active_id: Option<u32> },
+
+    #[error("The deposit amounts moved beyond your slippage tolerance. Increase the amount slippage and try again.")]
+    AmountSlippage,
+
+    #[error("No pool exists for these tokens and bin step. Create the pool first.")]
+    PairNotCreated,
+
+    #[error("Invalid liquidity parameters: {0}")]
+    InvalidParameters(String),
+
+    // LB Pair
+    #[error("There is not enough liquidity in the pool. Try a smaller amount.")]
+    InsufficientLiquidity,
+
+    #[error("The input amount is too small. Enter a larger amount.")]
+    InsufficientAmountIn,
+
+    #[error("Amounts must be greater than zero.")]
+    ZeroAmount,
+
+    // LB Factory
+    #[error("A pool with these tokens and bin step already exists.")]
+    PairAlreadyExists,
+
+    #[error("Both tokens are the same. Choose two different tokens.")]
+    IdenticalTokens,
+
+    #[error("This bin step is not available. Choose a different bin step.")]
+    BinStepNotAvailable,
+
+    #[error("This token can't be used as the quote asset. Choose a different quote token.")]
+    QuoteAssetNotWhitelisted,
+
+    // SNIP-20
+    #[error("Your token balance is too low for this transaction.")]
+    InsufficientFunds,
+
+    #[error("The router isn't allowed to spend enough of your tokens. Increase the allowance and try again.")]
+    InsufficientAllowance,
+
+    #[error(
+        "The viewing key is missing or wrong. Set a viewing key for this token and try again."
+    )]
+    ViewingKey,
+
+    // SNIP-24
+    #[error("This contract doesn't accept query permits. Use a viewing key instead.")]
+    PermitsUnsupported,
+
+    #[error("The query permit was rejected. Sign a new permit and try again.")]
+    PermitRejected,
+
+    #[error("The contract returned an error that could not be decrypted.")]
+    Encrypted,
+
+    #[error("{0}")]
+    Other(String),
+}
+
+impl ContractError {
+    /// Returns `None` if `raw` doesn't look like an error produced by a contract.
+    pub fn decode(raw: &str) -> Option<Self> {
+        let (message, is_contract_error) = unwrap_message(raw);
+
+        let known = KNOWN_ERRORS.iter().find_map(|(template, decode)| {
+            captures(&message, template).map(|values| decode(&message, &values))
+        });
+
+        match known {
+            Some(error) => Some(error),
+            None if is_contract_error => Some(Self::Other(message)),
+            None => None,
+        }
+    }
+}
+
+type Decoder = fn(&str, &[&str]) -> ContractError;
+
+/// Error messages as the contracts format them, with `{}` where a value is filled in.
+const KNOWN_ERRORS: &[(&str, Decoder)] = &[
+    ("encrypted: {}", |_, _| ContractError::Encrypted),
+    // LB Router
+    (
+        "Deadline exceeded. Deadline: {}, Current timestamp: {}",
+        |_, values| ContractError::DeadlineExceeded {
+            deadline: values[0].parse().ok(),
+            current_timestamp: values[1].parse().ok(),
+        },
+    ),
+    (
+        "Insufficient amount out. Amount out min: {}, Amount out: {}",
+        |_, values| ContractError::InsufficientAmountOut {
+            amount_out_min: values[0].parse().ok(),
+            amount_out: values[1].parse().ok(),
+        },
+    ),
+    (
+        "Max amount in exceeded. Amount in max: {}, Amount in: {}",
+        |_, _| ContractError::MaxAmountInExceeded,
+    ),
+    ("Wrong token order", |_, _| ContractError::WrongTokenOrder),
+    (
+        "Id slippage caught. Active id desired: {}, Id slippage: {}, Active id: {}",
+        |_, values| ContractError::IdSlippage {
+            active_id: values[2].parse().ok(),
+        },
+    ),
+    (
+        "Amount slippage caught. AmountXMin: {}, AmountX: {}, AmountYMin: {}, AmountY: {}",
+        |_, _| ContractError::AmountSlippage,
+    ),
+    ("Pair not created: {} and {}, binStep: {}", |_, _| {
+        ContractError::PairNotCreated
+    }),
+    ("Wrong amounts: amount: {}, reserve: {}", |message, _| {
+        ContractError::InvalidParameters(message.to_string())
+    }),
+    ("Lengths mismatch", |message, _| {
+        ContractError::InvalidParameters(message.to_string())
+    }),
+    ("Invalid version: {}", |message, _| {
+        ContractError::InvalidParameters(message.to_string())
+    }),
+    // LB Pair
+    ("Insufficient amount in!", |_, _| {
+        ContractError::InsufficientAmountIn
+    }),
+    ("Insufficient amount out!", |_, _| {
+        ContractError::InsufficientAmountOut {
+            amount_out_min: None,
+            amount_out: None,
+        }
+    }),
+    ("Out of liquidity!", |_, _| {
+        ContractError::InsufficientLiquidity
+    }),
+    ("Zero amount for bin id: {}", |_, _| {
+        ContractError::ZeroAmount
+    }),
+    (
+        "Zero amounts out for bin id: {} amount to burn: {} total supply: {}",
+        |_, _| ContractError::ZeroAmount,
+    ),
+    ("Zero Shares for bin id: {}", |_, _| {
+        ContractError::ZeroAmount
+    }),
+    // LB Factory
+    ("LBPair ({}, {}, bin_step: {}) already exists!", |_, _| {
+        ContractError::PairAlreadyExists
+    }),
+    ("Tokens are identical! Both addresses are {}!", |_, _| {
+        ContractError::IdenticalTokens
+    }),
+    ("Bin step {} has no preset!", |_, _| {
+        ContractError::BinStepNotAvailable
+    }),
+    (
+        "Preset {} is locked for users! {} is not the owner!",
+        |_, _| ContractError::BinStepNotAvailable,
+    ),
+    ("Quote Asset {} is not whitelisted!", |_, _| {
+        ContractError::QuoteAssetNotWhitelisted
+    }),
+    // SNIP-20
+    ("insufficient funds: balance={}, required={}", |_, _| {
+        ContractError::InsufficientFunds
+    }),
+    (
+        "insufficient allowance: allowance={}, required={}",
+        |_, _| ContractError::InsufficientAllowance,
+    ),
+    (
+        "Wrong viewing key for this address or viewing key not set",
+        |_, _| ContractError::ViewingKey,
+    ),
+    // SNIP-24
+    (
+        "Error parsing into type {}: unknown variant `with_permit`{}",
+        |_, _| ContractError::PermitsUnsupported,
+    ),
+    (
+        "Failed to verify signatures for the given permit",
+        |_, _| ContractError::PermitRejected,
+    ),
+    ("Invalid signature format", |_, _| {
+        ContractError::PermitRejected
+    }),
+    ("Invalid public key format", |_, _| {
+        ContractError::PermitRejected
+    }),
+    ("Permit {} was revoked by account {}", |_, _| {
+        ContractError::PermitRejected
+    }),
+];
+
+/// Matches `message` against `template` and returns the values that fill its `{}` placeholders,
+/// or `None` if the message doesn't have that exact form.
+fn captures<'a>(message: &'a str, template: &str) -> Option<Vec<&'a str>> {
+    let mut literals = template.split("{}");
+    let mut rest = message.strip_prefix(literals.next()?)?;
+    let literals: Vec<&str> = literals.collect();
+    let mut values = Vec::with_capacity(literals.len());
+
+    for (index, literal) in literals.iter().enumerate() {
+        let end = if index + 1 == literals.len() {
+            // the last value runs up to the end of the template
+            rest.strip_suffix(literal)?.len()
+        } else {
+            rest.find(literal)?
+        };
+        values.push(&rest[..end]);
+        rest = &rest[end + literal.len()..];
+    }
+
+    rest.is_empty().then_some(values)
+}
+
+/// Strips the layers around a contract error message. Also returns whether any of those layers
+/// were found, which tells a contract error apart from (for example) a network error.
+fn unwrap_message(raw: &str) -> (String, bool) {
+    let raw = raw.trim();
+
+    if let Ok(serde_json::Value::Object(map)) = serde_json::from_str::<serde_json::Value>(raw) {
+        // e.g. {"generic_err":{"msg":"..."}}
+        if let Some((key, value)) = map.iter().next() {
+            if map.len() == 1 && (key.ends_with("err") || key.ends_with("error")) {
+                let msg = value.get("msg").and_then(|msg| msg.as_str()).unwrap_or(key);
+                return (msg.to_string(), true);
+            }
+        }
+        return (raw.to_string(), false);
+    }
+
+    let mut message = raw;
+    let mut found = false;
+
+    const PREFIXES: [&str; 3] = [
+        "query contract failed:",
+        "generic error:",
+        "encrypted error:",
+    ];
+    const SUFFIXES: [&str; 2] = [
+        ": execute wasm contract failed",
+        ": instantiate wasm contract failed",
+    ];
+
+    if let Some(index) = message.find("message index:") {
+        // skip past "message index: N:"
+        let rest = &message[index + "message index:".len()..];
+        message = rest.split_once(':').map_or(rest, |(_, rest)| rest).trim();
+        found = true;
+    }
+
+    loop {
+        let lower = message.to_lowercase();
+        let Some(prefix) = PREFIXES.iter().find(|prefix| lower.starts_with(*prefix)) else {
+            break;
+        };
+        message = message[prefix.len()..].trim();
+        found = true;
+    }
+
+    for suffix in SUFFIXES {
+        if let Some(stripped) = message.strip_suffix(suffix) {
+            message = stripped.trim();
+            found = true;
+        }
+    }
+
+    (message.to_string(), found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_query_errors() {
+        let raw = "query contract failed: Generic error: Deadline exceeded. Deadline: 1700000000, Current timestamp: 1700000100";
+
+        assert_eq!(
+            ContractError::decode(raw),
+            Some(ContractError::DeadlineExceeded {
+                deadline: Some(1_700_000_000),
+                current_timestamp: Some(1_700_000_100),
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_tx_log_errors() {
+        let raw = "failed to execute message; message index: 1: Id slippage caught. Active id desired: 8388608, Id slippage: 5, Active id: 8388620: execute wasm contract failed";
+
+        assert_eq!(
+            ContractError::decode(raw),
+            Some(ContractError::IdSlippage {
+                active_id: Some(8_388_620),
+            })
+        );
+    }
+
+    #[test]
+    fn decodes_query_response_errors() {
+        let raw = r#"{"viewing_key_error":{"msg":"Wrong viewing key for this address or viewing key not set"}}"#;
+
+        assert_eq!(ContractError::decode(raw), Some(ContractError::ViewingKey));
+    }
+
+    #[test]
+    fn decodes_snip20_errors() {
+        let raw = "failed to execute message; message index: 0: insufficient funds: balance=10, required=20: execute wasm contract failed";
+
+        assert_eq!(
+            ContractError::decode(raw),
+            Some(ContractError::InsufficientFunds)
+        );
+    }
+
+    #[test]
+    fn decodes_encrypted_errors() {
+        let raw = "failed to execute message; message index: 0: encrypted: dGhpcyBpcyBub3QgcmVhbA==: execute wasm contract failed";
+
+        assert_eq!(ContractError::decode(raw), Some(ContractError::Encrypted));
+    }
+
+    #[test]
+    fn keeps_unknown_contract_errors_as_they_are() {
+        for message in [
+            "Viewing key already exists for this address",
+            "Tokens must not be identical to the reward token",
+            "Insufficient liquidity minted",
+        ] {
+            let raw = format!("query contract failed: Generic error: {message}");
+
+            assert_eq!(
+                ContractError::decode(&raw),
+                Some(ContractError::Other(message.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn ignores_errors_from_outside_contracts() {
+        assert_eq!(ContractError::decode("error sending request for url"), None);
+        assert_eq!(
+            ContractError::decode(r#"{"code":5,"message":"not found"}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn captures_placeholder_values() {
+        assert_eq!(
+            captures("Bin step 25 has no preset!", "Bin step {} has no preset!"),
+            Some(vec!["25"])
+        );
+        assert_eq!(
+            captures("Invalid version: 3", "Invalid version: {}"),
+            Some(vec!["3"])
+        );
+        assert_eq!(
+            captures("Lengths mismatch", "Lengths mismatch"),
+            Some(vec![])
+        );
+        assert_eq!(captures("Lengths mismatch!", "Lengths mismatch"), None);
+        assert_eq!(captures("Bin step 25", "Bin step {} has no preset!"), None);
+    }
+
+    #[test]
+    fn decodes_permit_errors() {
+        let unsupported = "query contract failed: Error parsing into type snip20_reference_impl::msg::QueryMsg: unknown variant `with_permit`, expected one of `token_info`, `balance`";
+        let rejected = "query contract failed: Generic error: Failed to verify signatures for the given permit";
+
+        assert_eq!(
+            ContractError::decode(unsupported),
+            Some(ContractError::PermitsUnsupported)
+        );
+        assert_eq!(
+            ContractError::decode(rejected),
+            Some(ContractError::PermitRejected)
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::contract_error::ContractError;
+
 // The Serialize and Deserialize traits are derived to ensure that Errors can be
 // transmitted to or from a server, which is necessary for them to function as Resources.
 #[derive(thiserror::Error, serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
@@ -14,11 +16,17 @@ pub enum Error {
     #[error("Secret error: {0}")]
     Secret(String),
 
+    #[error("{0}")]
+    Contract(ContractError),
+
     #[error("Keplr error: {0}")]
     Keplr(String),
 
     #[error("Keplr is not enabled!")]
     KeplrDisabled,
+
+    #[error("Storage error: {0}")]
+    Storage(String),
 }
 
 impl Error {
@@ -30,6 +38,14 @@ impl Error {
         let message = message.to_string();
         Error::Serde(message)
     }
+    /// Decodes `message` into a [`ContractError`] if it came from a contract.
+    pub fn secret(message: impl ToString) -> Self {
+        let message = message.to_string();
+        match ContractError::decode(&message) {
+            Some(error) => Error::Contract(error),
+            None => Error::Secret(message),
+        }
+    }
 }
 
 impl From<&str> for Error {
@@ -58,19 +74,19 @@ impl From<std::num::ParseFloatError> for Error {
 
 impl From<cosmwasm_std::StdError> for Error {
     fn from(error: cosmwasm_std::StdError) -> Self {
-        Error::Secret(error.to_string())
+        Error::secret(error)
     }
 }
 
 impl From<rsecret::Error> for Error {
     fn from(error: rsecret::Error) -> Self {
-        Error::Secret(error.to_string())
+        Error::secret(error)
     }
 }
 
 impl From<secretrs::ErrorReport> for Error {
     fn from(error: secretrs::ErrorReport) -> Self {
-        Error::Secret(error.to_string())
+        Error::secret(error)
     }
 }
 
@@ -80,8 +96,20 @@ impl From<keplr::Error> for Error {
     }
 }
 
+impl From<rexie::Error> for Error {
+    fn from(error: rexie::Error) -> Self {
+        Error::Storage(error.to_string())
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(error: serde_json::Error) -> Self {
         Error::Serde(error.to_string())
     }
 }
+
+impl From<ContractError> for Error {
+    fn from(error: ContractError) -> Self {
+        Error::Contract(error)
+    }
+}
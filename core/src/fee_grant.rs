@@ -0,0 +1,152 @@
+//! Fee grants, so users without SCRT can still pay for gas.
+//!
+//! When a granter is configured, the manager looks for a grant to the sender before each
+//! transaction. If the grant is active and covers the fee, the granter is set on the fee and pays
+//! it instead of the sender.
+
+use crate::error::Error;
+use prost::Message;
+use secretrs::{
+    proto::cosmos::{
+        base::v1beta1::Coin,
+        feegrant::v1beta1::{
+            query_client::QueryClient, AllowedMsgAllowance, BasicAllowance, PeriodicAllowance,
+            QueryAllowanceRequest,
+        },
+    },
+    Any,
+};
+use tonic_web_wasm_client::Client;
+use tracing::debug;
+use web_sys::js_sys::Date;
+
+/// The address that pays the fees of users it has granted an allowance. Set this to sponsor
+/// transactions on a deployment.
+pub const FEE_GRANTER: Option<&str> = None;
+
+const BASIC_ALLOWANCE: &str = "/cosmos.feegrant.v1beta1.BasicAllowance";
+const PERIODIC_ALLOWANCE: &str = "/cosmos.feegrant.v1beta1.PeriodicAllowance";
+const ALLOWED_MSG_ALLOWANCE: &str = "/cosmos.feegrant.v1beta1.AllowedMsgAllowance";
+const MSG_EXECUTE_CONTRACT: &str = "/secret.compute.v1beta1.MsgExecuteContract";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeGrant {
+    pub granter: String,
+    pub grantee: String,
+    /// In uscrt. `None` means there is no limit.
+    pub spend_limit: Option<u128>,
+    /// Seconds since the Unix epoch.
+    pub expiration: Option<i64>,
+    /// Whether the grant can pay for contract executions at all.
+    pub allows_execute: bool,
+}
+
+impl FeeGrant {
+    pub fn is_expired(&self) -> bool {
+        self.expiration
+            .is_some_and(|expiration| expiration as f64 <= Date::now() / 1000.0)
+    }
+
+    /// Whether the grant can pay a fee of `amount` uscrt right now.
+    pub fn covers(&self, amount: u128) -> bool {
+        self.allows_execute
+            && !self.is_expired()
+            && self.spend_limit.is_none_or(|limit| limit >= amount)
+    }
+}
+
+/// Looks up the grant from `granter` to `grantee`. Returns `None` if there isn't one.
+pub async fn query(url: &str, granter: &str, grantee: &str) -> Result<Option<FeeGrant>, Error> {
+    let response = QueryClient::new(Client::new(url.to_string()))
+        .allowance(QueryAllowanceRequest {
+            granter: granter.to_string(),
+            grantee: grantee.to_string(),
+        })
+        .await;
+
+    let grant = match response {
+        Ok(response) => response.into_inner().allowance,
+        // the node reports a missing grant as an error
+        Err(status) => {
+            debug!("no fee grant for {grantee}: {}", status.message());
+            return Ok(None);
+        }
+    };
+
+    let Some(allowance) = grant.and_then(|grant| grant.allowance) else {
+        return Ok(None);
+    };
+
+    let mut fee_grant = FeeGrant {
+        granter: granter.to_string(),
+        grantee: grantee.to_string(),
+        spend_limit: None,
+        expiration: None,
+        allows_execute: true,
+    };
+    read_allowance(&allowance, &mut fee_grant)?;
+
+    Ok(Some(fee_grant))
+}
+
+/// Fills in the limits of `allowance`, unwrapping the allowance kinds that wrap others.
+fn read_allowance(allowance: &Any, fee_grant: &mut FeeGrant) -> Result<(), Error> {
+    match allowance.type_url.as_str() {
+        BASIC_ALLOWANCE => {
+            let basic =
+                BasicAllowance::decode(allowance.value.as_slice()).map_err(Error::generic)?;
+            read_basic(&basic, fee_grant);
+        }
+        PERIODIC_ALLOWANCE => {
+            let periodic =
+                PeriodicAllowance::decode(allowance.value.as_slice()).map_err(Error::generic)?;
+            if let Some(basic) = &periodic.basic {
+                read_basic(basic, fee_grant);
+            }
+            // what is left in this period is the tighter limit
+            let period_can_spend = uscrt(&periodic.period_can_spend);
+            fee_grant.spend_limit = match (fee_grant.spend_limit, period_can_spend) {
+                (Some(limit), Some(period)) => Some(limit.min(period)),
+                (limit, period) => limit.or(period),
+            };
+        }
+        ALLOWED_MSG_ALLOWANCE => {
+            let allowed =
+                AllowedMsgAllowance::decode(allowance.value.as_slice()).map_err(Error::generic)?;
+            fee_grant.allows_execute = allowed
+                .allowed_messages
+                .iter()
+                .any(|msg| msg == MSG_EXECUTE_CONTRACT);
+            if let Some(inner) = &allowed.allowance {
+                read_allowance(inner, fee_grant)?;
+            }
+        }
+        other => {
+            return Err(Error::generic(format!(
+                "unknown fee allowance type {other}"
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+fn read_basic(basic: &BasicAllowance, fee_grant: &mut FeeGrant) {
+    fee_grant.spend_limit = uscrt(&basic.spend_limit);
+    fee_grant.expiration = basic.expiration.as_ref().map(|time| time.seconds);
+}
+
+/// The uscrt amount in `coins`. An empty list means no limit.
+fn uscrt(coins: &[Coin]) -> Option<u128> {
+    if coins.is_empty() {
+        return None;
+    }
+
+    Some(
+        coins
+            .iter()
+            .find(|coin| coin.denom == "uscrt")
+            .and_then(|coin| coin.amount.parse().ok())
+            .unwrap_or_default(),
+    )
+}
@@ -0,0 +1,358 @@
+//! Gas estimation and fees.
+//!
+//! Transactions are simulated before they are signed. The gas used is scaled by a multiplier to
+//! get the gas limit, and the fee is the gas limit times the gas price of the chosen
+//! [`FeeTier`]. A simulation that fails with a contract error stops the transaction before the
+//! wallet is ever asked to sign.
+
+use crate::{constants::CHAIN_INFO, error::Error, CHAIN_ID};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use keplr::{experimental::GasPriceStep, EnigmaUtils, Key};
+use leptos::prelude::window;
+use prost::Message;
+use secretrs::{
+    compute::MsgExecuteContract,
+    crypto::PublicKey,
+    proto::cosmos::{
+        auth::v1beta1::{query_client::QueryClient, BaseAccount, QueryAccountRequest},
+        tx::v1beta1::{service_client::ServiceClient, SimulateRequest, TxRaw},
+    },
+    tx::{Body, Fee, Msg, SignerInfo},
+    utils::encryption::SecretUtils,
+    Coin, Denom,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tonic_web_wasm_client::Client;
+use tracing::debug;
+
+/// Simulated gas is scaled by this much, because execution can cost a little more by the time
+/// the transaction lands in a block.
+pub const DEFAULT_GAS_MULTIPLIER: f64 = 1.3;
+
+const FEE_TIER_STORAGE_KEY: &str = "fee_tier";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeeTier {
+    Low,
+    #[default]
+    Average,
+    High,
+}
+
+impl FeeTier {
+    pub const ALL: [FeeTier; 3] = [FeeTier::Low, FeeTier::Average, FeeTier::High];
+
+    /// The gas price in uscrt, from the steps in the chain's fee config.
+    pub fn gas_price(self) -> f64 {
+        let step = gas_price_step();
+        match self {
+            FeeTier::Low => step.low,
+            FeeTier::Average => step.average,
+            FeeTier::High => step.high,
+        }
+    }
+
+    /// The tier chosen last time.
+    pub fn load() -> Self {
+        window()
+            .local_storage()
+            .ok()
+            .flatten()
+            .and_then(|storage| storage.get_item(FEE_TIER_STORAGE_KEY).ok().flatten())
+            .and_then(|tier| serde_json::from_str(&tier).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn store(self) {
+        if let Some(storage) = window().local_storage().ok().flatten() {
+            let tier = serde_json::to_string(&self).expect("fee tier is serializable");
+            _ = storage.set_item(FEE_TIER_STORAGE_KEY, &tier);
+        }
+    }
+}
+
+/// The gas price steps of uscrt on this chain, the same ones the wallet is told about.
+pub fn gas_price_step() -> &'static GasPriceStep {
+    CHAIN_INFO
+        .fee_currencies
+        .iter()
+        .find(|currency| currency.coin_minimal_denom == "uscrt")
+        .map(|currency| &currency.gas_price_step)
+        .expect("the chain config has no uscrt fee currency")
+}
+
+impl fmt::Display for FeeTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeeTier::Low => write!(f, "Low"),
+            FeeTier::Average => write!(f, "Average"),
+            FeeTier::High => write!(f, "High"),
+        }
+    }
+}
+
+/// The gas and fee of a simulated transaction.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    pub gas_used: u64,
+    pub gas_limit: u64,
+    pub tier: FeeTier,
+    /// In uscrt.
+    pub amount: u128,
+    /// Who pays the fee in place of the sender, through a fee grant.
+    #[serde(default)]
+    pub granter: Option<String>,
+}
+
+impl FeeEstimate {
+    pub fn new(gas_used: u64, multiplier: f64, tier: FeeTier) -> Self {
+        let gas_limit = (gas_used as f64 * multiplier).ceil() as u64;
+        let amount = (gas_limit as f64 * tier.gas_price()).ceil() as u128;
+
+        Self {
+            gas_used,
+            gas_limit,
+            tier,
+            amount,
+            granter: None,
+        }
+    }
+
+    /// The fee of the same gas limit at another tier.
+    pub fn with_tier(&self, tier: FeeTier) -> Self {
+        Self {
+            tier,
+            amount: (self.gas_limit as f64 * tier.gas_price()).ceil() as u128,
+            ..self.clone()
+        }
+    }
+
+    /// Who pays the fee, for display.
+    pub fn payer(&self) -> String {
+        match &self.granter {
+            Some(granter) => format!("paid by {}", crate::utils::shorten_address(granter)),
+            None => "paid by you".to_string(),
+        }
+    }
+
+    /// The fee in SCRT, for display.
+    pub fn scrt(&self) -> String {
+        crate::utils::display_token_amount(self.amount, 6u8)
+    }
+}
+
+/// Simulates `msgs` as a transaction from `key`, and returns the gas it used.
+///
+/// Contract errors come back encrypted, so they are decrypted with `enigma_utils` and decoded
+/// into an [`Error::Contract`] where possible.
+pub async fn simulate(
+    url: &str,
+    key: &Key,
+    msgs: &[MsgExecuteContract],
+    enigma_utils: &EnigmaUtils,
+) -> Result<u64, Error> {
+    let sequence = account_sequence(url, &key.bech32_address).await?;
+
+    let public_key = PublicKey::from_raw_secp256k1(&key.pub_key)
+        .ok_or(Error::generic("the wallet returned an invalid public key"))?;
+
+    let messages = msgs
+        .iter()
+        .map(Msg::to_any)
+        .collect::<Result<Vec<_>, _>>()?;
+    let body = Body::new(messages, "", 0u32);
+
+    // the fee doesn't matter to the simulation
+    let fee = Fee::from_amount_and_gas(
+        Coin {
+            denom: "uscrt".parse::<Denom>()?,
+            amount: 0,
+        },
+        0u64,
+    );
+    let auth_info = SignerInfo::single_direct(Some(public_key), sequence).auth_info(fee);
+
+    let tx_bytes = TxRaw {
+        body_bytes: body.into_bytes()?,
+        auth_info_bytes: auth_info.into_bytes()?,
+        // signatures aren't checked in simulation, but there has to be one per signer
+        signatures: vec![vec![]],
+    }
+    .encode_to_vec();
+
+    #[allow(deprecated)]
+    let request = SimulateRequest {
+        tx_bytes,
+        ..Default::default()
+    };
+
+    let response = ServiceClient::new(Client::new(url.to_string()))
+        .simulate(request)
+        .await;
+
+    match response {
+        Ok(response) => {
+            let gas_used = response
+                .into_inner()
+                .gas_info
+                .map(|gas_info| gas_info.gas_used)
+                .ok_or(Error::generic("the simulation returned no gas info"))?;
+            debug!(
+                "simulated {} messages on {CHAIN_ID}: {gas_used} gas",
+                msgs.len()
+            );
+
+            Ok(gas_used)
+        }
+        Err(status) => {
+            let message = decrypt_error(status.message(), msgs, enigma_utils).await;
+            Err(Error::secret(message))
+        }
+    }
+}
+
+async fn account_sequence(url: &str, address: &str) -> Result<u64, Error> {
+    let response = QueryClient::new(Client::new(url.to_string()))
+        .account(QueryAccountRequest {
+            address: address.to_string(),
+        })
+        .await
+        .map_err(|status| Error::secret(status.message()))?;
+
+    let account = response
+        .into_inner()
+        .account
+        .ok_or(Error::generic("account not found"))?;
+
+    sequence(&account.type_url, &account.value)
+}
+
+const BASE_ACCOUNT: &str = "/cosmos.auth.v1beta1.BaseAccount";
+
+const VESTING_ACCOUNTS: [&str; 4] = [
+    "/cosmos.vesting.v1beta1.ContinuousVestingAccount",
+    "/cosmos.vesting.v1beta1.DelayedVestingAccount",
+    "/cosmos.vesting.v1beta1.PeriodicVestingAccount",
+    "/cosmos.vesting.v1beta1.PermanentLockedAccount",
+];
+
+/// The first field of every vesting account type. The rest is left undecoded.
+#[derive(Clone, PartialEq, prost::Message)]
+struct VestingAccount {
+    #[prost(message, optional, tag = "1")]
+    base_vesting_account: Option<BaseVestingAccount>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct BaseVestingAccount {
+    #[prost(message, optional, tag = "1")]
+    base_account: Option<BaseAccount>,
+}
+
+/// Reads the sequence of an account returned by the auth module. Module accounts and unknown
+/// types can't sign transactions, so they are an error.
+fn sequence(type_url: &str, value: &[u8]) -> Result<u64, Error> {
+    let account = match type_url {
+        BASE_ACCOUNT => BaseAccount::decode(value).map_err(Error::generic)?,
+        type_url if VESTING_ACCOUNTS.contains(&type_url) => VestingAccount::decode(value)
+            .map_err(Error::generic)?
+            .base_vesting_account
+            .and_then(|vesting| vesting.base_account)
+            .ok_or(Error::generic("the vesting account has no base account"))?,
+        type_url => {
+            return Err(Error::generic(format!(
+                "can't sign for an account of type {type_url}"
+            )))
+        }
+    };
+
+    Ok(account.sequence)
+}
+
+/// Replaces `encrypted: <base64>` in `message` with the decrypted error, if it can be.
+///
+/// The message index in the error says which message failed, and the first 32 bytes of that
+/// message are the nonce it was encrypted with.
+pub async fn decrypt_error(
+    message: &str,
+    msgs: &[MsgExecuteContract],
+    enigma_utils: &EnigmaUtils,
+) -> String {
+    let Some((before, rest)) = message.split_once("encrypted: ") else {
+        return message.to_string();
+    };
+    let (ciphertext, after) = rest.split_once(':').unwrap_or((rest, ""));
+
+    let index = message
+        .split_once("message index: ")
+        .and_then(|(_, rest)| rest.split(|c: char| !c.is_ascii_digit()).next())
+        .and_then(|index| index.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let Some(nonce) = msgs
+        .get(index)
+        .and_then(|msg| msg.msg.get(..32))
+        .and_then(|nonce| <[u8; 32]>::try_from(nonce).ok())
+    else {
+        return message.to_string();
+    };
+
+    let Ok(ciphertext) = BASE64_STANDARD.decode(ciphertext.trim()) else {
+        return message.to_string();
+    };
+
+    match enigma_utils.decrypt(&nonce, &ciphertext).await {
+        Ok(plaintext) => {
+            let plaintext = String::from_utf8_lossy(&plaintext);
+            format!("{before}{plaintext}:{after}")
+        }
+        Err(error) => {
+            debug!("could not decrypt the error: {error}");
+            message.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_account(sequence: u64) -> BaseAccount {
+        BaseAccount {
+            address: "secret1ap26qrlp8mcq2pg6r47w43l0y8zkqm8a450s03".to_string(),
+            account_number: 12,
+            sequence,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reads_the_sequence_of_a_base_account() {
+        let value = base_account(7).encode_to_vec();
+
+        assert_eq!(sequence(BASE_ACCOUNT, &value), Ok(7));
+    }
+
+    #[test]
+    fn reads_the_sequence_of_a_vesting_account() {
+        let value = VestingAccount {
+            base_vesting_account: Some(BaseVestingAccount {
+                base_account: Some(base_account(9)),
+            }),
+        }
+        .encode_to_vec();
+
+        assert_eq!(
+            sequence("/cosmos.vesting.v1beta1.ContinuousVestingAccount", &value),
+            Ok(9)
+        );
+    }
+
+    #[test]
+    fn rejects_module_accounts() {
+        let value = base_account(3).encode_to_vec();
+
+        assert!(sequence("/cosmos.auth.v1beta1.ModuleAccount", &value).is_err());
+    }
+}
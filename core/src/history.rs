@@ -0,0 +1,175 @@
+//! Transaction history, persisted per address in IndexedDB.
+
+use crate::{
+    error::Error,
+    tx::{decrypt_tx, TxKind, TxRecord, TxStatus},
+    CHAIN_ID,
+};
+use keplr::wallet;
+use leptos::wasm_bindgen::JsValue;
+use rexie::{Index, KeyRange, ObjectStore, Rexie, TransactionMode};
+use secretrs::proto::cosmos::tx::v1beta1::{
+    service_client::ServiceClient, GetTxsEventRequest, OrderBy,
+};
+use serde::{Deserialize, Serialize};
+use tonic_web_wasm_client::Client;
+use tracing::{debug, warn};
+use web_sys::js_sys::Date;
+
+const DATABASE: &str = "ammber";
+const TX_HISTORY: &str = "tx_history";
+const BY_ADDRESS: &str = "address";
+
+#[derive(Serialize, Deserialize)]
+struct StoredRecord {
+    /// The tx hash, or a local key for transactions that never made it on chain.
+    key: String,
+    address: String,
+    record: TxRecord,
+}
+
+async fn open() -> Result<Rexie, Error> {
+    Rexie::builder(DATABASE)
+        .version(1)
+        .add_object_store(
+            ObjectStore::new(TX_HISTORY)
+                .key_path("key")
+                .add_index(Index::new(BY_ADDRESS, "address")),
+        )
+        .build()
+        .await
+        .map_err(Error::from)
+}
+
+pub async fn save(address: &str, record: &TxRecord) -> Result<(), Error> {
+    let stored = StoredRecord {
+        key: record
+            .hash
+            .clone()
+            .unwrap_or_else(|| format!("local-{}", record.submitted_at)),
+        address: address.to_string(),
+        record: record.clone(),
+    };
+    let value = serde_wasm_bindgen::to_value(&stored).map_err(Error::serde)?;
+
+    let db = open().await?;
+    let transaction = db.transaction(&[TX_HISTORY], TransactionMode::ReadWrite)?;
+    transaction.store(TX_HISTORY)?.put(&value, None).await?;
+    transaction.done().await?;
+
+    Ok(())
+}
+
+/// Returns the saved transactions of `address`, newest first.
+pub async fn load(address: &str) -> Result<Vec<TxRecord>, Error> {
+    let db = open().await?;
+    let transaction = db.transaction(&[TX_HISTORY], TransactionMode::ReadOnly)?;
+    let values = transaction
+        .store(TX_HISTORY)?
+        .index(BY_ADDRESS)?
+        .get_all(Some(KeyRange::only(&JsValue::from_str(address))?), None)
+        .await?;
+
+    let mut records = values
+        .into_iter()
+        .filter_map(|value| {
+            serde_wasm_bindgen::from_value::<StoredRecord>(value)
+                .inspect_err(|error| warn!("skipping unreadable history record: {error}"))
+                .ok()
+        })
+        .map(|stored| stored.record)
+        .collect::<Vec<_>>();
+
+    records.sort_by(|a, b| b.submitted_at.total_cmp(&a.submitted_at));
+
+    Ok(records)
+}
+
+pub async fn clear(address: &str) -> Result<(), Error> {
+    let records = load(address).await?;
+
+    let db = open().await?;
+    let transaction = db.transaction(&[TX_HISTORY], TransactionMode::ReadWrite)?;
+    let store = transaction.store(TX_HISTORY)?;
+
+    for record in records {
+        let key = record
+            .hash
+            .unwrap_or_else(|| format!("local-{}", record.submitted_at));
+        store.delete(JsValue::from_str(&key)).await?;
+    }
+    transaction.done().await?;
+
+    Ok(())
+}
+
+/// Searches the node for past transactions from `address` to `contract` and adds any that are
+/// missing from the saved history. Returns the number of transactions added.
+///
+/// Only the first page of results is read, newest first, so this is capped at the node's default
+/// page size (100). Older transactions than that are not recovered.
+pub async fn backfill(url: &str, address: &str, contract: &str) -> Result<usize, Error> {
+    let known = load(address)
+        .await?
+        .into_iter()
+        .filter_map(|record| record.hash)
+        .collect::<Vec<_>>();
+
+    let request = GetTxsEventRequest {
+        events: vec![
+            format!("message.sender='{address}'"),
+            format!("wasm.contract_address='{contract}'"),
+        ],
+        order_by: OrderBy::Desc as i32,
+        ..Default::default()
+    };
+
+    let tx_responses = ServiceClient::new(Client::new(url.to_string()))
+        .get_txs_event(request)
+        .await
+        .map_err(|status| Error::generic(status.message()))?
+        .into_inner()
+        .tx_responses;
+
+    debug!("found {} transactions on chain", tx_responses.len());
+
+    let mut added = 0;
+
+    for tx_response in tx_responses
+        .into_iter()
+        .filter(|tx_response| !known.contains(&tx_response.txhash))
+    {
+        let hash = tx_response.txhash.clone();
+        let height = tx_response.height as u64;
+        let gas_used = tx_response.gas_used as u64;
+        let submitted_at = Date::parse(&tx_response.timestamp);
+
+        let tx = decrypt_tx(wallet::active().get_enigma_utils(CHAIN_ID)?, tx_response).await?;
+
+        let status = if tx.code == 0 {
+            TxStatus::Included
+        } else {
+            TxStatus::Failed(Error::secret(&tx.logs))
+        };
+
+        let record = TxRecord {
+            id: 0,
+            kind: TxKind::Other,
+            tokens: vec![],
+            sender: Some(address.to_string()),
+            status,
+            hash: Some(hash),
+            height: Some(height),
+            gas_used: Some(gas_used),
+            fee: None,
+            data: tx.data,
+            logs: Some(tx.logs),
+            submitted_at,
+        };
+
+        save(address, &record).await?;
+        added += 1;
+    }
+
+    Ok(added)
+}
@@ -1,13 +1,28 @@
+pub mod account;
+pub mod allowance;
 pub mod constants;
+pub mod contract_error;
 mod error;
+pub mod fee_grant;
+pub mod gas;
+pub mod history;
+pub mod limit_order;
+pub mod permit;
+pub mod position;
 pub mod prelude;
+pub mod price;
 pub mod state;
 pub mod support;
+pub mod tx;
 pub mod types;
 pub mod utils;
+pub mod viewing_key;
 
+pub use account::Account;
 pub use constants::{CHAIN_ID, NODE, TOKEN_MAP};
+pub use contract_error::ContractError;
 pub use error::Error;
-pub use state::{ChainId, Endpoint, KeplrSignals, TokenMap};
+pub use state::{Balances, ChainId, Endpoint, TokenMap, WalletSignals};
+pub use tx::{TxBatch, TxKind, TxManager, TxRecord, TxStatus, TxSummary};
 
 pub const BASE_URL: &str = "/liquidity-book-leptos";
@@ -0,0 +1,200 @@
+//! Limit orders placed as single-bin liquidity.
+//!
+//! A bin above the active price only holds token X, and a bin below it only holds token Y. When
+//! swaps move the price through the bin, its liquidity converts to the other token, which is
+//! exactly a filled limit order. Orders are kept in local storage per user, and their fill state
+//! is read back from the composition of the bin.
+//!
+//! An order only owns the LB tokens its deposit minted. The user may hold more in the same bin,
+//! from another order or as regular liquidity, and none of that is counted or withdrawn with it.
+
+use crate::{constants::CHAIN_ID, permit, support::ILbPair, Error};
+use ammber_sdk::utils::get_price_from_id;
+use cosmwasm_std::{ContractInfo, Uint128, Uint256};
+use leptos::prelude::window;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::warn;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LimitOrder {
+    pub lb_pair: ContractInfo,
+    pub token_x: ContractInfo,
+    pub token_y: ContractInfo,
+    pub bin_step: u16,
+    pub bin_id: u32,
+    /// Selling token X for token Y, or the other way around.
+    pub sell_x: bool,
+    /// Of the token being sold.
+    pub amount: Uint128,
+    /// The LB tokens minted by the deposit, from the router's response.
+    pub liquidity: Uint256,
+    /// Milliseconds since the Unix epoch.
+    pub placed_at: f64,
+}
+
+impl LimitOrder {
+    /// The price of the bin, in raw units of token Y per raw unit of token X.
+    pub fn price(&self) -> f64 {
+        get_price_from_id(self.bin_id.to_string(), self.bin_step)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FillStatus {
+    Open,
+    PartiallyFilled,
+    Filled,
+    /// The liquidity has already been withdrawn.
+    Closed,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderState {
+    pub status: FillStatus,
+    /// Between 0 and 1.
+    pub filled: f64,
+    /// The share of the bin reserves owned by the order.
+    pub amount_x: Uint128,
+    pub amount_y: Uint128,
+    /// The LB tokens to burn to withdraw.
+    pub liquidity: Uint256,
+}
+
+fn storage_key(owner: &str) -> String {
+    format!("limit_orders_{CHAIN_ID}_{owner}")
+}
+
+/// Returns the orders of `owner`, newest first.
+pub fn load(owner: &str) -> Vec<LimitOrder> {
+    window()
+        .local_storage()
+        .ok()
+        .flatten()
+        .and_then(|storage| storage.get_item(&storage_key(owner)).ok().flatten())
+        .and_then(|orders| {
+            serde_json::from_str(&orders)
+                .inspect_err(|error| warn!("discarding unreadable limit orders: {error}"))
+                .ok()
+        })
+        .unwrap_or_default()
+}
+
+fn save(owner: &str, orders: &[LimitOrder]) {
+    if let Some(storage) = window().local_storage().ok().flatten() {
+        let orders = serde_json::to_string(orders).expect("limit orders are serializable");
+        _ = storage.set_item(&storage_key(owner), &orders);
+    }
+}
+
+pub fn remember(owner: &str, order: LimitOrder) {
+    let mut orders = load(owner);
+    orders.insert(0, order);
+    save(owner, &orders);
+}
+
+pub fn forget(owner: &str, order: &LimitOrder) {
+    let mut orders = load(owner);
+    orders.retain(|other| other != order);
+    save(owner, &orders);
+}
+
+/// The LB tokens held by the orders of `owner` in `lb_pair`, by bin.
+fn reserved(owner: &str, lb_pair: &ContractInfo) -> HashMap<u32, Uint256> {
+    let mut reserved: HashMap<u32, Uint256> = HashMap::new();
+
+    for order in load(owner) {
+        if order.lb_pair == *lb_pair {
+            let liquidity = reserved.entry(order.bin_id).or_default();
+            *liquidity = liquidity.saturating_add(order.liquidity);
+        }
+    }
+
+    reserved
+}
+
+/// Takes the liquidity held by the orders of `owner` out of their balances in `lb_pair`, leaving
+/// only the regular position. Bins that only hold orders are left out.
+pub fn exclude_orders(
+    owner: &str,
+    lb_pair: &ContractInfo,
+    ids: Vec<u32>,
+    amounts: Vec<Uint256>,
+) -> (Vec<u32>, Vec<Uint256>) {
+    let reserved = reserved(owner, lb_pair);
+
+    ids.into_iter()
+        .zip(amounts)
+        .map(|(id, amount)| {
+            let reserved = reserved.get(&id).copied().unwrap_or_default();
+            (id, amount.saturating_sub(reserved))
+        })
+        .filter(|(_, amount)| !amount.is_zero())
+        .unzip()
+}
+
+/// Reads how much of `order` has been filled, from its share of the bin.
+pub async fn state(owner: &str, order: &LimitOrder) -> Result<OrderState, Error> {
+    let lb_pair = ILbPair(order.lb_pair.clone());
+
+    let balance = permit::lb_balances(&order.lb_pair, owner, vec![order.bin_id])
+        .await?
+        .first()
+        .copied()
+        .unwrap_or_default();
+    // anything above what the order minted belongs to something else
+    let liquidity = order.liquidity.min(balance);
+
+    if liquidity.is_zero() {
+        return Ok(OrderState {
+            status: FillStatus::Closed,
+            filled: 0.0,
+            amount_x: Uint128::zero(),
+            amount_y: Uint128::zero(),
+            liquidity,
+        });
+    }
+
+    let bin = lb_pair.get_bin(order.bin_id).await?;
+    let total_supply = lb_pair.total_supply(order.bin_id).await?;
+
+    let share = |reserve: Uint128| {
+        Uint256::from(reserve)
+            .checked_multiply_ratio(liquidity, total_supply)
+            .ok()
+            .and_then(|amount| Uint128::try_from(amount).ok())
+            .unwrap_or_default()
+    };
+    let amount_x = share(bin.bin_reserve_x);
+    let amount_y = share(bin.bin_reserve_y);
+
+    // both sides valued in token Y
+    let value_x = amount_x.u128() as f64 * order.price();
+    let value_y = amount_y.u128() as f64;
+    let total = value_x + value_y;
+
+    let filled = if total == 0.0 {
+        0.0
+    } else if order.sell_x {
+        value_y / total
+    } else {
+        value_x / total
+    };
+
+    let remaining = if order.sell_x { amount_x } else { amount_y };
+    let status = if remaining.is_zero() {
+        FillStatus::Filled
+    } else if filled > 0.0 {
+        FillStatus::PartiallyFilled
+    } else {
+        FillStatus::Open
+    };
+
+    Ok(OrderState {
+        status,
+        filled,
+        amount_x,
+        amount_y,
+        liquidity,
+    })
+}
@@ -0,0 +1,513 @@
+//! SNIP-24 query permits.
+//!
+//! A permit is signed once per address and chain, cached in local storage, and then used for
+//! authenticated queries in place of viewing keys. Whether a contract accepts permits at all is
+//! checked before signing, and remembered, so contracts without permit support never cause a
+//! wallet prompt.
+
+use crate::{
+    constants::TOKEN_MAP,
+    contract_error::ContractError,
+    error::Error,
+    support::{chain_batch_query, chain_query, ILbPair},
+    viewing_key, CHAIN_ID,
+};
+use ammber_sdk::contract_interfaces::lb_pair;
+use batch_query::{BatchItemResponseStatus, BatchQueryParams, BatchQueryParsedResponse};
+use cosmwasm_std::{ContractInfo, Uint128, Uint256};
+use futures::{future::join_all, lock::Mutex};
+use keplr::wallet;
+use leptos::prelude::window;
+use rsecret::wallet::{wallet_amino::StdSignDoc, Signer};
+use secret_toolkit_snip20::BalanceResponse;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use std::{collections::HashMap, future::Future, sync::LazyLock};
+use tracing::{debug, warn};
+
+const PERMIT_NAME: &str = "ammber";
+
+/// Held while a permit is being signed, so concurrent callers wait for it and reuse it instead of
+/// each signing (and storing) a permit of their own.
+static SIGNING: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Permit {
+    pub params: PermitParams,
+    pub signature: PermitSignature,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PermitParams {
+    pub permit_name: String,
+    pub allowed_tokens: Vec<String>,
+    pub chain_id: String,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Allowance,
+    Balance,
+    History,
+    Owner,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PermitSignature {
+    pub pub_key: PubKey,
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PubKey {
+    pub r#type: String,
+    pub value: String,
+}
+
+impl Permit {
+    pub fn allows(&self, contract_address: &str) -> bool {
+        self.params
+            .allowed_tokens
+            .iter()
+            .any(|token| token == contract_address)
+    }
+
+    /// A permit with an empty signature. Contracts that support permits reject it for the
+    /// signature, and contracts that don't reject the `with_permit` query itself.
+    fn unsigned(contract_address: &str) -> Self {
+        Self {
+            params: PermitParams {
+                permit_name: PERMIT_NAME.to_string(),
+                allowed_tokens: vec![contract_address.to_string()],
+                chain_id: CHAIN_ID.to_string(),
+                permissions: vec![Permission::Balance],
+            },
+            signature: PermitSignature {
+                pub_key: PubKey {
+                    r#type: "tendermint/PubKeySecp256k1".to_string(),
+                    value: String::new(),
+                },
+                signature: String::new(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum PermitQuery<Q> {
+    WithPermit { permit: Permit, query: Q },
+}
+
+fn storage_key(address: &str) -> String {
+    format!("query_permit_{CHAIN_ID}_{address}")
+}
+
+fn load(address: &str) -> Option<Permit> {
+    let storage = window().local_storage().ok().flatten()?;
+    let permit = storage.get_item(&storage_key(address)).ok().flatten()?;
+
+    serde_json::from_str(&permit)
+        .inspect_err(|error| warn!("discarding unreadable permit: {error}"))
+        .ok()
+}
+
+fn store(address: &str, permit: &Permit) {
+    if let Some(storage) = window().local_storage().ok().flatten() {
+        let permit = serde_json::to_string(permit).expect("permit is serializable");
+        _ = storage.set_item(&storage_key(address), &permit);
+    }
+}
+
+fn support_storage_key() -> String {
+    format!("permit_support_{CHAIN_ID}")
+}
+
+fn load_support() -> HashMap<String, bool> {
+    window()
+        .local_storage()
+        .ok()
+        .flatten()
+        .and_then(|storage| storage.get_item(&support_storage_key()).ok().flatten())
+        .and_then(|support| serde_json::from_str(&support).ok())
+        .unwrap_or_default()
+}
+
+fn remember_support(contract_address: &str, supported: bool) {
+    let mut support = load_support();
+    support.insert(contract_address.to_string(), supported);
+
+    if let Some(storage) = window().local_storage().ok().flatten() {
+        let support = serde_json::to_string(&support).expect("permit support is serializable");
+        _ = storage.set_item(&support_storage_key(), &support);
+    }
+}
+
+/// Removes the cached permit for `address`.
+pub fn forget(address: &str) {
+    if let Some(storage) = window().local_storage().ok().flatten() {
+        _ = storage.remove_item(&storage_key(address));
+    }
+}
+
+/// Asks the wallet to sign a permit for `allowed_tokens`.
+pub async fn sign(address: &str, allowed_tokens: Vec<String>) -> Result<Permit, Error> {
+    let permissions = vec![
+        Permission::Allowance,
+        Permission::Balance,
+        Permission::History,
+    ];
+
+    let sign_doc: StdSignDoc<serde_json::Value> = serde_json::from_value(json!({
+        "chain_id": CHAIN_ID,
+        "account_number": "0",
+        "sequence": "0",
+        "fee": {
+            "amount": [{ "denom": "uscrt", "amount": "0" }],
+            "gas": "1",
+        },
+        "msgs": [{
+            "type": "query_permit",
+            "value": {
+                "permit_name": PERMIT_NAME,
+                "allowed_tokens": allowed_tokens,
+                "permissions": permissions,
+            },
+        }],
+        "memo": "",
+    }))?;
+
+    let response = wallet::active()
+        .get_offline_signer_auto(CHAIN_ID)
+        .await?
+        .sign_permit(address, sign_doc)
+        .await
+        .map_err(|error| Error::Keplr(error.to_string()))?;
+
+    let signature = serde_json::to_value(response)?
+        .get_mut("signature")
+        .map(serde_json::Value::take)
+        .ok_or(Error::generic("the wallet did not return a signature"))?;
+
+    Ok(Permit {
+        params: PermitParams {
+            permit_name: PERMIT_NAME.to_string(),
+            allowed_tokens,
+            chain_id: CHAIN_ID.to_string(),
+            permissions,
+        },
+        signature: serde_json::from_value(signature)?,
+    })
+}
+
+/// Returns a permit for `address` that covers `contract_address`.
+pub async fn get_or_sign(address: &str, contract_address: &str) -> Result<Permit, Error> {
+    get_or_sign_all(address, &[contract_address]).await
+}
+
+/// Returns a permit for `address` that covers every one of `contract_addresses`.
+///
+/// The cached permit is reused when it already covers them. Otherwise a new one is signed for
+/// every known token, every contract the cached permit covered, and the new contracts, so the
+/// wallet prompts as rarely as possible.
+pub async fn get_or_sign_all(address: &str, contract_addresses: &[&str]) -> Result<Permit, Error> {
+    let _signing = SIGNING.lock().await;
+
+    // another caller may have signed a permit that covers these while this one waited
+    let cached = load(address);
+
+    if let Some(permit) = cached.as_ref().filter(|permit| {
+        contract_addresses
+            .iter()
+            .all(|contract_address| permit.allows(contract_address))
+    }) {
+        return Ok(permit.clone());
+    }
+
+    let mut allowed_tokens = TOKEN_MAP.keys().cloned().collect::<Vec<_>>();
+    for token in cached
+        .into_iter()
+        .flat_map(|permit| permit.params.allowed_tokens)
+        .chain(contract_addresses.iter().map(|address| address.to_string()))
+    {
+        if !allowed_tokens.contains(&token) {
+            allowed_tokens.push(token);
+        }
+    }
+
+    debug!(
+        "signing a query permit for {} contracts",
+        allowed_tokens.len()
+    );
+
+    let permit = sign(address, allowed_tokens).await?;
+    store(address, &permit);
+
+    Ok(permit)
+}
+
+/// Wraps `query` in a SNIP-24 `with_permit` query.
+pub async fn query_with_permit<T>(
+    contract: &ContractInfo,
+    permit: Permit,
+    query: impl Serialize + Send + Sync + 'static,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned + 'static,
+{
+    chain_query::<T>(
+        contract.code_hash.clone(),
+        contract.address.to_string(),
+        PermitQuery::WithPermit { permit, query },
+    )
+    .await
+}
+
+/// Whether `error` means the contract or wallet can't handle permits at all, as opposed to a
+/// permit that was rejected or a query that failed for another reason.
+pub fn is_unsupported(error: &Error) -> bool {
+    rejects_permits(error)
+        || (matches!(error, Error::Keplr(_)) && error.to_string().contains("not supported"))
+}
+
+/// Whether `error` is a contract refusing the `with_permit` query itself.
+fn rejects_permits(error: &Error) -> bool {
+    matches!(error, Error::Contract(ContractError::PermitsUnsupported))
+}
+
+/// A query that `contract` would answer, so a probe can only fail on the permit. Tokens are
+/// SNIP-20 contracts, and everything else queried with permits is an LB pair.
+fn probe_query(contract: &ContractInfo) -> serde_json::Value {
+    if TOKEN_MAP.contains_key(contract.address.as_str()) {
+        json!({ "balance": {} })
+    } else {
+        json!({ "balance_of_batch": { "accounts": [], "ids": [] } })
+    }
+}
+
+/// Whether `contract` accepts SNIP-24 `with_permit` queries.
+///
+/// The first time, the contract is sent a query with an unsigned permit, which needs no wallet
+/// prompt. The answer is remembered only if the contract either rejects the permit or doesn't
+/// know the query. Any other error says nothing about permits, so viewing keys are used this
+/// time and the contract is probed again next time.
+pub async fn supports_permits(contract: &ContractInfo) -> Result<bool, Error> {
+    if let Some(&supported) = load_support().get(contract.address.as_str()) {
+        return Ok(supported);
+    }
+
+    let probe = chain_query::<serde_json::Value>(
+        contract.code_hash.clone(),
+        contract.address.to_string(),
+        PermitQuery::WithPermit {
+            permit: Permit::unsigned(contract.address.as_str()),
+            query: probe_query(contract),
+        },
+    )
+    .await;
+
+    let supported = match probe {
+        // the contract read the permit, and turned down its signature
+        Ok(_) | Err(Error::Contract(ContractError::PermitRejected)) => true,
+        Err(Error::Contract(ContractError::PermitsUnsupported)) => false,
+        Err(Error::Contract(error)) => {
+            debug!(
+                "inconclusive permit probe for {}: {error}",
+                contract.address
+            );
+            return Ok(false);
+        }
+        Err(error) => return Err(error),
+    };
+
+    debug!("{} supports permits: {supported}", contract.address);
+    remember_support(contract.address.as_str(), supported);
+
+    Ok(supported)
+}
+
+/// Queries `contract` for `address` with a permit if the contract supports them, and with
+/// `fallback` otherwise. The wallet is only asked to sign for contracts that accept permits.
+pub async fn query_or_else<T, P, F>(
+    address: &str,
+    contract: &ContractInfo,
+    with_permit: impl FnOnce(Permit) -> P,
+    fallback: impl FnOnce() -> F,
+) -> Result<T, Error>
+where
+    P: Future<Output = Result<T, Error>>,
+    F: Future<Output = Result<T, Error>>,
+{
+    if supports_permits(contract).await? {
+        let result = match get_or_sign(address, contract.address.as_str()).await {
+            Ok(permit) => with_permit(permit).await,
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Err(error) if is_unsupported(&error) => {
+                if rejects_permits(&error) {
+                    remember_support(contract.address.as_str(), false);
+                }
+                debug!("can't use a permit for {}: {error}", contract.address);
+            }
+            result => return result,
+        }
+    }
+
+    fallback().await
+}
+
+/// Queries the SNIP-20 balance of `address`, using a permit if the token supports them and the
+/// viewing key stored in the wallet otherwise.
+pub async fn snip20_balance(address: &str, token: &ContractInfo) -> Result<Uint128, Error> {
+    query_or_else(
+        address,
+        token,
+        |permit| async move {
+            query_with_permit::<BalanceResponse>(token, permit, json!({ "balance": {} }))
+                .await
+                .map(|response| response.balance.amount)
+        },
+        || async move {
+            let key = match viewing_key::saved(address).remove(token.address.as_str()) {
+                Some(key) => key,
+                None => {
+                    wallet::active()
+                        .get_secret_20_viewing_key(CHAIN_ID, token.address.as_str())
+                        .await?
+                }
+            };
+
+            chain_query::<BalanceResponse>(
+                token.code_hash.clone(),
+                token.address.to_string(),
+                secret_toolkit_snip20::QueryMsg::Balance {
+                    address: address.to_string(),
+                    key,
+                },
+            )
+            .await
+            .map(|response| response.balance.amount)
+        },
+    )
+    .await
+}
+
+/// Queries the LB token balances of `address` in each of the bins `ids`, using a permit if the
+/// pair supports them and the public `balance_of_batch` query otherwise.
+pub async fn lb_balances(
+    lb_pair: &ContractInfo,
+    address: &str,
+    ids: Vec<u32>,
+) -> Result<Vec<Uint256>, Error> {
+    let lb_pair = &ILbPair(lb_pair.clone());
+    let accounts = vec![address.to_string(); ids.len()];
+    let (permit_accounts, permit_ids) = (accounts.clone(), ids.clone());
+
+    query_or_else(
+        address,
+        lb_pair,
+        move |permit| lb_pair.balance_of_batch_with_permit(permit, permit_accounts, permit_ids),
+        move || lb_pair.balance_of_batch(accounts, ids),
+    )
+    .await
+}
+
+/// Like [`lb_balances`], for many pairs at once. Each pair comes with the bins to check, and the
+/// results are in the same order.
+///
+/// One permit covers every pair that supports them, so the wallet prompts at most once, and all
+/// the pairs are queried in one batch. A pair whose query fails in the batch is queried again on
+/// its own with [`lb_balances`].
+pub async fn lb_balances_batch(
+    address: &str,
+    queries: Vec<(ContractInfo, Vec<u32>)>,
+) -> Vec<Result<Vec<Uint256>, Error>> {
+    let support = join_all(queries.iter().map(|(lb_pair, _)| supports_permits(lb_pair))).await;
+
+    let permit_pairs: Vec<&str> = queries
+        .iter()
+        .zip(&support)
+        .filter(|(_, supported)| matches!(supported, Ok(true)))
+        .map(|((lb_pair, _), _)| lb_pair.address.as_str())
+        .collect();
+    let permit = if permit_pairs.is_empty() {
+        None
+    } else {
+        Some(get_or_sign_all(address, &permit_pairs).await)
+    };
+
+    let mut batch = vec![];
+    let mut errors = HashMap::new();
+
+    for (index, ((lb_pair, ids), supported)) in queries.iter().zip(&support).enumerate() {
+        let query = lb_pair::QueryMsg::BalanceOfBatch {
+            accounts: vec![address.to_string(); ids.len()],
+            ids: ids.clone(),
+        };
+
+        let query_msg = match (supported, &permit) {
+            (Ok(true), Some(Ok(permit))) => serde_json::to_value(PermitQuery::WithPermit {
+                permit: permit.clone(),
+                query,
+            }),
+            // signing failed, and asking again for every pair would only prompt again
+            (Ok(true), Some(Err(error))) if !is_unsupported(error) => {
+                errors.insert(index, error.clone());
+                continue;
+            }
+            (Ok(_), _) => serde_json::to_value(query),
+            // queried on its own below
+            (Err(_), _) => continue,
+        }
+        .expect("LB pair queries are serializable");
+
+        batch.push(BatchQueryParams {
+            id: index.to_string(),
+            contract: lb_pair.clone(),
+            query_msg,
+        });
+    }
+
+    let batched = if batch.is_empty() {
+        Ok(BatchQueryParsedResponse { items: vec![] })
+    } else {
+        chain_batch_query(batch).await
+    };
+    let mut responses: HashMap<String, String> = match batched {
+        Ok(response) => response
+            .items
+            .into_iter()
+            .filter(|item| item.status == BatchItemResponseStatus::SUCCESS)
+            .map(|item| (item.id, item.response))
+            .collect(),
+        Err(error) => {
+            warn!("failed to batch the LB balance queries: {error}");
+            HashMap::new()
+        }
+    };
+
+    join_all(
+        queries
+            .into_iter()
+            .enumerate()
+            .map(|(index, (lb_pair, ids))| {
+                let error = errors.remove(&index);
+                let response = responses
+                    .remove(&index.to_string())
+                    .and_then(|response| serde_json::from_str::<Vec<Uint256>>(&response).ok());
+
+                async move {
+                    match (error, response) {
+                        (Some(error), _) => Err(error),
+                        (None, Some(amounts)) => Ok(amounts),
+                        (None, None) => lb_balances(&lb_pair, address, ids).await,
+                    }
+                }
+            }),
+    )
+    .await
+}
@@ -0,0 +1,508 @@
+//! Liquidity positions, read back from a user's LB token balances.
+//!
+//! LB pairs can't list the bins an account holds, so they are discovered by querying balances in
+//! windows that expand outward from the active bin, until a few windows in a row come back empty
+//! on each side. The bins found are cached per user and pair, and later loads query those bins and
+//! the area around the active bin directly. Several pairs can be searched at once, with each step
+//! of the search sent as one batch.
+
+use crate::{constants::CHAIN_ID, permit, support::chain_batch_query, Error};
+use ammber_sdk::contract_interfaces::lb_pair::{self, BinResponse, TotalSupplyResponse};
+use batch_query::{BatchItemResponseStatus, BatchQueryParams};
+use cosmwasm_std::{ContractInfo, Uint128, Uint256};
+use leptos::prelude::window;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tracing::{debug, warn};
+use web_sys::js_sys::Date;
+
+/// How many bins on either side of the active bin are always checked for a balance.
+pub const SEARCH_RADIUS: u32 = 49;
+/// How many bins each step of the search adds on either side.
+const WINDOW: u32 = 50;
+/// A side of the search stops after this many empty windows in a row.
+const EMPTY_WINDOWS: u32 = 3;
+/// Bin ids are 24 bits.
+const MAX_ID: u32 = (1 << 24) - 1;
+/// How long cached bins are trusted before the full search runs again, in milliseconds.
+const CACHE_TTL: f64 = 60.0 * 60.0 * 1000.0;
+
+/// A liquidity position in one pool, with what it would withdraw right now.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Position {
+    pub ids: Vec<u32>,
+    /// Expressed in LB tokens, one per id.
+    pub amounts: Vec<Uint256>,
+    pub amount_x: Uint128,
+    pub amount_y: Uint128,
+}
+
+impl Position {
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Whether the active bin is one of the bins the position holds.
+    pub fn is_in_range(&self, active_id: u32) -> bool {
+        match (self.ids.first(), self.ids.last()) {
+            (Some(low), Some(high)) => (*low..=*high).contains(&active_id),
+            _ => false,
+        }
+    }
+
+    /// The position valued in raw units of token Y, at `price` raw Y per raw X.
+    pub fn value_in_y(&self, price: f64) -> f64 {
+        self.amount_x.u128() as f64 * price + self.amount_y.u128() as f64
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedBins {
+    ids: Vec<u32>,
+    /// Milliseconds since the Unix epoch.
+    updated_at: f64,
+}
+
+fn storage_key(lb_pair: &ContractInfo, owner: &str) -> String {
+    format!("lb_bins_{CHAIN_ID}_{owner}_{}", lb_pair.address)
+}
+
+fn load_cache(lb_pair: &ContractInfo, owner: &str) -> Option<CachedBins> {
+    let storage = window().local_storage().ok().flatten()?;
+    let bins = storage
+        .get_item(&storage_key(lb_pair, owner))
+        .ok()
+        .flatten()?;
+
+    serde_json::from_str(&bins)
+        .inspect_err(|error| warn!("discarding unreadable cached bins: {error}"))
+        .ok()
+}
+
+fn store_cache(lb_pair: &ContractInfo, owner: &str, ids: Vec<u32>) {
+    if let Some(storage) = window().local_storage().ok().flatten() {
+        let bins = CachedBins {
+            ids,
+            updated_at: Date::now(),
+        };
+        let bins = serde_json::to_string(&bins).expect("cached bins are serializable");
+        _ = storage.set_item(&storage_key(lb_pair, owner), &bins);
+    }
+}
+
+/// Forgets the bins cached for `owner` in `lb_pair`, so the next discovery searches again.
+pub fn forget(lb_pair: &ContractInfo, owner: &str) {
+    if let Some(storage) = window().local_storage().ok().flatten() {
+        _ = storage.remove_item(&storage_key(lb_pair, owner));
+    }
+}
+
+/// The state of the search in one pair.
+struct Search {
+    lb_pair: ContractInfo,
+    /// The lowest and highest bins checked so far.
+    low: u32,
+    high: u32,
+    empty_below: u32,
+    empty_above: u32,
+    balances: BTreeMap<u32, Uint256>,
+    error: Option<Error>,
+}
+
+impl Search {
+    /// Starts a search around `active_id`, with the bins to check first: those within
+    /// [`SEARCH_RADIUS`] of the active bin, and any cached bins. A cache younger than
+    /// [`CACHE_TTL`] at `now` is trusted, so the search doesn't expand.
+    fn new(
+        lb_pair: ContractInfo,
+        active_id: u32,
+        cached: Option<CachedBins>,
+        now: f64,
+    ) -> (Self, Vec<u32>) {
+        let low = active_id.saturating_sub(SEARCH_RADIUS);
+        let high = active_id.saturating_add(SEARCH_RADIUS).min(MAX_ID);
+
+        let mut ids: BTreeSet<u32> = (low..=high).collect();
+        if let Some(cached) = &cached {
+            ids.extend(&cached.ids);
+        }
+
+        let is_fresh = cached.is_some_and(|cached| now - cached.updated_at < CACHE_TTL);
+        let empty = if is_fresh { EMPTY_WINDOWS } else { 0 };
+
+        let search = Search {
+            lb_pair,
+            low,
+            high,
+            empty_below: empty,
+            empty_above: empty,
+            balances: BTreeMap::new(),
+            error: None,
+        };
+
+        (search, ids.into_iter().collect())
+    }
+
+    /// The next window of bins on either side, or nothing on a side that has stopped.
+    fn next_window(&self) -> (Vec<u32>, Vec<u32>) {
+        let below = if self.empty_below < EMPTY_WINDOWS && self.low > 0 {
+            (self.low.saturating_sub(WINDOW)..self.low).collect()
+        } else {
+            vec![]
+        };
+        let above = if self.empty_above < EMPTY_WINDOWS && self.high < MAX_ID {
+            (self.high + 1..=self.high.saturating_add(WINDOW).min(MAX_ID)).collect()
+        } else {
+            vec![]
+        };
+
+        (below, above)
+    }
+
+    /// Records the balances of a window, in the order of `below` then `above`. Bins without an
+    /// amount count as empty.
+    fn record_window(&mut self, below: &[u32], above: &[u32], amounts: Vec<Uint256>) {
+        let (amounts_below, amounts_above) = amounts.split_at(below.len().min(amounts.len()));
+
+        if let Some(&first) = below.first() {
+            self.low = first;
+            self.empty_below = if amounts_below.iter().all(Uint256::is_zero) {
+                self.empty_below + 1
+            } else {
+                0
+            };
+        }
+        if let Some(&last) = above.last() {
+            self.high = last;
+            self.empty_above = if amounts_above.iter().all(Uint256::is_zero) {
+                self.empty_above + 1
+            } else {
+                0
+            };
+        }
+
+        self.balances
+            .extend(below.iter().chain(above).copied().zip(amounts));
+    }
+}
+
+/// Returns every bin where `owner` holds LB tokens in `lb_pair`, with the balances, by id.
+pub async fn discover(
+    lb_pair: &ContractInfo,
+    owner: &str,
+    active_id: u32,
+) -> Result<(Vec<u32>, Vec<Uint256>), Error> {
+    discover_all(owner, vec![(lb_pair.clone(), active_id)])
+        .await
+        .pop()
+        .expect("there is a result for every pair")
+}
+
+/// Like [`discover`], for every pair in `lb_pairs` with its active id. Each step of the search
+/// queries all the pairs in one batch, and the results are in the same order.
+pub async fn discover_all(
+    owner: &str,
+    lb_pairs: Vec<(ContractInfo, u32)>,
+) -> Vec<Result<(Vec<u32>, Vec<Uint256>), Error>> {
+    let mut searches = vec![];
+    let mut queries = vec![];
+
+    for (lb_pair, active_id) in lb_pairs {
+        let cached = load_cache(&lb_pair, owner);
+        let (search, ids) = Search::new(lb_pair, active_id, cached, Date::now());
+
+        queries.push((search.lb_pair.clone(), ids));
+        searches.push(search);
+    }
+
+    let amounts = permit::lb_balances_batch(owner, queries.clone()).await;
+    for ((search, (_, ids)), amounts) in searches.iter_mut().zip(queries).zip(amounts) {
+        match amounts {
+            Ok(amounts) => search.balances.extend(ids.into_iter().zip(amounts)),
+            Err(error) => search.error = Some(error),
+        }
+    }
+
+    loop {
+        let windows: Vec<(usize, Vec<u32>, Vec<u32>)> = searches
+            .iter()
+            .enumerate()
+            .filter(|(_, search)| search.error.is_none())
+            .map(|(index, search)| {
+                let (below, above) = search.next_window();
+                (index, below, above)
+            })
+            .filter(|(_, below, above)| !below.is_empty() || !above.is_empty())
+            .collect();
+
+        if windows.is_empty() {
+            break;
+        }
+
+        // both sides of every pair go in one batch
+        let queries = windows
+            .iter()
+            .map(|(index, below, above)| {
+                let ids = below.iter().chain(above).copied().collect();
+                (searches[*index].lb_pair.clone(), ids)
+            })
+            .collect();
+        let amounts = permit::lb_balances_batch(owner, queries).await;
+
+        for ((index, below, above), amounts) in windows.into_iter().zip(amounts) {
+            let search = &mut searches[index];
+            match amounts {
+                Ok(amounts) => search.record_window(&below, &above, amounts),
+                Err(error) => search.error = Some(error),
+            }
+        }
+    }
+
+    searches
+        .into_iter()
+        .map(|search| {
+            if let Some(error) = search.error {
+                return Err(error);
+            }
+
+            debug!(
+                "searched bins {} to {} of {}",
+                search.low, search.high, search.lb_pair.address
+            );
+
+            let (ids, amounts): (Vec<u32>, Vec<Uint256>) = search
+                .balances
+                .into_iter()
+                .filter(|(_, amount)| !amount.is_zero())
+                .unzip();
+
+            store_cache(&search.lb_pair, owner, ids.clone());
+
+            Ok((ids, amounts))
+        })
+        .collect()
+}
+
+/// Finds every bin where `owner` holds LB tokens, and values them.
+pub async fn find(lb_pair: &ContractInfo, owner: &str, active_id: u32) -> Result<Position, Error> {
+    let (ids, amounts) = discover(lb_pair, owner, active_id).await?;
+
+    from_balances(lb_pair, ids, amounts).await
+}
+
+/// Values the non-zero balances among `ids` by the user's share of each bin's reserves.
+///
+/// The reserves and total supply of every bin are queried in one batch.
+pub async fn from_balances(
+    lb_pair: &ContractInfo,
+    ids: Vec<u32>,
+    amounts: Vec<Uint256>,
+) -> Result<Position, Error> {
+    let (ids, amounts): (Vec<u32>, Vec<Uint256>) = ids
+        .into_iter()
+        .zip(amounts)
+        .filter(|(_, amount)| !amount.is_zero())
+        .unzip();
+
+    if ids.is_empty() {
+        return Ok(Position::default());
+    }
+
+    let queries = ids
+        .iter()
+        .flat_map(|&id| {
+            [
+                ("bin", lb_pair::QueryMsg::GetBin { id }),
+                ("supply", lb_pair::QueryMsg::TotalSupply { id }),
+            ]
+            .map(|(kind, query_msg)| BatchQueryParams {
+                id: format!("{id}:{kind}"),
+                contract: lb_pair.clone(),
+                query_msg,
+            })
+        })
+        .collect();
+
+    let mut responses: HashMap<String, Result<String, Error>> = chain_batch_query(queries)
+        .await?
+        .items
+        .into_iter()
+        .map(|item| {
+            let response = match item.status {
+                BatchItemResponseStatus::SUCCESS => Ok(item.response),
+                BatchItemResponseStatus::ERROR => Err(Error::secret(item.response)),
+            };
+            (item.id, response)
+        })
+        .collect();
+    let mut response = |id: String| {
+        responses
+            .remove(&id)
+            .unwrap_or_else(|| Err(Error::generic(format!("no response for {id}"))))
+    };
+
+    let mut amount_x = Uint128::zero();
+    let mut amount_y = Uint128::zero();
+
+    for (&id, &amount) in ids.iter().zip(&amounts) {
+        let bin: BinResponse = serde_json::from_str(&response(format!("{id}:bin"))?)?;
+        let total_supply: TotalSupplyResponse =
+            serde_json::from_str(&response(format!("{id}:supply"))?)?;
+
+        let share = |reserve: Uint128| {
+            Uint256::from(reserve)
+                .checked_multiply_ratio(amount, total_supply.total_supply)
+                .ok()
+                .and_then(|amount| Uint128::try_from(amount).ok())
+                .unwrap_or_default()
+        };
+
+        amount_x += share(bin.bin_reserve_x);
+        amount_y += share(bin.bin_reserve_y);
+    }
+
+    Ok(Position {
+        ids,
+        amounts,
+        amount_x,
+        amount_y,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::Addr;
+
+    fn lb_pair() -> ContractInfo {
+        ContractInfo {
+            address: Addr::unchecked("lb_pair"),
+            code_hash: String::new(),
+        }
+    }
+
+    fn search_at(active_id: u32) -> Search {
+        Search::new(lb_pair(), active_id, None, 0.0).0
+    }
+
+    fn amounts(amounts: &[u128]) -> Vec<Uint256> {
+        amounts
+            .iter()
+            .map(|&amount| Uint256::from(amount))
+            .collect()
+    }
+
+    #[test]
+    fn starts_around_the_active_bin() {
+        let (search, ids) = Search::new(lb_pair(), 1000, None, 0.0);
+
+        assert_eq!((search.low, search.high), (951, 1049));
+        assert_eq!(ids, (951..=1049).collect::<Vec<_>>());
+        assert_eq!((search.empty_below, search.empty_above), (0, 0));
+    }
+
+    #[test]
+    fn a_fresh_cache_stops_the_search() {
+        let cached = |updated_at| CachedBins {
+            ids: vec![5, 2000],
+            updated_at,
+        };
+
+        let (search, ids) = Search::new(lb_pair(), 1000, Some(cached(0.0)), 1.0);
+        assert!(ids.contains(&5) && ids.contains(&2000));
+        assert_eq!(search.next_window(), (vec![], vec![]));
+
+        // a stale cache adds its bins, but the search still expands
+        let (search, ids) = Search::new(lb_pair(), 1000, Some(cached(0.0)), CACHE_TTL);
+        assert!(ids.contains(&5) && ids.contains(&2000));
+        assert_eq!(
+            search.next_window(),
+            ((901..951).collect(), (1050..1100).collect())
+        );
+    }
+
+    #[test]
+    fn stops_at_the_ends_of_the_id_range() {
+        let search = search_at(0);
+        assert_eq!((search.low, search.high), (0, SEARCH_RADIUS));
+        let (below, above) = search.next_window();
+        assert!(below.is_empty());
+        assert_eq!(above, (50..100).collect::<Vec<_>>());
+
+        let search = search_at(MAX_ID);
+        assert_eq!((search.low, search.high), (MAX_ID - SEARCH_RADIUS, MAX_ID));
+        let (below, above) = search.next_window();
+        assert_eq!(below.len(), WINDOW as usize);
+        assert!(above.is_empty());
+
+        // windows are cut short at the edges
+        let mut search = search_at(60);
+        search.record_window(&(0..11).collect::<Vec<_>>(), &[], amounts(&[0; 11]));
+        assert_eq!(search.low, 0);
+        assert!(search.next_window().0.is_empty());
+
+        let mut search = search_at(MAX_ID - 60);
+        let (_, above) = search.next_window();
+        assert_eq!(above.last(), Some(&MAX_ID));
+        assert_eq!(above.len(), 11);
+        search.record_window(&[], &above, amounts(&[0; 11]));
+        assert!(search.next_window().1.is_empty());
+    }
+
+    #[test]
+    fn stops_a_side_after_enough_empty_windows() {
+        let mut search = search_at(1000);
+
+        for _ in 0..EMPTY_WINDOWS {
+            let (below, above) = search.next_window();
+            assert!(!below.is_empty());
+            let mut window = amounts(&vec![0; below.len() + above.len()]);
+            // keep finding liquidity above
+            *window.last_mut().unwrap() = Uint256::from(1u128);
+            search.record_window(&below, &above, window);
+        }
+
+        assert_eq!(search.empty_below, EMPTY_WINDOWS);
+        assert_eq!(search.empty_above, 0);
+        let (below, above) = search.next_window();
+        assert!(below.is_empty());
+        assert!(!above.is_empty());
+    }
+
+    #[test]
+    fn liquidity_resets_the_empty_window_count() {
+        let mut search = search_at(1000);
+
+        let (below, above) = search.next_window();
+        search.record_window(&below, &above, amounts(&vec![0; 100]));
+        assert_eq!((search.empty_below, search.empty_above), (1, 1));
+
+        let (below, above) = search.next_window();
+        let mut window = amounts(&vec![0; 100]);
+        window[0] = Uint256::from(1u128);
+        search.record_window(&below, &above, window);
+        assert_eq!((search.empty_below, search.empty_above), (0, 2));
+        assert_eq!(search.balances.get(&below[0]), Some(&Uint256::from(1u128)));
+    }
+
+    #[test]
+    fn missing_amounts_count_as_empty() {
+        let mut search = search_at(1000);
+        let (below, above) = search.next_window();
+
+        // only the window below came back, with liquidity in its last bin
+        let mut window = amounts(&vec![0; below.len()]);
+        *window.last_mut().unwrap() = Uint256::from(1u128);
+        search.record_window(&below, &above, window);
+
+        assert_eq!((search.empty_below, search.empty_above), (0, 1));
+        assert_eq!((search.low, search.high), (901, 1099));
+        assert_eq!(search.balances.len(), below.len());
+
+        // fewer amounts than the window below
+        let (below, above) = search.next_window();
+        search.record_window(&below, &above, amounts(&[0; 10]));
+        assert_eq!((search.empty_below, search.empty_above), (1, 2));
+        assert_eq!(search.balances.len(), 60);
+    }
+}
@@ -0,0 +1,347 @@
+//! USD prices for valuations.
+//!
+//! Prices are derived on chain. The reference stablecoin of the chain is worth one dollar, and every other token
+//! is priced by walking the LB pairs outward from it: a token that trades against tokens already
+//! priced takes the active bin price of each of those pairs, weighted by the liquidity in them.
+//!
+//! An external feed can be plugged in. Its prices take precedence and also seed the walk, so
+//! tokens without a path to the stablecoin can still be priced through them.
+
+use crate::{
+    constants::{REFERENCE_STABLECOIN, TOKEN_MAP},
+    support::chain_batch_query,
+    utils::get_token_decimals,
+    Error,
+};
+use ammber_sdk::{
+    contract_interfaces::lb_pair::{self, ActiveIdResponse, LbPair, ReservesResponse},
+    utils::get_price_from_id,
+};
+use async_trait::async_trait;
+use batch_query::{BatchItemResponseStatus, BatchQueryParams};
+use futures::future::join_all;
+use std::{collections::HashMap, sync::Arc};
+use tracing::warn;
+
+/// A source of USD prices from outside the chain.
+#[async_trait(?Send)]
+pub trait PriceFeed {
+    /// The USD price of one whole token at `address`, or `None` if the feed doesn't know it.
+    async fn usd_price(&self, address: &str) -> Result<Option<f64>, Error>;
+}
+
+/// Fixed prices by token address, for stubbing the feed.
+#[async_trait(?Send)]
+impl PriceFeed for HashMap<String, f64> {
+    async fn usd_price(&self, address: &str) -> Result<Option<f64>, Error> {
+        Ok(self.get(address).copied())
+    }
+}
+
+/// The state of one pair, in whole tokens.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PairQuote {
+    pub token_x: String,
+    pub token_y: String,
+    /// Whole units of token Y per whole unit of token X, at the active bin.
+    pub price: f64,
+    pub reserve_x: f64,
+    pub reserve_y: f64,
+}
+
+/// Queries the active id and reserves of every pair in one batch. Pairs that fail to answer are
+/// left out.
+pub async fn pair_quotes(lb_pairs: &[LbPair]) -> Result<Vec<PairQuote>, Error> {
+    let queries = lb_pairs
+        .iter()
+        .enumerate()
+        .flat_map(|(index, lb_pair)| {
+            [
+                ("active", lb_pair::QueryMsg::GetActiveId {}),
+                ("reserves", lb_pair::QueryMsg::GetReserves {}),
+            ]
+            .map(|(kind, query_msg)| BatchQueryParams {
+                id: format!("{index}:{kind}"),
+                contract: lb_pair.contract.clone(),
+                query_msg,
+            })
+        })
+        .collect();
+
+    let responses: HashMap<String, String> = chain_batch_query(queries)
+        .await?
+        .items
+        .into_iter()
+        .filter(|item| item.status == BatchItemResponseStatus::SUCCESS)
+        .map(|item| (item.id, item.response))
+        .collect();
+
+    let quotes = lb_pairs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, lb_pair)| {
+            let active: ActiveIdResponse =
+                serde_json::from_str(responses.get(&format!("{index}:active"))?).ok()?;
+            let reserves: ReservesResponse =
+                serde_json::from_str(responses.get(&format!("{index}:reserves"))?).ok()?;
+
+            let token_x = lb_pair.token_x.address().to_string();
+            let token_y = lb_pair.token_y.address().to_string();
+            let decimals_x = get_token_decimals(&token_x).ok()? as i32;
+            let decimals_y = get_token_decimals(&token_y).ok()? as i32;
+
+            let price = get_price_from_id(active.active_id.to_string(), lb_pair.bin_step)
+                * 10f64.powi(decimals_x - decimals_y);
+
+            Some(PairQuote {
+                token_x,
+                token_y,
+                price,
+                reserve_x: reserves.reserve_x.u128() as f64 / 10f64.powi(decimals_x),
+                reserve_y: reserves.reserve_y.u128() as f64 / 10f64.powi(decimals_y),
+            })
+        })
+        .collect();
+
+    Ok(quotes)
+}
+
+/// Prices every token reachable from the tokens in `known`, one hop at a time.
+pub fn derive_prices(quotes: &[PairQuote], known: HashMap<String, f64>) -> Prices {
+    let mut prices = known;
+
+    loop {
+        // price and weight sums for the tokens one hop away from the priced ones
+        let mut candidates: HashMap<&str, (f64, f64)> = HashMap::new();
+
+        for quote in quotes {
+            if !quote.price.is_finite() || quote.price <= 0.0 {
+                continue;
+            }
+
+            let (token, price, liquidity) =
+                match (prices.get(&quote.token_x), prices.get(&quote.token_y)) {
+                    (Some(_), Some(_)) | (None, None) => continue,
+                    (None, Some(&price_y)) => {
+                        let price_x = quote.price * price_y;
+                        let liquidity = quote.reserve_x * price_x + quote.reserve_y * price_y;
+                        (quote.token_x.as_str(), price_x, liquidity)
+                    }
+                    (Some(&price_x), None) => {
+                        let price_y = price_x / quote.price;
+                        let liquidity = quote.reserve_x * price_x + quote.reserve_y * price_y;
+                        (quote.token_y.as_str(), price_y, liquidity)
+                    }
+                };
+
+            // an empty pair still says something, but much less than any liquid one
+            let weight = liquidity.max(f64::EPSILON);
+            let (sum, weights) = candidates.entry(token).or_default();
+            *sum += price * weight;
+            *weights += weight;
+        }
+
+        if candidates.is_empty() {
+            break;
+        }
+
+        for (token, (sum, weights)) in candidates {
+            prices.insert(token.to_string(), sum / weights);
+        }
+    }
+
+    Prices(prices)
+}
+
+/// USD prices of whole tokens, by address.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Prices(HashMap<String, f64>);
+
+impl Prices {
+    pub fn get(&self, address: &str) -> Option<f64> {
+        self.0.get(address).copied()
+    }
+
+    /// The USD value of `amount` raw units of the token at `address`.
+    pub fn value(&self, address: &str, amount: u128) -> Option<f64> {
+        let decimals = get_token_decimals(address).ok()?;
+        Some(amount as f64 / 10f64.powi(decimals as i32) * self.get(address)?)
+    }
+}
+
+/// Formats a USD amount for display.
+pub fn display_usd(value: f64) -> String {
+    format!("${value:.2}")
+}
+
+#[derive(Clone, Default)]
+pub struct PriceService {
+    feed: Option<Arc<dyn PriceFeed>>,
+}
+
+impl PriceService {
+    pub fn with_feed(feed: impl PriceFeed + 'static) -> Self {
+        Self {
+            feed: Some(Arc::new(feed)),
+        }
+    }
+
+    /// Prices every known token, from the feed if there is one and from `lb_pairs` otherwise.
+    pub async fn prices(&self, lb_pairs: &[LbPair]) -> Result<Prices, Error> {
+        let stablecoin = *REFERENCE_STABLECOIN;
+        let stablecoin = if TOKEN_MAP.contains_key(stablecoin) {
+            Some(stablecoin)
+        } else {
+            warn!("the reference stablecoin {stablecoin} is not in the token map");
+            None
+        };
+        let tokens: Vec<&str> = TOKEN_MAP.keys().map(String::as_str).collect();
+
+        let quotes = pair_quotes(lb_pairs).await?;
+
+        Ok(self.derive(stablecoin, &tokens, &quotes).await)
+    }
+
+    /// Prices `tokens` from the feed, starting from `stablecoin` at one dollar, and then every
+    /// token reachable from those through `quotes`. All the feed lookups run at once.
+    async fn derive(
+        &self,
+        stablecoin: Option<&str>,
+        tokens: &[&str],
+        quotes: &[PairQuote],
+    ) -> Prices {
+        let mut known = HashMap::new();
+
+        if let Some(stablecoin) = stablecoin {
+            known.insert(stablecoin.to_string(), 1.0);
+        }
+
+        if let Some(feed) = &self.feed {
+            let feed_prices = join_all(tokens.iter().map(|address| feed.usd_price(address))).await;
+
+            for (address, price) in tokens.iter().zip(feed_prices) {
+                match price {
+                    Ok(Some(price)) => {
+                        known.insert(address.to_string(), price);
+                    }
+                    Ok(None) => {}
+                    Err(error) => warn!("price feed failed for {address}: {error}"),
+                }
+            }
+        }
+
+        derive_prices(quotes, known)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{get_reference_stablecoin, get_token_map};
+    use futures::executor::block_on;
+
+    const USDC: &str = "usdc";
+
+    fn quote(token_x: &str, token_y: &str, price: f64) -> PairQuote {
+        PairQuote {
+            token_x: token_x.to_string(),
+            token_y: token_y.to_string(),
+            price,
+            reserve_x: 1_000.0,
+            reserve_y: 1_000.0,
+        }
+    }
+
+    fn usdc_is_one_dollar() -> HashMap<String, f64> {
+        HashMap::from([(USDC.to_string(), 1.0)])
+    }
+
+    fn assert_price(prices: &Prices, address: &str, expected: f64) {
+        let price = prices.get(address).expect("token has no price");
+        assert!(
+            (price - expected).abs() < 1e-9,
+            "{address} is priced {price}, expected {expected}"
+        );
+    }
+
+    #[test]
+    fn prices_pairs_with_the_stablecoin_directly() {
+        // the stablecoin as token Y, then as token X
+        let quotes = [quote("amber", USDC, 2.0), quote(USDC, "shd", 4.0)];
+
+        let prices = derive_prices(&quotes, usdc_is_one_dollar());
+
+        assert_price(&prices, USDC, 1.0);
+        assert_price(&prices, "amber", 2.0);
+        assert_price(&prices, "shd", 0.25);
+    }
+
+    #[test]
+    fn prices_through_an_intermediate_token() {
+        let quotes = [quote("sscrt", "amber", 3.0), quote("amber", USDC, 2.0)];
+
+        let prices = derive_prices(&quotes, usdc_is_one_dollar());
+
+        assert_price(&prices, "amber", 2.0);
+        assert_price(&prices, "sscrt", 6.0);
+    }
+
+    #[test]
+    fn leaves_tokens_without_a_route_unpriced() {
+        let quotes = [quote("amber", USDC, 2.0), quote("sscrt", "shd", 3.0)];
+
+        let prices = derive_prices(&quotes, usdc_is_one_dollar());
+
+        assert_price(&prices, "amber", 2.0);
+        assert_eq!(prices.get("sscrt"), None);
+        assert_eq!(prices.get("shd"), None);
+    }
+
+    #[test]
+    fn feed_prices_override_and_seed_the_walk() {
+        // the pairs say amber is worth $2, but the feed says $5
+        let feed = HashMap::from([("amber".to_string(), 5.0), ("shd".to_string(), 7.0)]);
+        let service = PriceService::with_feed(feed);
+        // sscrt only trades against amber, and shd has no pairs
+        let quotes = [quote("amber", USDC, 2.0), quote("sscrt", "amber", 3.0)];
+
+        let prices =
+            block_on(service.derive(Some(USDC), &[USDC, "amber", "sscrt", "shd"], &quotes));
+
+        assert_price(&prices, USDC, 1.0);
+        assert_price(&prices, "amber", 5.0);
+        assert_price(&prices, "sscrt", 15.0);
+        assert_price(&prices, "shd", 7.0);
+    }
+
+    struct BrokenFeed;
+
+    #[async_trait(?Send)]
+    impl PriceFeed for BrokenFeed {
+        async fn usd_price(&self, _: &str) -> Result<Option<f64>, Error> {
+            Err(Error::generic("feed is down"))
+        }
+    }
+
+    #[test]
+    fn falls_back_to_the_pairs_when_the_feed_fails() {
+        let service = PriceService::with_feed(BrokenFeed);
+
+        let prices =
+            block_on(service.derive(Some(USDC), &[USDC, "amber"], &[quote("amber", USDC, 2.0)]));
+
+        assert_price(&prices, USDC, 1.0);
+        assert_price(&prices, "amber", 2.0);
+    }
+
+    #[test]
+    fn reference_stablecoin_is_in_every_token_map() {
+        for chain_id in ["secretdev-1", "pulsar-3"] {
+            let stablecoin = get_reference_stablecoin(chain_id);
+            assert!(
+                get_token_map(chain_id).contains_key(stablecoin),
+                "{stablecoin} is not a token on {chain_id}"
+            );
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use crate::{constants::*, error::Error};
-use keplr::{tokens::KeplrToken, Keplr, Key};
+use keplr::{tokens::KeplrToken, wallet, Key, WalletKind};
 use leptos::prelude::*;
 use reactive_stores::{Field, Store};
 use std::sync::Arc;
-use std::{collections::HashMap, ops::Deref};
+use std::{collections::HashMap, ops::Deref, time::Duration};
 use tracing::{debug, trace};
 
 // #[derive(Clone, Debug, PartialEq, Store)]
@@ -117,28 +117,201 @@ impl AsRef<HashMap<String, Token>> for TokenMap {
     }
 }
 
+/// Balance versions, keyed by token address (or denom, for native coins).
+///
+/// Anything that shows a balance reads the version of its token, so it refetches when that token
+/// is invalidated. The [`TxManager`](crate::TxManager) invalidates the tokens of every successful
+/// transaction.
+#[derive(Copy, Clone)]
+pub struct Balances {
+    versions: StoredValue<HashMap<String, ArcRwSignal<u32>>>,
+}
+
+impl Balances {
+    pub fn new() -> Self {
+        Self {
+            versions: StoredValue::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the current version of the balance of `token`, and subscribes to changes of it.
+    pub fn track(&self, token: &str) -> u32 {
+        self.version(token).get()
+    }
+
+    pub fn invalidate(&self, token: &str) {
+        trace!("invalidating balance of {token}");
+        self.version(token).update(|version| *version += 1);
+    }
+
+    pub fn invalidate_all(&self) {
+        self.versions.with_value(|versions| {
+            versions
+                .values()
+                .for_each(|version| version.update(|version| *version += 1))
+        });
+    }
+
+    fn version(&self, token: &str) -> ArcRwSignal<u32> {
+        if let Some(version) = self
+            .versions
+            .with_value(|versions| versions.get(token).cloned())
+        {
+            return version;
+        }
+
+        let version = ArcRwSignal::new(0);
+        self.versions.update_value(|versions| {
+            versions.insert(token.to_string(), version.clone());
+        });
+        version
+    }
+}
+
 // TODO: probably should change this. I'm not sure the derived signal works as intended
+/// Connection state for whichever wallet the user picked.
 #[derive(Copy, Clone)]
-pub struct KeplrSignals {
+pub struct WalletSignals {
+    pub wallet: RwSignal<WalletKind>,
     pub enabled: RwSignal<bool>,
     pub key: AsyncDerived<Result<Key, Error>, LocalStorage>,
     // pub key: RwSignal<Option<Result<Key, Error>>>,
+    reconnect_timer: StoredValue<Option<TimeoutHandle>>,
 }
 
+const WALLET_STORAGE_KEY: &str = "wallet";
+const CONNECTED_STORAGE_KEY: &str = "is_keplr_enabled";
+
+/// How long to wait before reconnecting. Extensions inject their provider around the load event,
+/// and repeated requests within this window collapse into one.
+const RECONNECT_DELAY: Duration = Duration::from_millis(300);
+
 // TODO: use runtime chain_id instead of static
-impl KeplrSignals {
+impl WalletSignals {
     pub fn new() -> Self {
+        // remember the wallet picked last time
+        let kind = window()
+            .local_storage()
+            .ok()
+            .flatten()
+            .and_then(|storage| storage.get_item(WALLET_STORAGE_KEY).ok().flatten())
+            .and_then(|kind| kind.parse::<WalletKind>().ok())
+            .filter(|kind| WalletKind::available_on(CHAIN_ID).any(|available| available == *kind))
+            .unwrap_or_default();
+        wallet::set_active(kind);
+
+        let wallet = RwSignal::new(kind);
         let enabled = RwSignal::new(false);
         let key = AsyncDerived::new_unsync(move || async move {
+            let wallet = wallet.get().wallet();
             if enabled.get() {
-                trace!("Updating Keplr key (derived signal)");
-                Keplr::get_key(CHAIN_ID).await.map_err(Into::into)
+                trace!("Updating {} key (derived signal)", wallet.name());
+                wallet.get_key(CHAIN_ID).await.map_err(Into::into)
             } else {
                 Err(Error::KeplrDisabled)
             }
         });
         // let key = RwSignal::new(None);
 
-        Self { enabled, key }
+        Self {
+            wallet,
+            enabled,
+            key,
+            reconnect_timer: StoredValue::new(None),
+        }
+    }
+
+    /// Whether the user was connected when they last left the app.
+    pub fn was_connected(&self) -> bool {
+        window()
+            .local_storage()
+            .ok()
+            .flatten()
+            .and_then(|storage| storage.get_item(CONNECTED_STORAGE_KEY).ok().flatten())
+            .is_some_and(|connected| connected == "true")
+    }
+
+    fn remember_connected(&self, connected: bool) {
+        if let Some(storage) = window().local_storage().ok().flatten() {
+            _ = storage.set_item(CONNECTED_STORAGE_KEY, &connected.to_string());
+        }
+    }
+
+    /// Reconnects without prompting if the user was connected last time.
+    ///
+    /// This asks the wallet for the key rather than calling `enable`, so nothing pops up if the
+    /// site is no longer allowed. Calls made in quick succession are debounced, and nothing
+    /// happens if the wallet is already connected, so the key is only derived once.
+    pub fn reconnect(&self) {
+        if !self.was_connected() {
+            return;
+        }
+
+        if let Some(timer) = self.reconnect_timer.get_value() {
+            timer.clear();
+        }
+
+        let this = *self;
+        let timer = set_timeout_with_handle(
+            move || leptos::task::spawn_local(async move { this.try_reconnect().await }),
+            RECONNECT_DELAY,
+        )
+        .ok();
+        self.reconnect_timer.set_value(timer);
+    }
+
+    async fn try_reconnect(&self) {
+        let wallet = self.wallet.get_untracked().wallet();
+
+        if self.enabled.get_untracked() || !wallet.is_available() {
+            return;
+        }
+
+        match wallet.get_key(CHAIN_ID).await {
+            Ok(key) => {
+                debug!("reconnected to {} as {}", wallet.name(), key.bech32_address);
+                self.enabled.set(true);
+            }
+            Err(error) => debug!("not reconnecting to {}: {error}", wallet.name()),
+        }
+    }
+
+    /// Switches to `kind`, disconnecting the current wallet if it's a different one.
+    pub fn select(&self, kind: WalletKind) {
+        if self.wallet.get_untracked() == kind {
+            return;
+        }
+
+        self.disconnect();
+
+        wallet::set_active(kind);
+        self.wallet.set(kind);
+
+        if let Some(storage) = window().local_storage().ok().flatten() {
+            _ = storage.set_item(WALLET_STORAGE_KEY, &kind.to_string());
+        }
+    }
+
+    /// Asks the selected wallet to connect to the chain.
+    pub async fn connect(&self) -> Result<(), Error> {
+        let wallet = self.wallet.get_untracked().wallet();
+
+        match wallet.enable(vec![CHAIN_ID.to_string()]).await {
+            Ok(()) => {
+                self.enabled.set(true);
+                self.remember_connected(true);
+                Ok(())
+            }
+            Err(error) => {
+                self.enabled.set(false);
+                Err(error.into())
+            }
+        }
+    }
+
+    pub fn disconnect(&self) {
+        self.wallet.get_untracked().wallet().disable(CHAIN_ID);
+        self.enabled.set(false);
+        self.remember_connected(false);
     }
 }
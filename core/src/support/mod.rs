@@ -1,6 +1,7 @@
 mod query_helpers;
 
 pub use query_helpers::{
-    chain_batch_query, chain_query, get_compute_querier, ILbFactory, ILbPair, ILbQuoter, Querier,
+    chain_batch_query, chain_batch_query_with_config, chain_batch_query_with_routers, chain_query,
+    get_compute_querier, ChainBatchQuerier, ILbFactory, ILbPair, ILbQuoter, Querier,
     COMPUTE_QUERIER,
 };
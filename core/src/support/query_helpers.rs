@@ -1,13 +1,13 @@
 use crate::prelude::*;
-use crate::Error;
+use crate::{ContractError, Error};
 use ammber_sdk::contract_interfaces::{
     lb_factory::{self, *},
     lb_pair::{self, *},
     lb_quoter::{self, *},
 };
 use batch_query::{
-    get_batch_query_router, msg_batch_query, parse_batch_query, BatchQueryParams,
-    BatchQueryParsedResponse, BatchQueryResponse,
+    BatchClient, BatchConfig, BatchQuerier, BatchQuery, BatchQueryParams, BatchQueryParsedResponse,
+    BatchQueryResponse, BatchRouterRegistry,
 };
 use cosmwasm_std::{ContractInfo, StdResult, Uint128, Uint256};
 use leptos::prelude::*;
@@ -60,9 +60,6 @@ impl<T: Serialize + Send + Sync> Querier for T {
     }
 }
 
-// TODO: Each response can be either the specific expected response struct, or any of the potential
-// error types within the contract. Figure out how to handle this.
-
 pub fn chain_query<T>(
     code_hash: impl Into<String>,
     contract_address: impl Into<String>,
@@ -77,37 +74,88 @@ where
             .await
             .inspect(|response| debug!("{response}"))
             .inspect_err(|e| error!("{e}"))
-            .and_then(|response| Ok(serde_json::from_str::<T>(&response)?))
-            .map_err(Into::into)
+            .map_err(Error::from)
+            .and_then(|response| {
+                // The response is either the expected struct, or one of the contract's errors.
+                serde_json::from_str::<T>(&response).map_err(|error| {
+                    ContractError::decode(&response)
+                        .map(Error::Contract)
+                        .unwrap_or_else(|| error.into())
+                })
+            })
     })
 }
 
+/// Sends batch (or individual) queries through the global `COMPUTE_QUERIER`.
+pub struct ChainBatchQuerier;
+
+impl BatchQuerier for ChainBatchQuerier {
+    type Error = Error;
+
+    async fn query_batch(
+        &self,
+        router: &ContractInfo,
+        batch: &BatchQuery,
+    ) -> Result<BatchQueryResponse, Error> {
+        COMPUTE_QUERIER
+            .query_secret_contract(router.address.to_string(), router.code_hash.clone(), batch)
+            .await
+            .inspect(|response| debug!("{response}"))
+            .inspect_err(|e| error!("{e}"))
+            .map_err(Error::from)
+            .and_then(|response| Ok(serde_json::from_str::<BatchQueryResponse>(&response)?))
+    }
+
+    async fn query_contract(
+        &self,
+        contract: &ContractInfo,
+        query: &serde_json::Value,
+    ) -> Result<String, Error> {
+        COMPUTE_QUERIER
+            .query_secret_contract(
+                contract.address.to_string(),
+                contract.code_hash.clone(),
+                query,
+            )
+            .await
+            .inspect_err(|e| error!("{e}"))
+            .map_err(Error::from)
+    }
+}
+
 pub fn chain_batch_query<T>(
     queries: Vec<BatchQueryParams<T>>,
 ) -> impl std::future::Future<Output = Result<BatchQueryParsedResponse, Error>> + Send
 where
     T: Serialize + Send + Sync + 'static,
 {
-    // Get appropriate router based on the static CHAIN_ID
-    let router = get_batch_query_router(CHAIN_ID);
-    let batch_query = msg_batch_query(queries);
+    chain_batch_query_with_config(queries, BatchConfig::default())
+}
 
-    SendWrapper::new(async move {
-        COMPUTE_QUERIER
-            .query_secret_contract(
-                router.address.to_string(),
-                router.code_hash.clone(),
-                batch_query,
-            )
-            .await
-            .inspect(|response| debug!("{response}"))
-            .inspect_err(|e| error!("{e}"))
-            .and_then(|response| {
-                let batch_response = serde_json::from_str::<BatchQueryResponse>(&response)?;
-                Ok(parse_batch_query(batch_response))
-            })
-            .map_err(Into::into)
-    })
+/// Like `chain_batch_query`, but with control over chunk size, concurrency and retries.
+pub fn chain_batch_query_with_config<T>(
+    queries: Vec<BatchQueryParams<T>>,
+    config: BatchConfig,
+) -> impl std::future::Future<Output = Result<BatchQueryParsedResponse, Error>> + Send
+where
+    T: Serialize + Send + Sync + 'static,
+{
+    chain_batch_query_with_routers(&BATCH_ROUTERS, queries, config)
+}
+
+/// Like `chain_batch_query_with_config`, with the batch routers in `registry` instead of the
+/// deployed ones. Queries go one by one if `registry` has no router for the chain.
+pub fn chain_batch_query_with_routers<T>(
+    registry: &BatchRouterRegistry,
+    queries: Vec<BatchQueryParams<T>>,
+    config: BatchConfig,
+) -> impl std::future::Future<Output = Result<BatchQueryParsedResponse, Error>> + Send
+where
+    T: Serialize + Send + Sync + 'static,
+{
+    let client = BatchClient::new(ChainBatchQuerier, registry, CHAIN_ID).with_config(config);
+
+    SendWrapper::new(async move { client.query(queries).await })
 }
 
 /// A thin wrapper around `ContractInfo` that provides additional
@@ -234,6 +282,15 @@ impl ILbPair {
         .await
         .map(|response| response.next_id)
     }
+    pub async fn total_supply(&self, id: u32) -> Result<Uint256, Error> {
+        chain_query::<TotalSupplyResponse>(
+            self.0.code_hash.clone(),
+            self.0.address.to_string(),
+            lb_pair::QueryMsg::TotalSupply { id },
+        )
+        .await
+        .map(|response| response.total_supply)
+    }
     pub async fn balance_of_batch(
         &self,
         accounts: Vec<String>,
@@ -246,6 +303,19 @@ impl ILbPair {
         )
         .await
     }
+    pub async fn balance_of_batch_with_permit(
+        &self,
+        permit: crate::permit::Permit,
+        accounts: Vec<String>,
+        ids: Vec<u32>,
+    ) -> Result<Vec<Uint256>, Error> {
+        crate::permit::query_with_permit::<Vec<Uint256>>(
+            &self.0,
+            permit,
+            lb_pair::QueryMsg::BalanceOfBatch { accounts, ids },
+        )
+        .await
+    }
 }
 
 /// A thin wrapper around `ContractInfo` that provides additional
@@ -0,0 +1,700 @@
+//! Shared transaction lifecycle.
+//!
+//! Components encrypt their messages with [`TxManager::execute_msg`] and hand them to
+//! [`TxManager::submit`], or collect several into a [`TxBatch`] so they are signed once and
+//! succeed or fail together. The manager simulates them to estimate the fee, waits for the user
+//! to approve it, broadcasts them, waits for the transaction to be included in a block, decrypts
+//! the response, and keeps a reactive list of every transaction it has seen.
+//!
+//! ```text
+//! Simulating ──> AwaitingApproval ──> Broadcasting ──> Pending ──> Included
+//!      │                │                  │             │
+//!      └────────────────┴──────────────────┴─────────────┴──> Failed / TimedOut
+//! ```
+
+use crate::{
+    error::Error,
+    fee_grant::{self, FeeGrant, FEE_GRANTER},
+    gas::{self, FeeEstimate, FeeTier, DEFAULT_GAS_MULTIPLIER},
+    history,
+    state::{Balances, Endpoint},
+    CHAIN_ID,
+};
+use cosmwasm_std::{ContractInfo, Uint128};
+use futures::channel::oneshot;
+use keplr::wallet;
+use leptos::prelude::*;
+use rsecret::{
+    secret_client::{CreateTxSenderOptions, TxDecrypter},
+    tx::ComputeServiceClient,
+    TxOptions,
+};
+use secretrs::{
+    compute::{MsgExecuteContract, MsgExecuteContractResponse},
+    proto::cosmos::{
+        base::abci::v1beta1::TxResponse,
+        tx::v1beta1::{service_client::ServiceClient, GetTxRequest},
+    },
+    tx::Msg,
+    AccountId,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
+use tonic_web_wasm_client::Client;
+use tracing::{debug, error, info, warn};
+use web_sys::js_sys::Date;
+
+pub type TxId = u64;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TxKind {
+    Swap,
+    AddLiquidity,
+    RemoveLiquidity,
+    CreatePool,
+    ViewingKey,
+    Allowance,
+    LimitOrder,
+    Rebalance,
+    /// Found on chain, but not submitted from this app.
+    Other,
+}
+
+impl fmt::Display for TxKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxKind::Swap => write!(f, "Swap"),
+            TxKind::AddLiquidity => write!(f, "Add Liquidity"),
+            TxKind::RemoveLiquidity => write!(f, "Remove Liquidity"),
+            TxKind::CreatePool => write!(f, "Create Pool"),
+            TxKind::ViewingKey => write!(f, "Viewing Key"),
+            TxKind::Allowance => write!(f, "Allowance"),
+            TxKind::LimitOrder => write!(f, "Limit Order"),
+            TxKind::Rebalance => write!(f, "Rebalance"),
+            TxKind::Other => write!(f, "Contract Call"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TokenAmount {
+    pub address: String,
+    pub amount: Uint128,
+}
+
+/// What a transaction is meant to do, as described by the component submitting it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxSummary {
+    pub kind: TxKind,
+    pub tokens: Vec<TokenAmount>,
+}
+
+impl TxSummary {
+    pub fn new(kind: TxKind) -> Self {
+        Self {
+            kind,
+            tokens: vec![],
+        }
+    }
+
+    pub fn with_token(mut self, address: impl Into<String>, amount: impl Into<Uint128>) -> Self {
+        self.tokens.push(TokenAmount {
+            address: address.into(),
+            amount: amount.into(),
+        });
+        self
+    }
+}
+
+/// Messages to several contracts, signed and broadcast as one atomic transaction.
+///
+/// The messages run in the order they were pushed, and if any of them fails the whole
+/// transaction is reverted. The response data of each message can be found in
+/// [`TxRecord::data`] at the index [`TxBatch::push`] returned.
+pub struct TxBatch {
+    manager: TxManager,
+    sender: String,
+    summary: TxSummary,
+    msgs: Vec<MsgExecuteContract>,
+}
+
+impl TxBatch {
+    /// Encrypts `msg` for `contract` and appends it to the batch. Returns its index.
+    pub async fn push<M: Serialize + Sync>(
+        &mut self,
+        contract: &ContractInfo,
+        msg: &M,
+    ) -> Result<usize, Error> {
+        let msg = self
+            .manager
+            .execute_msg(&self.sender, contract, msg)
+            .await?;
+        self.msgs.push(msg);
+
+        Ok(self.msgs.len() - 1)
+    }
+
+    /// Appends a SNIP-20 `IncreaseAllowance` letting `spender` move `amount` of `token`.
+    pub async fn increase_allowance(
+        &mut self,
+        token: &ContractInfo,
+        spender: &ContractInfo,
+        amount: impl Into<Uint128>,
+    ) -> Result<usize, Error> {
+        let msg = secret_toolkit_snip20::HandleMsg::IncreaseAllowance {
+            spender: spender.address.to_string(),
+            amount: amount.into(),
+            expiration: None,
+            padding: None,
+        };
+
+        self.push(token, &msg).await
+    }
+
+    /// Records a token the transaction moves, see [`TxSummary::with_token`].
+    pub fn with_token(mut self, address: impl Into<String>, amount: impl Into<Uint128>) -> Self {
+        self.summary = self.summary.with_token(address, amount);
+        self
+    }
+
+    /// The address signing the batch.
+    pub fn sender(&self) -> &str {
+        &self.sender
+    }
+
+    pub fn len(&self) -> usize {
+        self.msgs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.msgs.is_empty()
+    }
+
+    /// Submits every message in the batch as a single transaction.
+    pub async fn submit(self, tx_options: TxOptions) -> Result<TxRecord, Error> {
+        if self.msgs.is_empty() {
+            return Err(Error::generic("the transaction has no messages"));
+        }
+
+        self.manager
+            .submit(self.summary, self.msgs, tx_options)
+            .await
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TxStatus {
+    /// Being simulated to estimate the fee. Nothing has been signed yet.
+    Simulating,
+    /// Simulated, and waiting for the user to approve the fee. Nothing has been signed yet.
+    AwaitingApproval,
+    /// Being signed and sent to the node.
+    Broadcasting,
+    /// Accepted by the node, waiting to be included in a block.
+    Pending,
+    Included,
+    Failed(Error),
+    /// Not seen in a block before the timeout. It may still be included later.
+    TimedOut,
+}
+
+impl TxStatus {
+    pub fn is_settled(&self) -> bool {
+        !matches!(
+            self,
+            TxStatus::Simulating
+                | TxStatus::AwaitingApproval
+                | TxStatus::Broadcasting
+                | TxStatus::Pending
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TxRecord {
+    pub id: TxId,
+    pub kind: TxKind,
+    pub tokens: Vec<TokenAmount>,
+    pub sender: Option<String>,
+    pub status: TxStatus,
+    pub hash: Option<String>,
+    pub height: Option<u64>,
+    pub gas_used: Option<u64>,
+    /// The simulated gas and the fee offered for it.
+    #[serde(default)]
+    pub fee: Option<FeeEstimate>,
+    /// Decrypted response data, one entry per message.
+    pub data: Vec<Vec<u8>>,
+    /// Decrypted raw log.
+    pub logs: Option<String>,
+    /// Milliseconds since the Unix epoch.
+    pub submitted_at: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TxManagerConfig {
+    /// How long to wait for a broadcast transaction to be included in a block.
+    pub timeout: Duration,
+    pub poll_interval: Duration,
+    /// The oldest settled transactions are dropped from the list past this length.
+    pub max_records: usize,
+    /// The simulated gas is multiplied by this to get the gas limit.
+    pub gas_multiplier: f64,
+    /// Pays the fees of senders it has granted an allowance.
+    pub fee_granter: Option<&'static str>,
+}
+
+impl Default for TxManagerConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(60),
+            poll_interval: Duration::from_secs(2),
+            max_records: 20,
+            gas_multiplier: DEFAULT_GAS_MULTIPLIER,
+            fee_granter: FEE_GRANTER,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct TxManager {
+    endpoint: Endpoint,
+    balances: Balances,
+    records: RwSignal<Vec<TxRecord>>,
+    next_id: StoredValue<TxId>,
+    config: StoredValue<TxManagerConfig>,
+    fee_tier: RwSignal<FeeTier>,
+    fee_grant: RwSignal<Option<FeeGrant>>,
+    approvals: StoredValue<HashMap<TxId, oneshot::Sender<FeeTier>>>,
+}
+
+impl TxManager {
+    pub fn new(endpoint: Endpoint, balances: Balances) -> Self {
+        Self {
+            endpoint,
+            balances,
+            records: RwSignal::new(Vec::new()),
+            next_id: StoredValue::new(0),
+            config: StoredValue::new(TxManagerConfig::default()),
+            fee_tier: RwSignal::new(FeeTier::load()),
+            fee_grant: RwSignal::new(None),
+            approvals: StoredValue::new(HashMap::new()),
+        }
+    }
+
+    /// The gas price tier used for new transactions.
+    pub fn fee_tier(&self) -> Signal<FeeTier> {
+        self.fee_tier.into()
+    }
+
+    pub fn set_fee_tier(&self, tier: FeeTier) {
+        tier.store();
+        self.fee_tier.set(tier);
+    }
+
+    /// The active fee grant of the last sender checked, if the configured granter has one.
+    pub fn fee_grant(&self) -> Signal<Option<FeeGrant>> {
+        self.fee_grant.into()
+    }
+
+    /// Looks for an active grant from the configured granter to `grantee`, and remembers it.
+    pub async fn check_fee_grant(&self, grantee: &str) -> Option<FeeGrant> {
+        let granter = self.config.get_value().fee_granter?;
+        let url = self.endpoint.get_untracked();
+
+        let grant = match fee_grant::query(url, granter, grantee).await {
+            Ok(grant) => grant.filter(|grant| grant.covers(0)),
+            Err(error) => {
+                warn!("could not read the fee grant of {grantee}: {error}");
+                None
+            }
+        };
+        self.fee_grant.set(grant.clone());
+
+        grant
+    }
+
+    pub fn with_config(self, config: TxManagerConfig) -> Self {
+        self.config.set_value(config);
+        self
+    }
+
+    /// All known transactions, newest first.
+    pub fn records(&self) -> Signal<Vec<TxRecord>> {
+        self.records.into()
+    }
+
+    pub fn record(&self, id: TxId) -> Signal<Option<TxRecord>> {
+        let records = self.records;
+        Signal::derive(move || {
+            records.with(|records| records.iter().find(|record| record.id == id).cloned())
+        })
+    }
+
+    /// True while any transaction is still simulating, broadcasting or pending.
+    pub fn busy(&self) -> Signal<bool> {
+        let records = self.records;
+        Signal::derive(move || {
+            records.with(|records| records.iter().any(|record| !record.status.is_settled()))
+        })
+    }
+
+    pub fn clear_settled(&self) {
+        self.records
+            .update(|records| records.retain(|record| !record.status.is_settled()));
+    }
+
+    /// Broadcasts the transaction `id` at the gas price of `tier`, once it's awaiting approval.
+    pub fn approve(&self, id: TxId, tier: FeeTier) {
+        if let Some(approval) = self
+            .approvals
+            .try_update_value(|approvals| approvals.remove(&id))
+            .flatten()
+        {
+            _ = approval.send(tier);
+        }
+    }
+
+    /// Cancels the transaction `id` before it's signed.
+    pub fn reject(&self, id: TxId) {
+        self.approvals.update_value(|approvals| {
+            approvals.remove(&id);
+        });
+    }
+
+    /// Encrypts `msg` for `contract`, ready to be passed to [`TxManager::submit`].
+    pub async fn execute_msg<M: Serialize + Sync>(
+        &self,
+        sender: &str,
+        contract: &ContractInfo,
+        msg: &M,
+    ) -> Result<MsgExecuteContract, Error> {
+        let url = self.endpoint.get_untracked();
+        let wallet = wallet::active();
+
+        let options = CreateTxSenderOptions {
+            url,
+            chain_id: CHAIN_ID,
+            wallet: wallet.get_offline_signer_auto(CHAIN_ID).await?.into(),
+            wallet_address: sender.to_string().into(),
+            enigma_utils: wallet.get_enigma_utils(CHAIN_ID)?.into(),
+        };
+        let compute_service_client =
+            ComputeServiceClient::new(Client::new(url.to_string()), options);
+
+        Ok(MsgExecuteContract {
+            sender: AccountId::from_str(sender)?,
+            contract: AccountId::from_str(contract.address.as_str())?,
+            msg: compute_service_client
+                .encrypt(&contract.code_hash, msg)
+                .await?
+                .into_inner(),
+            sent_funds: vec![],
+        })
+    }
+
+    /// Starts a batch of messages from `sender`, to be submitted as one transaction.
+    pub fn batch(&self, sender: impl Into<String>, summary: TxSummary) -> TxBatch {
+        TxBatch {
+            manager: *self,
+            sender: sender.into(),
+            summary,
+            msgs: vec![],
+        }
+    }
+
+    /// Broadcasts `msgs` as a single transaction and waits for it to settle.
+    ///
+    /// The transaction is simulated first. Its gas limit comes from the simulation, replacing
+    /// whatever `tx_options` sets, and the record waits in [`TxStatus::AwaitingApproval`] with the
+    /// fee at the chosen [`FeeTier`] until [`TxManager::approve`] picks the tier to pay or
+    /// [`TxManager::reject`] cancels it. If the configured granter has a grant that covers the
+    /// fee, the granter pays it. If the simulation fails or the fee is rejected, the wallet is
+    /// never asked to sign.
+    ///
+    /// The returned record is also tracked in [`TxManager::records`], so components can either
+    /// await this or follow the status reactively. Settled records are saved to the history of
+    /// the sender, and the balances of the tokens involved are invalidated.
+    pub async fn submit(
+        &self,
+        summary: TxSummary,
+        msgs: Vec<MsgExecuteContract>,
+        tx_options: TxOptions,
+    ) -> Result<TxRecord, Error> {
+        let id = self.push(summary);
+
+        let result = self.run(id, msgs, tx_options).await;
+
+        match &result {
+            Ok(()) => self.update(id, |record| record.status = TxStatus::Included),
+            Err(error) => {
+                error!("{error}");
+                self.update(id, |record| {
+                    if record.status != TxStatus::TimedOut {
+                        record.status = TxStatus::Failed(error.clone())
+                    }
+                })
+            }
+        }
+
+        let record = self.get(id).ok_or_else(|| {
+            Error::generic("the transaction record was dropped before it settled")
+        })?;
+
+        // fees are paid by any transaction that made it into a block
+        if record.height.is_some() {
+            self.balances.invalidate("uscrt");
+        }
+        if record.status == TxStatus::Included {
+            for token in &record.tokens {
+                self.balances.invalidate(&token.address);
+            }
+        }
+
+        if let Some(sender) = &record.sender {
+            if let Err(error) = history::save(sender, &record).await {
+                warn!("failed to save transaction history: {error}");
+            }
+        }
+
+        result.map(|_| record)
+    }
+
+    async fn run(
+        &self,
+        id: TxId,
+        msgs: Vec<MsgExecuteContract>,
+        tx_options: TxOptions,
+    ) -> Result<(), Error> {
+        let url = self.endpoint.get_untracked();
+        let wallet = wallet::active();
+        let key = wallet.get_key(CHAIN_ID).await?;
+        let enigma_utils = wallet.get_enigma_utils(CHAIN_ID)?;
+
+        let options = CreateTxSenderOptions {
+            url,
+            chain_id: CHAIN_ID,
+            // Ledger and Keystone accounts can only sign amino
+            wallet: wallet.get_signer(CHAIN_ID, &key)?.into(),
+            wallet_address: key.bech32_address.clone().into(),
+            enigma_utils: enigma_utils.clone().into(),
+        };
+
+        self.update(id, |record| {
+            record.sender = Some(key.bech32_address.clone())
+        });
+
+        let gas_used = gas::simulate(url, &key, &msgs, &enigma_utils).await?;
+        let grant = self.check_fee_grant(&key.bech32_address).await;
+        let paid_with = |fee: FeeEstimate| FeeEstimate {
+            granter: grant
+                .as_ref()
+                .filter(|grant| grant.covers(fee.amount))
+                .map(|grant| grant.granter.clone()),
+            ..fee
+        };
+
+        let estimate = paid_with(FeeEstimate::new(
+            gas_used,
+            self.config.get_value().gas_multiplier,
+            self.fee_tier.get_untracked(),
+        ));
+        debug!("fee estimate: {estimate:?}");
+
+        let (approval, approved) = oneshot::channel();
+        self.approvals.update_value(|approvals| {
+            approvals.insert(id, approval);
+        });
+        self.update(id, |record| {
+            record.fee = Some(estimate.clone());
+            record.status = TxStatus::AwaitingApproval;
+        });
+
+        let tier = approved
+            .await
+            .map_err(|_| Error::generic("Transaction cancelled"))?;
+        let fee = paid_with(estimate.with_tier(tier));
+
+        let tx_options = TxOptions {
+            gas_limit: fee.gas_limit,
+            gas_price_in_fee_denom: fee.tier.gas_price() as _,
+            fee_granter: fee.granter.clone(),
+            wait_for_commit: false,
+            ..tx_options
+        };
+
+        self.update(id, |record| {
+            record.fee = Some(fee);
+            record.status = TxStatus::Broadcasting;
+        });
+
+        let compute_service_client =
+            ComputeServiceClient::new(Client::new(url.to_string()), options);
+
+        let tx = compute_service_client.broadcast(msgs, tx_options).await?;
+
+        // rejected before making it into the mempool
+        if tx.code != 0 {
+            return Err(Error::secret(&tx.raw_log));
+        }
+
+        let hash = tx.txhash;
+        info!("broadcast {hash}");
+
+        self.update(id, |record| {
+            record.status = TxStatus::Pending;
+            record.hash = Some(hash.clone());
+        });
+
+        let Some(tx_response) = self.wait_for_inclusion(url, &hash).await else {
+            self.update(id, |record| record.status = TxStatus::TimedOut);
+            return Err(Error::generic(format!(
+                "Transaction {hash} was not included in a block in time. It may still go through."
+            )));
+        };
+
+        self.update(id, |record| {
+            record.height = Some(tx_response.height as u64);
+            record.gas_used = Some(tx_response.gas_used as u64);
+        });
+
+        let tx = decrypt_tx(enigma_utils, tx_response).await?;
+
+        self.update(id, |record| {
+            record.logs = Some(tx.logs.clone());
+            record.data = tx.data;
+        });
+
+        if tx.code != 0 {
+            return Err(Error::secret(&tx.logs));
+        }
+
+        Ok(())
+    }
+
+    /// Polls the node until the transaction shows up in a block, or the timeout passes.
+    async fn wait_for_inclusion(&self, url: &str, hash: &str) -> Option<TxResponse> {
+        let config = self.config.get_value();
+        let mut client = ServiceClient::new(Client::new(url.to_string()));
+        let started = Date::now();
+
+        while Date::now() - started < config.timeout.as_millis() as f64 {
+            sleep(config.poll_interval).await;
+
+            let request = GetTxRequest {
+                hash: hash.to_string(),
+            };
+
+            match client.get_tx(request).await {
+                Ok(response) => {
+                    if let Some(tx_response) = response.into_inner().tx_response {
+                        return Some(tx_response);
+                    }
+                }
+                // the node reports "not found" until the tx is in a block
+                Err(status) => debug!("{hash} not found yet: {}", status.message()),
+            }
+        }
+
+        None
+    }
+
+    fn push(&self, summary: TxSummary) -> TxId {
+        let id = self.next_id.get_value();
+        self.next_id.set_value(id + 1);
+
+        let record = TxRecord {
+            id,
+            kind: summary.kind,
+            tokens: summary.tokens,
+            sender: None,
+            status: TxStatus::Simulating,
+            hash: None,
+            height: None,
+            gas_used: None,
+            fee: None,
+            data: vec![],
+            logs: None,
+            submitted_at: Date::now(),
+        };
+        let max_records = self.config.get_value().max_records;
+
+        self.records.update(|records| {
+            records.insert(0, record);
+            drop_oldest_settled(records, max_records);
+        });
+
+        id
+    }
+
+    fn update(&self, id: TxId, f: impl FnOnce(&mut TxRecord)) {
+        self.records.update(|records| {
+            if let Some(record) = records.iter_mut().find(|record| record.id == id) {
+                f(record)
+            }
+        });
+    }
+
+    fn get(&self, id: TxId) -> Option<TxRecord> {
+        self.records
+            .with_untracked(|records| records.iter().find(|record| record.id == id).cloned())
+    }
+}
+
+/// Drops the oldest settled records until no more than `max_records` are left. Records still in
+/// flight are kept even past the limit, since [`TxManager::submit`] needs them until they settle.
+fn drop_oldest_settled(records: &mut Vec<TxRecord>, max_records: usize) {
+    let mut excess = records.len().saturating_sub(max_records);
+    let mut index = records.len();
+
+    while excess > 0 && index > 0 {
+        index -= 1;
+        if records[index].status.is_settled() {
+            records.remove(index);
+            excess -= 1;
+        }
+    }
+}
+
+pub(crate) struct DecryptedTx {
+    pub code: u32,
+    pub logs: String,
+    pub data: Vec<Vec<u8>>,
+}
+
+/// Decrypts the logs and response data of an included transaction.
+pub(crate) async fn decrypt_tx(
+    enigma_utils: keplr::EnigmaUtils,
+    tx_response: TxResponse,
+) -> Result<DecryptedTx, Error> {
+    let tx = TxDecrypter::new(enigma_utils.into())
+        .decrypt_tx_response(tx_response)
+        .await?;
+
+    // failed transactions have no response data
+    let data = if tx.code == 0 {
+        tx.data
+            .iter()
+            .map(|any| MsgExecuteContractResponse::from_any(any).map(|response| response.data))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        vec![]
+    };
+
+    Ok(DecryptedTx {
+        code: tx.code,
+        logs: tx.raw_log,
+        data,
+    })
+}
+
+async fn sleep(duration: Duration) {
+    let (tx, rx) = oneshot::channel();
+    set_timeout(
+        move || {
+            let _ = tx.send(());
+        },
+        duration,
+    );
+    let _ = rx.await;
+}
@@ -6,6 +6,7 @@ use crate::{
 };
 use cosmwasm_std::{Addr, ContractInfo};
 use leptos::prelude::window;
+use liquidity_book::core::TokenType;
 
 pub fn alert(msg: impl AsRef<str>) {
     let _ = window().alert_with_message(msg.as_ref());
@@ -20,6 +21,15 @@ pub fn shorten_address(address: impl ToString) -> String {
     }
 }
 
+/// The contract of a SNIP-20 token. Native tokens have none, so pairs with them can't be traded
+/// through the router.
+pub fn token_contract(token: &TokenType) -> Result<ContractInfo, Error> {
+    token
+        .clone()
+        .into_contract_info()
+        .ok_or(Error::generic("Native tokens are not supported"))
+}
+
 pub fn get_token_decimals(address: &str) -> Result<u8, Error> {
     TOKEN_MAP
         .get(address)
@@ -27,6 +37,15 @@ pub fn get_token_decimals(address: &str) -> Result<u8, Error> {
         .ok_or(Error::UnknownToken)
 }
 
+/// The name to show for the token at `address`: its display name or symbol if it's in the token
+/// map, or else the shortened address.
+pub fn get_token_symbol(address: &str) -> String {
+    TOKEN_MAP
+        .get(address)
+        .map(|token| token.display_name.clone().unwrap_or(token.symbol.clone()))
+        .unwrap_or_else(|| shorten_address(address))
+}
+
 // pub fn display_token_amount(amount: impl Into<u128>, decimals: impl Into<u32>) -> String {
 //     let value = amount.into();
 //     let factor = 10u128.pow(decimals.into());
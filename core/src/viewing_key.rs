@@ -0,0 +1,159 @@
+//! Creating, storing and registering viewing keys.
+//!
+//! Keys created here are kept in local storage per owner and chain. SNIP-20 keys are also
+//! registered with the wallet, so other apps can use them too.
+
+use crate::{
+    error::Error,
+    tx::{TxKind, TxManager, TxRecord, TxSummary},
+    CHAIN_ID,
+};
+use cosmwasm_std::ContractInfo;
+use keplr::wallet;
+use leptos::prelude::window;
+use rsecret::TxOptions;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use tracing::{debug, warn};
+use web_sys::js_sys::{Date, Math};
+
+/// The kind of contract a viewing key is for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenStandard {
+    Snip20,
+    /// LB pairs, which hold their LB tokens as SNIP-1155 balances.
+    Snip1155,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ViewingKeyResponse {
+    CreateViewingKey { key: String },
+}
+
+fn storage_key(owner: &str) -> String {
+    format!("viewing_keys_{CHAIN_ID}_{owner}")
+}
+
+/// Returns the viewing keys saved for `owner`, by contract address.
+pub fn saved(owner: &str) -> HashMap<String, String> {
+    window()
+        .local_storage()
+        .ok()
+        .flatten()
+        .and_then(|storage| storage.get_item(&storage_key(owner)).ok().flatten())
+        .and_then(|keys| {
+            serde_json::from_str(&keys)
+                .inspect_err(|error| warn!("discarding unreadable viewing keys: {error}"))
+                .ok()
+        })
+        .unwrap_or_default()
+}
+
+fn save(owner: &str, keys: &HashMap<String, String>) {
+    if let Some(storage) = window().local_storage().ok().flatten() {
+        let keys = serde_json::to_string(keys).expect("viewing keys are serializable");
+        _ = storage.set_item(&storage_key(owner), &keys);
+    }
+}
+
+fn remember(owner: &str, contract_address: &str, key: &str) {
+    let mut keys = saved(owner);
+    keys.insert(contract_address.to_string(), key.to_string());
+    save(owner, &keys);
+}
+
+/// Removes the saved viewing key of `owner` for `contract_address`.
+pub fn forget(owner: &str, contract_address: &str) {
+    let mut keys = saved(owner);
+    keys.remove(contract_address);
+    save(owner, &keys);
+}
+
+/// Returns the viewing key of `owner` for `contract_address`, looking in local storage first
+/// and then in the wallet.
+pub async fn get(owner: &str, contract_address: &str) -> Option<String> {
+    if let Some(key) = saved(owner).remove(contract_address) {
+        return Some(key);
+    }
+
+    wallet::active()
+        .get_secret_20_viewing_key(CHAIN_ID, contract_address)
+        .await
+        .ok()
+}
+
+/// Asks the contract to generate a new viewing key, replacing any existing one.
+pub async fn create(
+    tx_manager: TxManager,
+    owner: &str,
+    contract: &ContractInfo,
+    standard: TokenStandard,
+) -> Result<String, Error> {
+    // The contract mixes this with its own randomness, so it only needs to differ per call.
+    let entropy = format!("{}{}", Date::now(), Math::random());
+    let msg = json!({ "create_viewing_key": { "entropy": entropy } });
+
+    let tx = execute(tx_manager, owner, contract, &msg).await?;
+
+    let data = tx.data.first().cloned().unwrap_or_default();
+    let ViewingKeyResponse::CreateViewingKey { key } = serde_json::from_slice(&data)?;
+
+    register(owner, contract, standard, &key).await;
+
+    Ok(key)
+}
+
+/// Sets a viewing key chosen by the user, replacing any existing one.
+pub async fn set(
+    tx_manager: TxManager,
+    owner: &str,
+    contract: &ContractInfo,
+    standard: TokenStandard,
+    key: String,
+) -> Result<String, Error> {
+    let msg = json!({ "set_viewing_key": { "key": key } });
+
+    execute(tx_manager, owner, contract, &msg).await?;
+    register(owner, contract, standard, &key).await;
+
+    Ok(key)
+}
+
+async fn execute(
+    tx_manager: TxManager,
+    owner: &str,
+    contract: &ContractInfo,
+    msg: &serde_json::Value,
+) -> Result<TxRecord, Error> {
+    let execute_msg = tx_manager.execute_msg(owner, contract, msg).await?;
+
+    let tx_options = TxOptions::default();
+
+    // The zero amount marks the token, so its balance refreshes once the key is set.
+    let summary =
+        TxSummary::new(TxKind::ViewingKey).with_token(contract.address.to_string(), 0u128);
+
+    tx_manager
+        .submit(summary, vec![execute_msg], tx_options)
+        .await
+}
+
+async fn register(owner: &str, contract: &ContractInfo, standard: TokenStandard, key: &str) {
+    remember(owner, contract.address.as_str(), key);
+
+    if standard == TokenStandard::Snip20 {
+        debug!(
+            "registering the viewing key for {} with the wallet",
+            contract.address
+        );
+
+        if let Err(error) = wallet::active()
+            .suggest_token(CHAIN_ID, contract.address.as_str(), Some(key))
+            .await
+        {
+            warn!("the wallet did not accept the viewing key: {error}");
+        }
+    }
+}
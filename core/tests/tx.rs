@@ -0,0 +1,238 @@
+use ammber_core::{
+    constants::REFERENCE_STABLECOIN, Balances, Endpoint, Error, TxKind, TxManager, TxRecord,
+    TxStatus, TxSummary, TOKEN_MAP,
+};
+use cosmwasm_std::ContractInfo;
+use futures::join;
+use gloo_timers::future::TimeoutFuture;
+use keplr::{mock::MockKeplr, Key};
+use leptos::prelude::*;
+use prost::Message;
+use rsecret::TxOptions;
+use secretrs::{
+    proto::cosmos::{
+        auth::v1beta1::{BaseAccount, QueryAccountResponse},
+        base::abci::v1beta1::GasInfo,
+        tx::v1beta1::SimulateResponse,
+    },
+    Any,
+};
+use std::cell::OnceCell;
+use wasm_bindgen_test::*;
+use web_sys::{
+    js_sys::{Function, Promise, Reflect, Uint8Array},
+    wasm_bindgen::{closure::Closure, JsCast, JsValue},
+    Headers, Request, Response, ResponseInit,
+};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const ADDRESS: &str = "secret1ap26qrlp8mcq2pg6r47w43l0y8zkqm8a450s03";
+/// The compressed secp256k1 generator point, which is a valid public key.
+const PUB_KEY: [u8; 33] = [
+    0x02, 0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87, 0x0b,
+    0x07, 0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8, 0x17,
+    0x98,
+];
+const GAS_USED: u64 = 100_000;
+
+const ACCOUNT: &str = "/cosmos.auth.v1beta1.Query/Account";
+const SIMULATE: &str = "/cosmos.tx.v1beta1.Service/Simulate";
+
+thread_local! {
+    static KEPLR: OnceCell<MockKeplr> = const { OnceCell::new() };
+}
+
+/// keplr-sys caches `window.keplr` on first access, so every test shares one mock.
+fn keplr() -> MockKeplr {
+    KEPLR.with(|keplr| {
+        keplr
+            .get_or_init(|| {
+                let keplr = MockKeplr::new().with_key(Key {
+                    pub_key: PUB_KEY.to_vec(),
+                    bech32_address: ADDRESS.to_string(),
+                    ..Default::default()
+                });
+                keplr.install().expect("failed to install the mock");
+                keplr
+            })
+            .clone()
+    })
+}
+
+/// A gRPC-web frame: a flag byte, the length, then the payload.
+fn frame(flag: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![flag];
+    frame.extend((payload.len() as u32).to_be_bytes());
+    frame.extend(payload);
+    frame
+}
+
+/// Answers the gRPC-web calls sent to `url` with `respond`, which gets the method path and
+/// returns the encoded response or a failure message. Other requests go to the real `fetch`.
+fn fake_node(url: &'static str, respond: impl Fn(&str) -> Result<Vec<u8>, String> + 'static) {
+    let window = web_sys::window().expect("no window");
+    let fetch: Function = Reflect::get(&window, &"fetch".into())
+        .expect("no fetch")
+        .unchecked_into();
+
+    let this = window.clone();
+    let fake = Closure::<dyn Fn(JsValue, JsValue) -> Promise>::new(move |input: JsValue, init| {
+        let window = &this;
+        let target = input
+            .as_string()
+            .unwrap_or_else(|| input.unchecked_ref::<Request>().url());
+
+        let Some(method) = target.strip_prefix(url) else {
+            return fetch
+                .call2(window, &input, &init)
+                .map(JsCast::unchecked_into)
+                .unwrap_or_else(|error| Promise::reject(&error));
+        };
+
+        let body = match respond(method) {
+            Ok(message) => [frame(0x00, &message), frame(0x80, b"grpc-status:0\r\n")].concat(),
+            Err(message) => frame(
+                0x80,
+                format!("grpc-status:2\r\ngrpc-message:{message}\r\n").as_bytes(),
+            ),
+        };
+
+        let headers = Headers::new().unwrap();
+        headers
+            .set("content-type", "application/grpc-web+proto")
+            .unwrap();
+        let response_init = ResponseInit::new();
+        response_init.set_status(200);
+        response_init.set_headers(&headers);
+        let response = Response::new_with_opt_buffer_source_and_init(
+            Some(&Uint8Array::from(&body[..])),
+            &response_init,
+        )
+        .unwrap();
+
+        Promise::resolve(&response)
+    });
+
+    Reflect::set(&window, &"fetch".into(), &fake.into_js_value()).expect("failed to fake fetch");
+}
+
+fn account() -> Vec<u8> {
+    QueryAccountResponse {
+        account: Some(Any {
+            type_url: "/cosmos.auth.v1beta1.BaseAccount".to_string(),
+            value: BaseAccount {
+                address: ADDRESS.to_string(),
+                sequence: 7,
+                ..Default::default()
+            }
+            .encode_to_vec(),
+        }),
+    }
+    .encode_to_vec()
+}
+
+fn simulation() -> Vec<u8> {
+    SimulateResponse {
+        gas_info: Some(GasInfo {
+            gas_wanted: 0,
+            gas_used: GAS_USED,
+        }),
+        ..Default::default()
+    }
+    .encode_to_vec()
+}
+
+/// Sets a viewing key on the reference stablecoin, which every chain's token map has.
+async fn set_viewing_key(tx_manager: TxManager) -> Result<TxRecord, Error> {
+    let token = ContractInfo::from(
+        TOKEN_MAP
+            .get(*REFERENCE_STABLECOIN)
+            .expect("the stablecoin is in the token map"),
+    );
+    let msg = secret_toolkit_snip20::HandleMsg::SetViewingKey {
+        key: "api_key_test".to_string(),
+        padding: None,
+    };
+    let msg = tx_manager.execute_msg(ADDRESS, &token, &msg).await?;
+
+    tx_manager
+        .submit(
+            TxSummary::new(TxKind::Other),
+            vec![msg],
+            TxOptions::default(),
+        )
+        .await
+}
+
+#[wasm_bindgen_test]
+async fn failed_simulation_never_asks_to_sign() {
+    const NODE: &str = "http://failing.node.test";
+    fake_node(NODE, |method| match method {
+        ACCOUNT => Ok(account()),
+        _ => Err("out of gas".to_string()),
+    });
+    let keplr = keplr();
+    keplr.clear_calls();
+
+    let tx_manager = TxManager::new(Endpoint::new(NODE), Balances::new());
+
+    let result = set_viewing_key(tx_manager).await;
+
+    assert!(result.is_err());
+
+    let records = tx_manager.records().get_untracked();
+    assert_eq!(records.len(), 1);
+    assert!(matches!(records[0].status, TxStatus::Failed(_)));
+    assert!(records[0].fee.is_none());
+
+    let calls = keplr.calls();
+    assert!(calls.contains(&"getKey".to_string()));
+    assert!(!calls.iter().any(|call| call.starts_with("sign")));
+}
+
+#[wasm_bindgen_test]
+async fn waits_for_approval_before_signing() {
+    const NODE: &str = "http://node.test";
+    fake_node(NODE, |method| match method {
+        ACCOUNT => Ok(account()),
+        SIMULATE => Ok(simulation()),
+        _ => Err(format!("{method} is not implemented")),
+    });
+    let keplr = keplr();
+    keplr.clear_calls();
+
+    let tx_manager = TxManager::new(Endpoint::new(NODE), Balances::new());
+
+    // follows the record, and cancels it once it asks for approval
+    let watch = async {
+        let mut statuses: Vec<TxStatus> = vec![];
+        loop {
+            if let Some(record) = tx_manager.records().get_untracked().first() {
+                if statuses.last() != Some(&record.status) {
+                    statuses.push(record.status.clone());
+                }
+                if record.status == TxStatus::AwaitingApproval {
+                    let fee = record.fee.clone().expect("the fee is estimated");
+                    assert_eq!(fee.gas_used, GAS_USED);
+                    assert!(fee.gas_limit > GAS_USED);
+                    tx_manager.reject(record.id);
+                }
+                if record.status.is_settled() {
+                    return statuses;
+                }
+            }
+            TimeoutFuture::new(5).await;
+        }
+    };
+
+    let (result, statuses) = join!(set_viewing_key(tx_manager), watch);
+
+    assert_eq!(result.unwrap_err().to_string(), "Transaction cancelled");
+    assert!(statuses.contains(&TxStatus::AwaitingApproval));
+    assert!(!statuses.contains(&TxStatus::Broadcasting));
+    assert!(matches!(statuses.last(), Some(TxStatus::Failed(_))));
+
+    let calls = keplr.calls();
+    assert!(!calls.iter().any(|call| call.starts_with("sign")));
+}
@@ -5,16 +5,7 @@ use std::collections::HashMap;
 use std::sync::{Arc, LazyLock};
 
 pub static BATCH_QUERY_ROUTER: LazyLock<HashMap<ChainId, ContractInfo>> = LazyLock::new(|| {
-    HashMap::from_iter([
-        (
-            ChainId::Dev,
-            ContractInfo {
-                // WARN: this address needs to be updated whenever creating a new localsecret
-                address: Addr::unchecked("secret15zvwtzf38yqhdzt2svdk7mnc5ha24493tqydn2"),
-                code_hash: "1c7e86ba4fdb6760e70bf08a7df7f44b53eb0b23290e3e69ca96140810d4f432"
-                    .to_string(),
-            },
-        ),
+    let mut routers = HashMap::from_iter([
         (
             ChainId::Pulsar,
             ContractInfo {
@@ -31,7 +22,15 @@ pub static BATCH_QUERY_ROUTER: LazyLock<HashMap<ChainId, ContractInfo>> = LazyLo
                     .to_string(),
             },
         ),
-    ])
+    ]);
+
+    // The localsecret address changes with every new chain, so it comes from the deployment file
+    // (if a router was deployed at all).
+    if let Some(router) = DEV_CONTRACTS.batch_query_router.clone() {
+        routers.insert(ChainId::Dev, router);
+    }
+
+    routers
 });
 
 // TODO: Once more stable, these values should be hardcoded instead.
@@ -68,6 +67,8 @@ pub struct DeployedContracts {
     pub lb_token: ContractInfo,
     pub lb_router: ContractInfo,
     pub lb_quoter: ContractInfo,
+    #[serde(default)]
+    pub batch_query_router: Option<ContractInfo>,
 }
 
 // Embed and deserialize the JSON files at compile time
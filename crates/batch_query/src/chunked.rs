@@ -0,0 +1,441 @@
+use crate::{encode_query, parse_batch_query, types::*};
+use cosmwasm_std::ContractInfo;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::Serialize;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Controls how a large list of queries is split up and executed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchConfig {
+    /// Maximum number of queries sent to the router in a single batch.
+    pub chunk_size: usize,
+    /// Maximum number of batches in flight at the same time.
+    pub max_concurrency: usize,
+    /// Number of additional attempts for a batch that fails as a whole.
+    pub max_retries: u32,
+    /// How long to wait before the first retry. The wait doubles with every retry after it.
+    pub retry_delay: Duration,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 25,
+            max_concurrency: 4,
+            max_retries: 2,
+            retry_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl BatchConfig {
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// The wait before retry number `attempt`, counting from 1.
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.retry_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+}
+
+/// Anything that can send a `BatchQuery` to a batch query router, or a plain query to a contract.
+///
+/// This is the only part of the batch layer that touches the network, so it's the part to mock.
+// The futures don't need to be `Send`; everything runs on the single-threaded wasm executor.
+#[allow(async_fn_in_trait)]
+pub trait BatchQuerier {
+    type Error: std::fmt::Display;
+
+    async fn query_batch(
+        &self,
+        router: &ContractInfo,
+        batch: &BatchQuery,
+    ) -> Result<BatchQueryResponse, Self::Error>;
+
+    /// Used for chains that don't have a batch query router.
+    async fn query_contract(
+        &self,
+        contract: &ContractInfo,
+        query: &serde_json::Value,
+    ) -> Result<String, Self::Error>;
+}
+
+/// Splits `queries` into chunks, runs them with bounded concurrency, retries failed chunks, and
+/// merges the parsed items back together in the original order. Queries that can't be serialized
+/// are never sent, and come back as `ERROR` items in their place.
+pub async fn batch_query_chunked<Q, T>(
+    querier: &Q,
+    router: &ContractInfo,
+    queries: Vec<BatchQueryParams<T>>,
+    config: BatchConfig,
+) -> Result<BatchQueryParsedResponse, Q::Error>
+where
+    Q: BatchQuerier,
+    T: Serialize,
+{
+    let mut rejected = Vec::new();
+    let mut encoded = Vec::with_capacity(queries.len());
+
+    for (index, query) in queries.into_iter().enumerate() {
+        let id = query.id.clone();
+        match encode_query(query) {
+            Ok(item) => encoded.push(item),
+            Err(error) => rejected.push((index, invalid_query(id, &error))),
+        }
+    }
+
+    let chunks = chunk_queries(encoded, config.chunk_size);
+
+    debug!(
+        "sending {} batch(es) with concurrency {}",
+        chunks.len(),
+        config.max_concurrency
+    );
+
+    let responses: Vec<BatchQueryParsedResponse> = stream::iter(chunks.into_iter().enumerate())
+        .map(|(index, batch)| query_with_retry(querier, router, index, batch, config))
+        // `buffered` (not `buffer_unordered`) keeps the output in the same order as the input
+        .buffered(config.max_concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    Ok(merge_responses(responses, rejected))
+}
+
+/// Sends each query on its own, one after another. Failed queries become `ERROR` items instead of
+/// failing the whole batch, the same as a router would report them.
+pub async fn query_sequential<Q, T>(
+    querier: &Q,
+    queries: Vec<BatchQueryParams<T>>,
+) -> BatchQueryParsedResponse
+where
+    Q: BatchQuerier,
+    T: Serialize,
+{
+    debug!(
+        "no batch router, sending {} queries one by one",
+        queries.len()
+    );
+
+    let mut items = Vec::with_capacity(queries.len());
+
+    for query in queries {
+        let query_msg = match serde_json::to_value(&query.query_msg) {
+            Ok(query_msg) => query_msg,
+            Err(error) => {
+                items.push(invalid_query(query.id, &error));
+                continue;
+            }
+        };
+
+        let (response, status) = match querier.query_contract(&query.contract, &query_msg).await {
+            Ok(response) => (response, BatchItemResponseStatus::SUCCESS),
+            Err(error) => (error.to_string(), BatchItemResponseStatus::ERROR),
+        };
+
+        items.push(BatchQueryParsedResponseItem {
+            id: query.id,
+            response,
+            status,
+            // individual queries don't report the height they were answered at
+            block_height: 0,
+        });
+    }
+
+    BatchQueryParsedResponse { items }
+}
+
+fn invalid_query(id: String, error: &serde_json::Error) -> BatchQueryParsedResponseItem {
+    BatchQueryParsedResponseItem {
+        id,
+        response: format!("invalid query message: {error}"),
+        status: BatchItemResponseStatus::ERROR,
+        block_height: 0,
+    }
+}
+
+fn chunk_queries(queries: Vec<BatchQueryItem>, chunk_size: usize) -> Vec<BatchQuery> {
+    let chunk_size = chunk_size.max(1);
+    let mut chunks = Vec::with_capacity(queries.len().div_ceil(chunk_size));
+    let mut queries = queries.into_iter().peekable();
+
+    while queries.peek().is_some() {
+        let chunk: Vec<BatchQueryItem> = queries.by_ref().take(chunk_size).collect();
+        chunks.push(BatchQuery {
+            batch: Batch { queries: chunk },
+        });
+    }
+
+    chunks
+}
+
+async fn query_with_retry<Q: BatchQuerier>(
+    querier: &Q,
+    router: &ContractInfo,
+    index: usize,
+    batch: BatchQuery,
+    config: BatchConfig,
+) -> Result<BatchQueryParsedResponse, Q::Error> {
+    let max_retries = config.max_retries;
+    let mut attempt = 0;
+
+    loop {
+        match querier.query_batch(router, &batch).await {
+            Ok(response) => return Ok(parse_batch_query(response)),
+            Err(error) if attempt < max_retries => {
+                attempt += 1;
+                let delay = config.backoff(attempt);
+                warn!(
+                    "batch {index} failed, retrying in {delay:?} ({attempt}/{max_retries}): {error}"
+                );
+                // a rate-limited or restarting node needs time, not another request right away
+                if !delay.is_zero() {
+                    gloo_timers::future::sleep(delay).await;
+                }
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// Puts the items of every chunk back together, with the `rejected` items at their original
+/// indices.
+fn merge_responses(
+    responses: Vec<BatchQueryParsedResponse>,
+    rejected: Vec<(usize, BatchQueryParsedResponseItem)>,
+) -> BatchQueryParsedResponse {
+    let mut items: Vec<BatchQueryParsedResponseItem> = responses
+        .into_iter()
+        .flat_map(|response| response.items)
+        .collect();
+
+    // in ascending order, so every earlier index is already filled
+    for (index, item) in rejected {
+        items.insert(index.min(items.len()), item);
+    }
+
+    BatchQueryParsedResponse { items }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{contract, queries, FakeRouter};
+    use futures::executor::block_on;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    #[test]
+    fn splits_queries_into_chunks() {
+        let router = FakeRouter::default();
+        let config = BatchConfig::default().with_chunk_size(3);
+
+        let response = block_on(batch_query_chunked(
+            &router,
+            &contract(),
+            queries(10),
+            config,
+        ));
+
+        assert_eq!(response.unwrap().items.len(), 10);
+        assert_eq!(router.batch_sizes.into_inner(), vec![3, 3, 3, 1]);
+    }
+
+    #[test]
+    fn keeps_the_original_order() {
+        let router = FakeRouter::default();
+        let config = BatchConfig::default()
+            .with_chunk_size(3)
+            .with_max_concurrency(4);
+
+        let response = block_on(batch_query_chunked(
+            &router,
+            &contract(),
+            queries(10),
+            config,
+        ))
+        .unwrap();
+
+        let ids: Vec<String> = response.items.iter().map(|item| item.id.clone()).collect();
+        let expected: Vec<String> = (0..10).map(|index| index.to_string()).collect();
+        assert_eq!(ids, expected);
+
+        for (index, item) in response.items.iter().enumerate() {
+            assert_eq!(item.status, BatchItemResponseStatus::SUCCESS);
+            assert_eq!(item.block_height, 100);
+            let query: Value = serde_json::from_str(&item.response).unwrap();
+            assert_eq!(query, json!({ "get_bin": { "id": index } }));
+        }
+    }
+
+    #[test]
+    fn retries_a_failed_chunk() {
+        let router = FakeRouter::failing("0", 2);
+        let config = BatchConfig::default()
+            .with_max_retries(2)
+            .with_retry_delay(Duration::ZERO);
+
+        let response = block_on(batch_query_chunked(
+            &router,
+            &contract(),
+            queries(5),
+            config,
+        ));
+
+        assert_eq!(response.unwrap().items.len(), 5);
+        assert_eq!(router.calls.get(), 3);
+    }
+
+    #[test]
+    fn gives_up_when_retries_run_out() {
+        let router = FakeRouter::failing("3", 3);
+        let config = BatchConfig::default()
+            .with_chunk_size(3)
+            .with_max_retries(2)
+            .with_retry_delay(Duration::ZERO);
+
+        let response = block_on(batch_query_chunked(
+            &router,
+            &contract(),
+            queries(6),
+            config,
+        ));
+
+        assert_eq!(response.unwrap_err(), "router unavailable");
+        assert_eq!(router.failures.borrow()["3"], 0);
+    }
+
+    #[test]
+    fn doubles_the_delay_between_retries() {
+        let config = BatchConfig::default().with_retry_delay(Duration::from_millis(100));
+
+        let delays: Vec<Duration> = (1..=4).map(|attempt| config.backoff(attempt)).collect();
+
+        assert_eq!(
+            delays,
+            [100, 200, 400, 800].map(Duration::from_millis).to_vec()
+        );
+        // saturates instead of overflowing
+        assert_eq!(config.backoff(40), config.backoff(u32::MAX));
+    }
+
+    #[test]
+    fn limits_concurrency() {
+        let router = FakeRouter::default();
+        let config = BatchConfig::default()
+            .with_chunk_size(1)
+            .with_max_concurrency(3);
+
+        let response = block_on(batch_query_chunked(
+            &router,
+            &contract(),
+            queries(10),
+            config,
+        ));
+
+        assert_eq!(response.unwrap().items.len(), 10);
+        assert_eq!(router.calls.get(), 10);
+        assert_eq!(router.max_in_flight.get(), 3);
+    }
+
+    #[test]
+    fn sequential_queries_report_failures_per_item() {
+        let router = FakeRouter::default();
+        let queries = vec![
+            BatchQueryParams {
+                id: "ok".to_string(),
+                contract: contract(),
+                query_msg: HashMap::from([("get_active_id".to_string(), json!({}))]),
+            },
+            BatchQueryParams {
+                id: "fails".to_string(),
+                contract: contract(),
+                query_msg: HashMap::from([("fail".to_string(), json!({}))]),
+            },
+        ];
+
+        let response = block_on(query_sequential(&router, queries));
+
+        assert_eq!(response.items[0].id, "ok");
+        assert_eq!(response.items[0].status, BatchItemResponseStatus::SUCCESS);
+        assert_eq!(response.items[0].response, r#"{"get_active_id":{}}"#);
+        assert_eq!(response.items[1].id, "fails");
+        assert_eq!(response.items[1].status, BatchItemResponseStatus::ERROR);
+        assert_eq!(response.items[1].response, "query failed");
+    }
+
+    #[test]
+    fn sequential_queries_survive_unserializable_messages() {
+        let router = FakeRouter::default();
+        // JSON object keys must be strings
+        let queries = vec![
+            BatchQueryParams {
+                id: "bad".to_string(),
+                contract: contract(),
+                query_msg: HashMap::from([((1u8, 2u8), 3u8)]),
+            },
+            BatchQueryParams {
+                id: "good".to_string(),
+                contract: contract(),
+                query_msg: HashMap::new(),
+            },
+        ];
+
+        let response = block_on(query_sequential(&router, queries));
+
+        assert_eq!(response.items[0].status, BatchItemResponseStatus::ERROR);
+        assert!(response.items[0]
+            .response
+            .starts_with("invalid query message"));
+        assert_eq!(response.items[1].status, BatchItemResponseStatus::SUCCESS);
+        assert_eq!(router.calls.get(), 1);
+    }
+
+    #[test]
+    fn chunked_queries_survive_unserializable_messages() {
+        let router = FakeRouter::default();
+        let config = BatchConfig::default().with_chunk_size(2);
+        let queries = ["0", "bad", "1", "2"]
+            .into_iter()
+            .map(|id| BatchQueryParams {
+                id: id.to_string(),
+                contract: contract(),
+                // JSON object keys must be strings
+                query_msg: if id == "bad" {
+                    HashMap::from([((1u8, 2u8), 3u8)])
+                } else {
+                    HashMap::new()
+                },
+            })
+            .collect();
+
+        let response =
+            block_on(batch_query_chunked(&router, &contract(), queries, config)).unwrap();
+
+        let ids: Vec<&str> = response.items.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, vec!["0", "bad", "1", "2"]);
+        assert_eq!(response.items[1].status, BatchItemResponseStatus::ERROR);
+        assert!(response.items[1]
+            .response
+            .starts_with("invalid query message"));
+        assert_eq!(response.items[2].status, BatchItemResponseStatus::SUCCESS);
+        // the bad query is never sent, so the other three fit in two batches
+        assert_eq!(router.batch_sizes.into_inner(), vec![2, 1]);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{batch_query_chunked, query_sequential, types::*, BatchConfig, BatchQuerier};
+use cosmwasm_std::ContractInfo;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Batch query router contracts, keyed by chain id.
+#[derive(Clone, Debug, Default)]
+pub struct BatchRouterRegistry(HashMap<String, ContractInfo>);
+
+impl BatchRouterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_router(mut self, chain_id: impl Into<String>, router: ContractInfo) -> Self {
+        self.0.insert(chain_id.into(), router);
+        self
+    }
+
+    pub fn get(&self, chain_id: &str) -> Option<&ContractInfo> {
+        self.0.get(chain_id)
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, ContractInfo)> for BatchRouterRegistry {
+    fn from_iter<I: IntoIterator<Item = (S, ContractInfo)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(chain_id, router)| (chain_id.into(), router))
+                .collect(),
+        )
+    }
+}
+
+/// Sends batch queries for a single chain.
+///
+/// If the registry has no router for the chain, queries are sent individually instead.
+pub struct BatchClient<Q> {
+    querier: Q,
+    router: Option<ContractInfo>,
+    config: BatchConfig,
+}
+
+impl<Q: BatchQuerier> BatchClient<Q> {
+    pub fn new(querier: Q, registry: &BatchRouterRegistry, chain_id: &str) -> Self {
+        Self {
+            querier,
+            router: registry.get(chain_id).cloned(),
+            config: BatchConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: BatchConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn router(&self) -> Option<&ContractInfo> {
+        self.router.as_ref()
+    }
+
+    pub async fn query<T: Serialize>(
+        &self,
+        queries: Vec<BatchQueryParams<T>>,
+    ) -> Result<BatchQueryParsedResponse, Q::Error> {
+        match self.router {
+            Some(ref router) => {
+                batch_query_chunked(&self.querier, router, queries, self.config).await
+            }
+            None => Ok(query_sequential(&self.querier, queries).await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{contract, queries, FakeRouter};
+    use futures::executor::block_on;
+
+    #[test]
+    fn uses_the_router_of_the_chain() {
+        let registry = BatchRouterRegistry::new().with_router("secret-4", contract());
+        let client = BatchClient::new(FakeRouter::default(), &registry, "secret-4");
+
+        let response = block_on(client.query(queries(3))).unwrap();
+
+        assert_eq!(client.router(), Some(&contract()));
+        assert_eq!(response.items.len(), 3);
+        assert!(response.items.iter().all(|item| item.block_height == 100));
+        assert_eq!(client.querier.batch_sizes.borrow().as_slice(), &[3]);
+    }
+
+    #[test]
+    fn queries_one_by_one_without_a_router() {
+        let registry = BatchRouterRegistry::new().with_router("secret-4", contract());
+        let client = BatchClient::new(FakeRouter::default(), &registry, "pulsar-3");
+
+        let response = block_on(client.query(queries(3))).unwrap();
+
+        assert_eq!(client.router(), None);
+        let ids: Vec<&str> = response.items.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, vec!["0", "1", "2"]);
+        assert!(response
+            .items
+            .iter()
+            .all(|item| item.status == BatchItemResponseStatus::SUCCESS && item.block_height == 0));
+        assert!(client.querier.batch_sizes.borrow().is_empty());
+        assert_eq!(client.querier.calls.get(), 3);
+    }
+}
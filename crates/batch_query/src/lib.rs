@@ -1,105 +1,55 @@
 use base64::prelude::{Engine as _, BASE64_STANDARD};
-use cosmwasm_std::{Addr, ContractInfo};
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::sync::LazyLock;
-use tracing::{debug, info, trace};
-
-pub struct BatchQueryRouter {
-    pub localsecret: ContractInfo,
-    pub pulsar: ContractInfo,
-    pub mainnet: ContractInfo,
-}
-
-// pub static BATCH_QUERY_ROUTER: LazyLock<ContractInfo> = LazyLock::new(|| {
-//     if CHAIN_ID == "secretdev-1" {
-//         ContractInfo {
-//             // FIXME: this address needs to be updated manually
-//             address: Addr::unchecked("secret1rgqxfst0frq5mgmw3e5pzajpre4qwepc2uh22m"),
-//             code_hash: "1c7e86ba4fdb6760e70bf08a7df7f44b53eb0b23290e3e69ca96140810d4f432"
-//                 .to_string(),
-//         }
-//     } else if CHAIN_ID == "pulsar-3" {
-//         ContractInfo {
-//             address: Addr::unchecked("secret19a9emj5ym504a5824vc7g5awaj2z5nwsl8jpcz"),
-//             code_hash: "1c7e86ba4fdb6760e70bf08a7df7f44b53eb0b23290e3e69ca96140810d4f432"
-//                 .to_string(),
-//         }
-//     } else {
-//         ContractInfo {
-//             address: Addr::unchecked("secret15mkmad8ac036v4nrpcc7nk8wyr578egt077syt"),
-//             code_hash: "1c7e86ba4fdb6760e70bf08a7df7f44b53eb0b23290e3e69ca96140810d4f432"
-//                 .to_string(),
-//         }
-//     }
-// });
-
-// TODO: figure out how to make this better. it can't rely on a crate-wide static, now that
-// it's a separate crate.
-
-pub fn get_batch_query_router(chain_id: &str) -> &'static ContractInfo {
-    match chain_id {
-        "secretdev-1" => &BATCH_QUERY_ROUTER.localsecret,
-        "pulsar-3" => &BATCH_QUERY_ROUTER.pulsar,
-        "secret-4" => &BATCH_QUERY_ROUTER.mainnet,
-        _ => panic!("Unsupported chain ID: {}", chain_id),
-    }
-}
-
-pub static BATCH_QUERY_ROUTER: LazyLock<BatchQueryRouter> = LazyLock::new(|| {
-    BatchQueryRouter {
-        localsecret: ContractInfo {
-            // FIXME: this address needs to be updated manually
-            address: Addr::unchecked("secret1rgqxfst0frq5mgmw3e5pzajpre4qwepc2uh22m"),
-            code_hash: "1c7e86ba4fdb6760e70bf08a7df7f44b53eb0b23290e3e69ca96140810d4f432"
-                .to_string(),
-        },
-        pulsar: ContractInfo {
-            address: Addr::unchecked("secret19a9emj5ym504a5824vc7g5awaj2z5nwsl8jpcz"),
-            code_hash: "1c7e86ba4fdb6760e70bf08a7df7f44b53eb0b23290e3e69ca96140810d4f432"
-                .to_string(),
-        },
-        mainnet: ContractInfo {
-            address: Addr::unchecked("secret15mkmad8ac036v4nrpcc7nk8wyr578egt077syt"),
-            code_hash: "1c7e86ba4fdb6760e70bf08a7df7f44b53eb0b23290e3e69ca96140810d4f432"
-                .to_string(),
-        },
-    }
-});
+use serde::Serialize;
 
+mod chunked;
+mod client;
+#[cfg(test)]
+mod testing;
 mod types;
 use types::*;
 
+pub use chunked::{batch_query_chunked, query_sequential, BatchConfig, BatchQuerier};
+pub use client::{BatchClient, BatchRouterRegistry};
 pub use types::{
     BatchItemResponseStatus, BatchQuery, BatchQueryParams, BatchQueryParsedResponse,
-    BatchQueryResponse,
+    BatchQueryParsedResponseItem, BatchQueryResponse,
 };
 
-fn decode_b64_to_json<T: DeserializeOwned>(base64_str: &str) -> T {
-    let decoded = BASE64_STANDARD.decode(base64_str).expect("Invalid Base64");
-    debug!("{}", String::from_utf8(decoded.clone()).unwrap());
-    serde_json::from_slice(&decoded).expect("Invalid JSON")
-}
-
 fn decode_b64_to_string(base64_str: &str) -> String {
     let decoded = BASE64_STANDARD.decode(base64_str).expect("Invalid Base64");
     String::from_utf8(decoded).expect("Invalid UTF-8")
 }
 
-pub fn msg_batch_query<T: Serialize>(queries: Vec<BatchQueryParams<T>>) -> BatchQuery {
+/// Ids are sent as JSON strings, and the router echoes them back as they were sent.
+fn decode_b64_to_id(base64_str: &str) -> String {
+    let id = decode_b64_to_string(base64_str);
+    serde_json::from_str(&id).unwrap_or(id)
+}
+
+fn encode_query<T: Serialize>(
+    batch_query: BatchQueryParams<T>,
+) -> Result<BatchQueryItem, serde_json::Error> {
+    Ok(BatchQueryItem {
+        id: BASE64_STANDARD.encode(serde_json::to_string(&batch_query.id)?),
+        contract: batch_query.contract,
+        query: BASE64_STANDARD.encode(serde_json::to_string(&batch_query.query_msg)?),
+    })
+}
+
+/// Fails if any of the query messages can't be serialized.
+pub fn msg_batch_query<T: Serialize>(
+    queries: Vec<BatchQueryParams<T>>,
+) -> Result<BatchQuery, serde_json::Error> {
     let batch_queries = queries
         .into_iter()
-        .map(|batch_query| BatchQueryItem {
-            id: BASE64_STANDARD.encode(&serde_json::to_string(&batch_query.id).unwrap()),
-            contract: batch_query.contract,
-            query: BASE64_STANDARD.encode(&serde_json::to_string(&batch_query.query_msg).unwrap()),
-        })
-        .collect();
+        .map(encode_query)
+        .collect::<Result<_, _>>()?;
 
-    BatchQuery {
+    Ok(BatchQuery {
         batch: Batch {
             queries: batch_queries,
         },
-    }
+    })
 }
 
 pub fn parse_batch_query(response: BatchQueryResponse) -> BatchQueryParsedResponse {
@@ -110,14 +60,14 @@ pub fn parse_batch_query(response: BatchQueryResponse) -> BatchQueryParsedRespon
         .map(|item| {
             if let Some(system_err) = item.response.system_err {
                 BatchQueryParsedResponseItem {
-                    id: decode_b64_to_string(&item.id), // Decode id from Base64
-                    response: system_err,               // Directly use the system_err string
+                    id: decode_b64_to_id(&item.id), // Decode id from Base64
+                    response: system_err,           // Directly use the system_err string
                     status: BatchItemResponseStatus::ERROR,
                     block_height: response.batch.block_height,
                 }
             } else if let Some(encoded_response) = item.response.response {
                 BatchQueryParsedResponseItem {
-                    id: decode_b64_to_string(&item.id), // Decode id from Base64
+                    id: decode_b64_to_id(&item.id), // Decode id from Base64
                     response: decode_b64_to_string(&encoded_response), // Decode response from Base64 and JSON
                     status: BatchItemResponseStatus::SUCCESS,
                     block_height: response.batch.block_height,
@@ -0,0 +1,135 @@
+//! A fake batch router for the tests of this crate.
+
+use crate::{types::*, BatchQuerier};
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use cosmwasm_std::{Addr, ContractInfo};
+use serde_json::{json, Value};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Returns `Pending` once, so other futures get polled in the meantime.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// A router that answers every query with the query itself.
+#[derive(Default)]
+pub(crate) struct FakeRouter {
+    /// How many times a batch fails before it is answered, by the id of its first query.
+    pub(crate) failures: RefCell<HashMap<String, u32>>,
+    pub(crate) calls: Cell<usize>,
+    pub(crate) in_flight: Cell<usize>,
+    pub(crate) max_in_flight: Cell<usize>,
+    pub(crate) batch_sizes: RefCell<Vec<usize>>,
+}
+
+impl FakeRouter {
+    pub(crate) fn failing(first_id: &str, times: u32) -> Self {
+        let router = Self::default();
+        router
+            .failures
+            .borrow_mut()
+            .insert(first_id.to_string(), times);
+        router
+    }
+}
+
+impl BatchQuerier for FakeRouter {
+    type Error = String;
+
+    async fn query_batch(
+        &self,
+        _router: &ContractInfo,
+        batch: &BatchQuery,
+    ) -> Result<BatchQueryResponse, String> {
+        let queries = &batch.batch.queries;
+        let first_id: String =
+            serde_json::from_slice(&BASE64_STANDARD.decode(&queries[0].id).unwrap()).unwrap();
+
+        self.calls.set(self.calls.get() + 1);
+        self.batch_sizes.borrow_mut().push(queries.len());
+        self.in_flight.set(self.in_flight.get() + 1);
+        self.max_in_flight
+            .set(self.max_in_flight.get().max(self.in_flight.get()));
+
+        // earlier batches take longer, so they finish last
+        let delay = 20 - first_id.parse::<usize>().unwrap_or(0).min(19);
+        for _ in 0..delay {
+            YieldNow(false).await;
+        }
+
+        self.in_flight.set(self.in_flight.get() - 1);
+
+        if let Some(remaining) = self.failures.borrow_mut().get_mut(&first_id) {
+            if *remaining > 0 {
+                *remaining -= 1;
+                return Err("router unavailable".to_string());
+            }
+        }
+
+        Ok(BatchQueryResponse {
+            batch: BatchResponse {
+                block_height: 100,
+                responses: queries
+                    .iter()
+                    .map(|query| BatchQueryResponseItem {
+                        id: query.id.clone(),
+                        contract: query.contract.clone(),
+                        response: Response {
+                            response: Some(query.query.clone()),
+                            system_err: None,
+                        },
+                    })
+                    .collect(),
+            },
+        })
+    }
+
+    async fn query_contract(
+        &self,
+        _contract: &ContractInfo,
+        query: &Value,
+    ) -> Result<String, String> {
+        self.calls.set(self.calls.get() + 1);
+
+        if query.get("fail").is_some() {
+            Err("query failed".to_string())
+        } else {
+            Ok(query.to_string())
+        }
+    }
+}
+
+pub(crate) fn contract() -> ContractInfo {
+    ContractInfo {
+        address: Addr::unchecked("secret1router"),
+        code_hash: "code_hash".to_string(),
+    }
+}
+
+pub(crate) fn queries(count: usize) -> Vec<BatchQueryParams<Value>> {
+    (0..count)
+        .map(|index| BatchQueryParams {
+            id: index.to_string(),
+            contract: contract(),
+            query_msg: json!({ "get_bin": { "id": index } }),
+        })
+        .collect()
+}
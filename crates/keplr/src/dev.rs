@@ -0,0 +1,334 @@
+//! An in-browser wallet for local development against `secretdev-1`.
+//!
+//! Keys are derived from a mnemonic and everything is signed locally, so end-to-end testing
+//! against LocalSecret doesn't need an extension with custom chain info and imported keys.
+//! Never use this with a mnemonic that holds real funds.
+
+use super::{
+    signers::serialize_std_sign_doc, EnigmaUtils, Error, KeplrOfflineSigner,
+    KeplrOfflineSignerOnlyAmino, KeplrSigner, Key, Wallet,
+};
+use async_trait::async_trait;
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use bip32::{DerivationPath, Language, Mnemonic};
+use rsecret::wallet::{
+    wallet_amino::{AccountData, AminoSignResponse, StdSignDoc},
+    wallet_proto::DirectSignResponse,
+    Error as SignerError, Signer,
+};
+use secretrs::{crypto::secp256k1::SigningKey, tx::SignMode};
+use send_wrapper::SendWrapper;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use std::{cell::RefCell, fmt, rc::Rc};
+use tracing::debug;
+use web_sys::wasm_bindgen::JsValue;
+
+/// The only chain the dev wallet will connect to.
+pub const DEV_CHAIN_ID: &str = "secretdev-1";
+
+/// Secret Network's BIP-44 coin type is 529.
+const HD_PATH: &str = "m/44'/529'/0'/0/0";
+
+/// The genesis accounts of LocalSecret.
+pub const LOCALSECRET_ACCOUNTS: [(&str, &str); 4] = [
+    (
+        "a",
+        "grant rice replace explain federal release fix clever romance raise often wild taxi quarter soccer fiber love must tape steak together observe swap guitar",
+    ),
+    (
+        "b",
+        "jelly shadow frog dirt dragon use armed praise universe win jungle close inmate rain oil canvas beauty pioneer chef soccer icon dizzy thunder meadow",
+    ),
+    (
+        "c",
+        "chair love bleak wonder skirt permit say assist aunt credit roast size obtain minute throw sand usual age smart exact enough room shadow charge",
+    ),
+    (
+        "d",
+        "word twist toast cloth movie predict advance crumble escape whale sail such angry muffin balcony keen move employ cook valve hurt glimpse breeze brick",
+    ),
+];
+
+thread_local! {
+    static ACCOUNT: RefCell<Option<DevAccount>> = const { RefCell::new(None) };
+}
+
+/// Keys derived from a mnemonic.
+struct DevAccount {
+    name: String,
+    signing_key: Rc<SigningKey>,
+    /// Seeds the transaction encryption key, so past transactions can still be decrypted after
+    /// a reload.
+    encryption_seed: [u8; 32],
+}
+
+impl DevAccount {
+    fn from_mnemonic(name: &str, phrase: &str) -> Result<Self, Error> {
+        let mnemonic = Mnemonic::new(phrase.trim(), Language::English).map_err(Error::generic)?;
+        let seed = mnemonic.to_seed("");
+        let path = HD_PATH.parse::<DerivationPath>().map_err(Error::generic)?;
+        let signing_key =
+            SigningKey::derive_from_path(seed.as_bytes(), &path).map_err(Error::generic)?;
+
+        let mut encryption_seed = [0u8; 32];
+        encryption_seed.copy_from_slice(&seed.as_bytes()[32..]);
+
+        Ok(Self {
+            name: name.to_string(),
+            signing_key: Rc::new(signing_key),
+            encryption_seed,
+        })
+    }
+}
+
+/// Switches the dev wallet to the account derived from `phrase`.
+pub fn use_mnemonic(name: &str, phrase: &str) -> Result<(), Error> {
+    let account = DevAccount::from_mnemonic(name, phrase)?;
+    debug!("using dev account {name}");
+    ACCOUNT.with(|current| *current.borrow_mut() = Some(account));
+
+    Ok(())
+}
+
+/// Returns the name of the dev account in use.
+pub fn account_name() -> String {
+    with_account(|account| account.name.clone()).unwrap_or_default()
+}
+
+/// Runs `f` with the current account, deriving LocalSecret account "a" on first use.
+fn with_account<T>(f: impl FnOnce(&DevAccount) -> T) -> Result<T, Error> {
+    ACCOUNT.with(|current| {
+        let mut current = current.borrow_mut();
+        if current.is_none() {
+            let (name, phrase) = LOCALSECRET_ACCOUNTS[0];
+            *current = Some(DevAccount::from_mnemonic(name, phrase)?);
+        }
+
+        Ok(f(current.as_ref().expect("dev account was just derived")))
+    })
+}
+
+fn check_chain_id(chain_id: &str) -> Result<(), Error> {
+    if chain_id == DEV_CHAIN_ID {
+        Ok(())
+    } else {
+        Err(Error::generic(format!(
+            "the dev wallet only works on {DEV_CHAIN_ID}, not {chain_id}"
+        )))
+    }
+}
+
+/// Signs direct and amino sign docs with a key held in memory.
+#[derive(Clone)]
+pub struct MnemonicSigner {
+    inner: SendWrapper<Rc<SigningKey>>,
+}
+
+impl fmt::Debug for MnemonicSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MnemonicSigner")
+            .field("address", &self.address().ok())
+            .finish()
+    }
+}
+
+impl MnemonicSigner {
+    fn address(&self) -> Result<String, Error> {
+        self.inner
+            .public_key()
+            .account_id("secret")
+            .map(|account_id| account_id.to_string())
+            .map_err(Error::generic)
+    }
+
+    fn check_signer(&self, signer_address: &str) -> Result<(), SignerError> {
+        let address = self.address().map_err(SignerError::custom)?;
+
+        if signer_address == address {
+            Ok(())
+        } else {
+            Err(SignerError::custom(Error::generic(format!(
+                "address {signer_address} not found in wallet"
+            ))))
+        }
+    }
+
+    /// Signs the SHA-256 hash of `bytes` and returns the signature in the `StdSignature` shape.
+    fn signature(&self, bytes: &[u8]) -> Result<serde_json::Value, SignerError> {
+        let signature = self
+            .inner
+            .sign(bytes)
+            .map_err(Error::generic)
+            .map_err(SignerError::custom)?;
+
+        Ok(json!({
+            "pub_key": {
+                "type": "tendermint/PubKeySecp256k1",
+                "value": BASE64_STANDARD.encode(self.inner.public_key().to_bytes()),
+            },
+            "signature": BASE64_STANDARD.encode(signature.to_bytes()),
+        }))
+    }
+}
+
+#[async_trait(?Send)]
+impl Signer for MnemonicSigner {
+    async fn get_accounts(&self) -> Result<Vec<AccountData>, SignerError> {
+        let account = json!({
+            "address": self.address().map_err(SignerError::custom)?,
+            "algo": "secp256k1",
+            "pubkey": self.inner.public_key().to_bytes(),
+        });
+
+        from_json(account).map(|account| vec![account])
+    }
+
+    async fn get_sign_mode(&self) -> Result<SignMode, SignerError> {
+        Ok(SignMode::Direct)
+    }
+
+    async fn sign_amino<T: Serialize + DeserializeOwned + Send + Sync>(
+        &self,
+        signer_address: &str,
+        sign_doc: StdSignDoc<T>,
+    ) -> Result<AminoSignResponse<T>, SignerError> {
+        self.check_signer(signer_address)?;
+
+        let signature = self.signature(&serialize_std_sign_doc(&sign_doc))?;
+
+        from_json(json!({
+            "signed": sign_doc,
+            "signature": signature,
+        }))
+    }
+
+    async fn sign_permit<T: Serialize + DeserializeOwned + Send + Sync>(
+        &self,
+        signer_address: &str,
+        sign_doc: StdSignDoc<T>,
+    ) -> Result<AminoSignResponse<T>, SignerError> {
+        // permits are amino sign docs that never get broadcast
+        self.sign_amino(signer_address, sign_doc).await
+    }
+
+    async fn sign_direct(
+        &self,
+        signer_address: &str,
+        sign_doc: secretrs::tx::SignDoc,
+    ) -> Result<DirectSignResponse, SignerError> {
+        self.check_signer(signer_address)?;
+
+        let bytes = sign_doc
+            .clone()
+            .into_bytes()
+            .map_err(Error::generic)
+            .map_err(SignerError::custom)?;
+        let signature = self.signature(&bytes)?;
+
+        Ok(DirectSignResponse {
+            signed: sign_doc.into(),
+            signature: from_json(signature)?,
+        })
+    }
+}
+
+fn from_json<T: DeserializeOwned>(value: serde_json::Value) -> Result<T, SignerError> {
+    serde_json::from_value(value)
+        .map_err(|error| Error::Serialization(error.to_string()))
+        .map_err(SignerError::custom)
+}
+
+/// A wallet backed by [`MnemonicSigner`], only available on [`DEV_CHAIN_ID`].
+pub struct DevWallet;
+
+pub static DEV: DevWallet = DevWallet;
+
+#[async_trait(?Send)]
+impl Wallet for DevWallet {
+    fn name(&self) -> &'static str {
+        "Dev Wallet"
+    }
+
+    fn provider(&self) -> Result<JsValue, Error> {
+        Err(Error::generic("the dev wallet has no injected provider"))
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    async fn enable(&self, chain_ids: Vec<String>) -> Result<(), Error> {
+        chain_ids
+            .iter()
+            .try_for_each(|chain_id| check_chain_id(chain_id))?;
+        with_account(|_| ())
+    }
+
+    async fn get_key(&self, chain_id: &str) -> Result<Key, Error> {
+        check_chain_id(chain_id)?;
+
+        with_account(|account| {
+            let public_key = account.signing_key.public_key();
+            let account_id = public_key.account_id("secret").map_err(Error::generic)?;
+
+            Ok(Key {
+                name: format!("localsecret {}", account.name),
+                algo: "secp256k1".to_string(),
+                pub_key: public_key.to_bytes(),
+                address: account_id.to_bytes(),
+                bech32_address: account_id.to_string(),
+                ..Default::default()
+            })
+        })?
+    }
+
+    fn get_offline_signer(&self, _chain_id: &str) -> Result<KeplrOfflineSigner, Error> {
+        Err(Error::generic("the dev wallet signs with get_signer"))
+    }
+
+    fn get_offline_signer_only_amino(
+        &self,
+        _chain_id: &str,
+    ) -> Result<KeplrOfflineSignerOnlyAmino, Error> {
+        Err(Error::generic("the dev wallet signs with get_signer"))
+    }
+
+    fn get_signer(&self, chain_id: &str, _key: &Key) -> Result<KeplrSigner, Error> {
+        check_chain_id(chain_id)?;
+
+        with_account(|account| {
+            KeplrSigner::Mnemonic(MnemonicSigner {
+                inner: SendWrapper::new(account.signing_key.clone()),
+            })
+        })
+    }
+
+    fn get_enigma_utils(&self, chain_id: &str) -> Result<EnigmaUtils, Error> {
+        check_chain_id(chain_id)?;
+
+        with_account(|account| EnigmaUtils::local(account.encryption_seed, chain_id))?
+    }
+
+    async fn suggest_token(
+        &self,
+        _chain_id: &str,
+        _contract_address: &str,
+        _viewing_key: Option<&str>,
+    ) -> Result<(), Error> {
+        // viewing keys are already kept in local storage by the app
+        Ok(())
+    }
+
+    async fn get_secret_20_viewing_key(
+        &self,
+        _chain_id: &str,
+        contract_address: &str,
+    ) -> Result<String, Error> {
+        Err(Error::generic(format!(
+            "the dev wallet has no viewing key for {contract_address}"
+        )))
+    }
+
+    fn disable(&self, _chain_id: &str) {}
+}
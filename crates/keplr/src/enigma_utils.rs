@@ -1,29 +1,51 @@
 use super::Error;
 use async_trait::async_trait;
-use secretrs::utils::{encryption::SecretUtils, Error as EncryptionError};
+use secretrs::utils::{
+    encryption::{EnigmaUtils as LocalEnigmaUtils, SecretUtils},
+    Error as EncryptionError,
+};
 use send_wrapper::SendWrapper;
 use serde::Serialize;
+use std::{fmt, sync::Arc};
 use tracing::{debug, trace};
 use web_sys::js_sys::Uint8Array;
 
-#[derive(Debug)]
-pub struct EnigmaUtils {
-    inner: SendWrapper<keplr_sys::EnigmaUtils>,
+/// Transaction encryption, done by the wallet extension or, for the dev wallet, in Rust.
+pub enum EnigmaUtils {
+    Extension(SendWrapper<keplr_sys::EnigmaUtils>),
+    Local(Arc<LocalEnigmaUtils>),
+}
+
+impl EnigmaUtils {
+    /// Encryption utils seeded with `seed`, for wallets without an extension.
+    pub fn local(seed: [u8; 32], chain_id: &str) -> Result<Self, Error> {
+        LocalEnigmaUtils::new(Some(seed), chain_id)
+            .map(|local| Self::Local(Arc::new(local)))
+            .map_err(Error::generic)
+    }
+}
+
+impl fmt::Debug for EnigmaUtils {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Extension(inner) => f.debug_tuple("Extension").field(&**inner).finish(),
+            Self::Local(_) => f.debug_tuple("Local").finish(),
+        }
+    }
 }
 
 impl Clone for EnigmaUtils {
     fn clone(&self) -> Self {
-        EnigmaUtils {
-            inner: SendWrapper::new(self.inner.clone().into()),
+        match self {
+            Self::Extension(inner) => Self::Extension(SendWrapper::new((**inner).clone())),
+            Self::Local(local) => Self::Local(Arc::clone(local)),
         }
     }
 }
 
 impl From<keplr_sys::EnigmaUtils> for EnigmaUtils {
     fn from(value: keplr_sys::EnigmaUtils) -> Self {
-        Self {
-            inner: SendWrapper::new(value),
-        }
+        Self::Extension(SendWrapper::new(value))
     }
 }
 
@@ -34,10 +56,14 @@ impl SecretUtils for EnigmaUtils {
         contract_code_hash: &str,
         msg: &M,
     ) -> Result<Vec<u8>, EncryptionError> {
+        let inner = match self {
+            Self::Extension(inner) => inner,
+            Self::Local(local) => return local.encrypt(contract_code_hash, msg).await,
+        };
+
         let msg = serde_wasm_bindgen::to_value(msg).expect("wasm_bindgen error");
 
-        let result = self
-            .inner
+        let result = inner
             .encrypt(contract_code_hash.to_string(), msg)
             .await
             .map_err(Error::js)
@@ -53,9 +79,13 @@ impl SecretUtils for EnigmaUtils {
         nonce: &[u8; 32],
         ciphertext: &[u8],
     ) -> Result<Vec<u8>, EncryptionError> {
+        let inner = match self {
+            Self::Extension(inner) => inner,
+            Self::Local(local) => return local.decrypt(nonce, ciphertext).await,
+        };
+
         // NOTE: the order of inputs is reversed in Keplr's decrypt method.
-        let result = self
-            .inner
+        let result = inner
             .decrypt(ciphertext, nonce)
             .await
             .map_err(Error::js)
@@ -67,7 +97,12 @@ impl SecretUtils for EnigmaUtils {
     }
 
     async fn get_pubkey(&self) -> [u8; 32] {
-        let key = self.inner.get_pubkey().await;
+        let inner = match self {
+            Self::Extension(inner) => inner,
+            Self::Local(local) => return local.get_pubkey().await,
+        };
+
+        let key = inner.get_pubkey().await;
         trace!("{:?}", key);
         let key = Uint8Array::try_from(key).unwrap();
         let mut array = [0u8; 32];
@@ -77,7 +112,12 @@ impl SecretUtils for EnigmaUtils {
     }
 
     async fn get_tx_encryption_key(&self, nonce: &[u8; 32]) -> [u8; 32] {
-        let key = self.inner.get_tx_encryption_key(nonce).await;
+        let inner = match self {
+            Self::Extension(inner) => inner,
+            Self::Local(local) => return local.get_tx_encryption_key(nonce).await,
+        };
+
+        let key = inner.get_tx_encryption_key(nonce).await;
         trace!("{:?}", key);
         let key = Uint8Array::try_from(key).unwrap();
         let mut array = [0u8; 32];
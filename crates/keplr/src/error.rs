@@ -6,6 +6,9 @@ pub enum Error {
     #[error("Keplr is unavailable!")]
     KeplrUnavailable,
 
+    #[error("{0} is unavailable!")]
+    WalletUnavailable(String),
+
     #[error("{0}")]
     Js(String),
 
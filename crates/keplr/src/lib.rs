@@ -4,15 +4,21 @@ use serde::{Deserialize, Serialize};
 use tracing::{debug, trace};
 use web_sys::{console, js_sys, wasm_bindgen::JsValue};
 
+#[cfg(feature = "dev")]
+pub mod dev;
 mod enigma_utils;
 mod error;
 pub mod experimental;
+#[cfg(feature = "mock")]
+pub mod mock;
 mod signers;
 pub mod tokens;
+pub mod wallet;
 
 pub use enigma_utils::EnigmaUtils;
 pub use error::Error;
-pub use signers::{KeplrOfflineSigner, KeplrOfflineSignerOnlyAmino};
+pub use signers::{KeplrOfflineSigner, KeplrOfflineSignerOnlyAmino, KeplrSigner};
+pub use wallet::{Wallet, WalletKind};
 
 pub struct Keplr {}
 
@@ -58,15 +64,12 @@ impl Keplr {
         keplr_sys::get_offline_signer_only_amino(chain_id).into()
     }
 
-    // TODO: Not sure how to do this. `match` arms have incompatible types
-    // pub async fn get_offline_signer_auto<T: Signer>(chain_id: &str) -> Result<T, Error> {
-    //     let key = Self::get_key(chain_id).await?;
-    //     let signer = match key.is_nano_ledger {
-    //         true => Self::get_offline_signer_only_amino(chain_id),
-    //         false => Self::get_offline_signer(chain_id),
-    //     };
-    //     Ok(signer)
-    // }
+    /// Returns the amino signer for Ledger and Keystone accounts, and the direct signer
+    /// otherwise.
+    pub async fn get_offline_signer_auto(chain_id: &str) -> Result<KeplrSigner, Error> {
+        let key = Self::get_key(chain_id).await?;
+        Wallet::get_signer(&Keplr {}, chain_id, &key)
+    }
 
     pub fn get_enigma_utils(chain_id: &str) -> EnigmaUtils {
         keplr_sys::get_enigma_utils(chain_id).into()
@@ -92,6 +95,23 @@ impl Keplr {
             .map_err(Error::js)
     }
 
+    /// Signs arbitrary `data` as an ADR-36 message, for proving ownership of an address
+    /// off-chain.
+    pub async fn sign_arbitrary(
+        chain_id: &str,
+        signer: &str,
+        data: &str,
+    ) -> Result<StdSignature, Error> {
+        let response = wallet::call_async(
+            &wallet::injected("keplr", "Keplr")?,
+            "signArbitrary",
+            &[chain_id.into(), signer.into(), data.into()],
+        )
+        .await?;
+
+        Ok(serde_wasm_bindgen::from_value(response)?)
+    }
+
     pub fn disable(chain_id: &str) {
         keplr_sys::disable(chain_id)
     }
@@ -138,3 +158,18 @@ impl std::fmt::Debug for Key {
             .finish()
     }
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StdSignature {
+    pub pub_key: PubKey,
+    /// Base64 encoded signature.
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PubKey {
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// Base64 encoded public key.
+    pub value: String,
+}
@@ -0,0 +1,289 @@
+//! A fake Keplr provider for headless tests.
+//!
+//! [`MockKeplr::install`] puts an object on `window.keplr` that answers the calls the app
+//! makes, so components can be rendered under `wasm-bindgen-test` in a headless browser
+//! without the extension:
+//!
+//! ```ignore
+//! let keplr = MockKeplr::new()
+//!     .with_key(key)
+//!     .with_viewing_key("secret1...", "api_key_...")
+//!     .failing("signDirect", "Request rejected");
+//! keplr.install()?;
+//!
+//! // render and interact ...
+//!
+//! assert!(keplr.calls().contains(&"signDirect".to_string()));
+//! keplr.uninstall();
+//! ```
+//!
+//! Install the mock before anything reads `window.keplr`, because keplr-sys caches the object
+//! on first access. The same mock can stand in for Leap or Fina with [`MockKeplr::install_as`].
+
+use super::{Error, Key};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use web_sys::{
+    js_sys::{self, Object, Promise, Reflect, Uint8Array},
+    wasm_bindgen::{self, closure::Closure, JsValue},
+};
+
+#[derive(Default)]
+struct MockState {
+    key: Key,
+    viewing_keys: HashMap<String, String>,
+    responses: HashMap<String, Value>,
+    failures: HashMap<String, String>,
+    calls: Vec<String>,
+}
+
+/// A configurable stand-in for the Keplr extension.
+///
+/// Clones share their state, so a test can keep one to reconfigure the mock or inspect its
+/// calls after installing it.
+#[derive(Clone, Default)]
+pub struct MockKeplr {
+    state: Rc<RefCell<MockState>>,
+    property: Rc<RefCell<Option<String>>>,
+}
+
+impl MockKeplr {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The key returned by `getKey` and by the offline signers' `getAccounts`.
+    pub fn with_key(self, key: Key) -> Self {
+        self.state.borrow_mut().key = key;
+        self
+    }
+
+    /// A viewing key returned by `getSecret20ViewingKey` for `contract_address`.
+    pub fn with_viewing_key(self, contract_address: &str, viewing_key: &str) -> Self {
+        self.state
+            .borrow_mut()
+            .viewing_keys
+            .insert(contract_address.to_string(), viewing_key.to_string());
+        self
+    }
+
+    /// Makes `method` resolve with `response` instead of the default. Applies to the provider's
+    /// methods and to the offline signers' `signAmino` and `signDirect`.
+    pub fn with_response(self, method: &str, response: impl Serialize) -> Self {
+        let response = serde_json::to_value(response).expect("mock response is serializable");
+        self.state
+            .borrow_mut()
+            .responses
+            .insert(method.to_string(), response);
+        self
+    }
+
+    /// Makes `method` fail with `message`, the way Keplr does when a request is rejected.
+    pub fn failing(self, method: &str, message: &str) -> Self {
+        self.state
+            .borrow_mut()
+            .failures
+            .insert(method.to_string(), message.to_string());
+        self
+    }
+
+    /// Stops `method` from failing.
+    pub fn succeeding(self, method: &str) -> Self {
+        self.state.borrow_mut().failures.remove(method);
+        self
+    }
+
+    /// The names of the methods called so far, in order.
+    pub fn calls(&self) -> Vec<String> {
+        self.state.borrow().calls.clone()
+    }
+
+    pub fn clear_calls(&self) {
+        self.state.borrow_mut().calls.clear();
+    }
+
+    /// Installs the mock as `window.keplr`.
+    pub fn install(&self) -> Result<(), Error> {
+        self.install_as("keplr")
+    }
+
+    /// Installs the mock as `window[property]`, e.g. `"leap"`.
+    pub fn install_as(&self, property: &str) -> Result<(), Error> {
+        let window = web_sys::window().ok_or(Error::generic("no window"))?;
+        Reflect::set(&window, &property.into(), &self.provider())?;
+        *self.property.borrow_mut() = Some(property.to_string());
+
+        Ok(())
+    }
+
+    /// Removes the mock from `window`.
+    pub fn uninstall(&self) {
+        if let (Some(window), Some(property)) = (web_sys::window(), self.property.take()) {
+            _ = Reflect::delete_property(&window, &property.into());
+        }
+    }
+
+    fn provider(&self) -> Object {
+        let provider = Object::new();
+
+        self.method(&provider, "enable", true, |_, _| Ok(JsValue::UNDEFINED));
+        self.method(&provider, "disable", false, |_, _| Ok(JsValue::UNDEFINED));
+        self.method(&provider, "getKey", true, |state, _| {
+            Ok(serde_wasm_bindgen::to_value(&state.key)?)
+        });
+        self.method(&provider, "suggestToken", true, |_, _| {
+            Ok(JsValue::UNDEFINED)
+        });
+        self.method(
+            &provider,
+            "getSecret20ViewingKey",
+            true,
+            |state, [_, contract, ..]| {
+                let contract = contract.as_string().unwrap_or_default();
+                state
+                    .viewing_keys
+                    .get(&contract)
+                    .map(JsValue::from)
+                    .ok_or_else(|| Error::generic("There is no matched secret20"))
+            },
+        );
+        self.method(&provider, "signAmino", true, |_, [_, _, sign_doc, _]| {
+            sign_response(sign_doc)
+        });
+        self.method(&provider, "signArbitrary", true, |_, _| {
+            to_js(&mock_signature())
+        });
+
+        let this = self.clone();
+        self.method(
+            &provider,
+            "getOfflineSigner",
+            false,
+            move |_, [chain_id, ..]| Ok(this.offline_signer(chain_id).into()),
+        );
+        let this = self.clone();
+        self.method(
+            &provider,
+            "getOfflineSignerOnlyAmino",
+            false,
+            move |_, [chain_id, ..]| Ok(this.offline_signer(chain_id).into()),
+        );
+        let this = self.clone();
+        self.method(&provider, "getEnigmaUtils", false, move |_, _| {
+            Ok(this.enigma_utils().into())
+        });
+
+        provider
+    }
+
+    fn offline_signer(&self, chain_id: JsValue) -> Object {
+        let signer = Object::new();
+        _ = Reflect::set(&signer, &"chainId".into(), &chain_id);
+
+        self.method(&signer, "getAccounts", true, |state, _| {
+            let account = json!({
+                "address": state.key.bech32_address,
+                "algo": state.key.algo,
+                "pubkey": state.key.pub_key,
+            });
+            Ok(js_sys::Array::of1(&to_js(&account)?).into())
+        });
+        self.method(&signer, "signAmino", true, |_, [_, sign_doc, ..]| {
+            sign_response(sign_doc)
+        });
+        self.method(&signer, "signDirect", true, |_, [_, sign_doc, ..]| {
+            sign_response(sign_doc)
+        });
+
+        signer
+    }
+
+    /// Encryption is the identity, so tests can read what would have been sent.
+    fn enigma_utils(&self) -> Object {
+        let utils = Object::new();
+
+        self.method(&utils, "encrypt", true, |_, [_, msg, ..]| {
+            let msg = js_sys::JSON::stringify(&msg)?
+                .as_string()
+                .unwrap_or_default();
+            Ok(Uint8Array::from(msg.as_bytes()).into())
+        });
+        self.method(&utils, "decrypt", true, |_, [ciphertext, ..]| {
+            Ok(ciphertext)
+        });
+        self.method(&utils, "getPubkey", true, |_, _| {
+            Ok(Uint8Array::from(&[0u8; 32][..]).into())
+        });
+        self.method(&utils, "getTxEncryptionKey", true, |_, _| {
+            Ok(Uint8Array::from(&[0u8; 32][..]).into())
+        });
+
+        utils
+    }
+
+    /// Defines `name` on `target`. Async methods return promises, like the real ones.
+    ///
+    /// Every call is recorded. Configured failures and responses take precedence over
+    /// `handler`.
+    fn method(
+        &self,
+        target: &Object,
+        name: &'static str,
+        is_async: bool,
+        handler: impl Fn(&MockState, [JsValue; 4]) -> Result<JsValue, Error> + 'static,
+    ) {
+        let state = Rc::clone(&self.state);
+
+        let function = Closure::<dyn Fn(JsValue, JsValue, JsValue, JsValue) -> JsValue>::new(
+            move |a, b, c, d| {
+                state.borrow_mut().calls.push(name.to_string());
+
+                let result = {
+                    let state = state.borrow();
+                    if let Some(message) = state.failures.get(name) {
+                        Err(Error::generic(message))
+                    } else if let Some(response) = state.responses.get(name) {
+                        to_js(response)
+                    } else {
+                        handler(&state, [a, b, c, d])
+                    }
+                };
+
+                match (result, is_async) {
+                    (Ok(value), true) => Promise::resolve(&value).into(),
+                    (Err(error), true) => {
+                        Promise::reject(&js_sys::Error::new(&error.to_string())).into()
+                    }
+                    (Ok(value), false) => value,
+                    (Err(error), false) => wasm_bindgen::throw_str(&error.to_string()),
+                }
+            },
+        );
+
+        _ = Reflect::set(target, &name.into(), &function.into_js_value());
+    }
+}
+
+/// Echoes `sign_doc` back as signed, with a placeholder signature.
+fn sign_response(sign_doc: JsValue) -> Result<JsValue, Error> {
+    let response = Object::new();
+    Reflect::set(&response, &"signed".into(), &sign_doc)?;
+    Reflect::set(&response, &"signature".into(), &to_js(&mock_signature())?)?;
+
+    Ok(response.into())
+}
+
+fn mock_signature() -> Value {
+    json!({
+        "pub_key": {
+            "type": "tendermint/PubKeySecp256k1",
+            "value": "",
+        },
+        "signature": "",
+    })
+}
+
+fn to_js(value: &impl Serialize) -> Result<JsValue, Error> {
+    Ok(value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
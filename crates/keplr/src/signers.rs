@@ -1,4 +1,9 @@
-use super::Error;
+#[cfg(feature = "dev")]
+use super::dev::MnemonicSigner;
+use super::{
+    wallet::{self, call_async},
+    Error,
+};
 use async_trait::async_trait;
 // TODO: any way to avoid depending on rsecret crate?
 use rsecret::wallet::{
@@ -12,7 +17,7 @@ use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{Map, Value};
 use std::rc::Rc;
 use tracing::{debug, trace};
-use web_sys::js_sys;
+use web_sys::{js_sys, wasm_bindgen::JsValue};
 
 // TODO: I think these things should belong in rsecret instead?
 
@@ -65,7 +70,11 @@ impl Signer for KeplrOfflineSigner {
         signer_address: &str,
         sign_doc: StdSignDoc<T>,
     ) -> Result<AminoSignResponse<T>, SignerError> {
-        todo!()
+        let sign_doc = to_js(&sign_doc)?;
+        let response =
+            call_async(&self.inner, "signAmino", &[signer_address.into(), sign_doc]).await?;
+
+        from_js(response)
     }
 
     async fn sign_permit<T: Serialize + DeserializeOwned + Send + Sync>(
@@ -73,7 +82,7 @@ impl Signer for KeplrOfflineSigner {
         signer_address: &str,
         sign_doc: StdSignDoc<T>,
     ) -> Result<AminoSignResponse<T>, SignerError> {
-        todo!()
+        sign_permit(&self.inner, signer_address, sign_doc).await
     }
 
     async fn sign_direct(
@@ -82,22 +91,20 @@ impl Signer for KeplrOfflineSigner {
         sign_doc: secretrs::tx::SignDoc,
     ) -> Result<DirectSignResponse, SignerError> {
         let sign_doc: SignDoc = sign_doc.into();
-        let sign_doc = serde_wasm_bindgen::to_value(&sign_doc).expect("serde_wasm_bindgen problem");
+        let sign_doc = to_js(&sign_doc)?;
 
-        let js_result = self
+        let response = self
             .inner
             .sign_direct(signer_address.into(), sign_doc)
             .await
-            .map(|js_value| {
-                serde_wasm_bindgen::from_value::<DirectSignResponse>(js_value)
-                    .expect("Problem deserializing DirectSignResponse")
-            })
             .map_err(Error::js)
-            .map_err(SignerError::custom);
+            .map_err(SignerError::custom)?;
+
+        let response = from_js::<DirectSignResponse>(response);
 
-        debug!("{:?}", js_result);
+        debug!("{:?}", response);
 
-        js_result
+        response
     }
 }
 
@@ -147,20 +154,17 @@ impl Signer for KeplrOfflineSignerOnlyAmino {
         signer_address: &str,
         sign_doc: StdSignDoc<T>,
     ) -> Result<AminoSignResponse<T>, SignerError> {
-        let sign_doc = serde_wasm_bindgen::to_value(&sign_doc).expect("serde_wasm_bindgen problem");
-        debug!("{:#?}", sign_doc);
-        let js_result = self
+        let sign_doc = to_js(&sign_doc)?;
+        trace!("{:#?}", sign_doc);
+
+        let response = self
             .inner
             .sign_amino(signer_address.into(), sign_doc)
             .await
-            .map(|js_value| {
-                serde_wasm_bindgen::from_value::<AminoSignResponse<T>>(js_value)
-                    .expect("Problem deserializing AminoSignResponse")
-            })
             .map_err(Error::js)
-            .map_err(SignerError::custom);
+            .map_err(SignerError::custom)?;
 
-        js_result
+        from_js(response)
     }
 
     async fn sign_permit<T: Serialize + DeserializeOwned + Send + Sync>(
@@ -168,7 +172,7 @@ impl Signer for KeplrOfflineSignerOnlyAmino {
         signer_address: &str,
         sign_doc: StdSignDoc<T>,
     ) -> Result<AminoSignResponse<T>, SignerError> {
-        todo!()
+        sign_permit(&self.inner, signer_address, sign_doc).await
     }
 
     async fn sign_direct(
@@ -176,8 +180,136 @@ impl Signer for KeplrOfflineSignerOnlyAmino {
         signer_address: &str,
         sign_doc: secretrs::tx::SignDoc,
     ) -> Result<DirectSignResponse, SignerError> {
-        unimplemented!()
+        Err(SignerError::custom(Error::generic(
+            "this signer only supports amino signing",
+        )))
+    }
+}
+
+/// Either of the Keplr offline signers, chosen by what the account can sign.
+///
+/// Ledger and Keystone accounts can't sign direct (protobuf) sign docs, so they get the
+/// amino-only signer. Everything else signs direct. See [`Wallet::get_signer`].
+///
+/// The dev wallet signs locally instead, see `MnemonicSigner` (only with the `dev` feature).
+///
+/// [`Wallet::get_signer`]: crate::Wallet::get_signer
+#[derive(Debug, Clone)]
+pub enum KeplrSigner {
+    Direct(KeplrOfflineSigner),
+    Amino(KeplrOfflineSignerOnlyAmino),
+    #[cfg(feature = "dev")]
+    Mnemonic(MnemonicSigner),
+}
+
+#[async_trait(?Send)]
+impl Signer for KeplrSigner {
+    async fn get_accounts(&self) -> Result<Vec<AccountData>, SignerError> {
+        match self {
+            Self::Direct(signer) => signer.get_accounts().await,
+            Self::Amino(signer) => signer.get_accounts().await,
+            #[cfg(feature = "dev")]
+            Self::Mnemonic(signer) => signer.get_accounts().await,
+        }
+    }
+
+    async fn get_sign_mode(&self) -> Result<SignMode, SignerError> {
+        match self {
+            Self::Direct(signer) => signer.get_sign_mode().await,
+            Self::Amino(signer) => signer.get_sign_mode().await,
+            #[cfg(feature = "dev")]
+            Self::Mnemonic(signer) => signer.get_sign_mode().await,
+        }
+    }
+
+    async fn sign_amino<T: Serialize + DeserializeOwned + Send + Sync>(
+        &self,
+        signer_address: &str,
+        sign_doc: StdSignDoc<T>,
+    ) -> Result<AminoSignResponse<T>, SignerError> {
+        match self {
+            Self::Direct(signer) => signer.sign_amino(signer_address, sign_doc).await,
+            Self::Amino(signer) => signer.sign_amino(signer_address, sign_doc).await,
+            #[cfg(feature = "dev")]
+            Self::Mnemonic(signer) => signer.sign_amino(signer_address, sign_doc).await,
+        }
     }
+
+    async fn sign_permit<T: Serialize + DeserializeOwned + Send + Sync>(
+        &self,
+        signer_address: &str,
+        sign_doc: StdSignDoc<T>,
+    ) -> Result<AminoSignResponse<T>, SignerError> {
+        match self {
+            Self::Direct(signer) => signer.sign_permit(signer_address, sign_doc).await,
+            Self::Amino(signer) => signer.sign_permit(signer_address, sign_doc).await,
+            #[cfg(feature = "dev")]
+            Self::Mnemonic(signer) => signer.sign_permit(signer_address, sign_doc).await,
+        }
+    }
+
+    async fn sign_direct(
+        &self,
+        signer_address: &str,
+        sign_doc: secretrs::tx::SignDoc,
+    ) -> Result<DirectSignResponse, SignerError> {
+        match self {
+            Self::Direct(signer) => signer.sign_direct(signer_address, sign_doc).await,
+            Self::Amino(signer) => signer.sign_direct(signer_address, sign_doc).await,
+            #[cfg(feature = "dev")]
+            Self::Mnemonic(signer) => signer.sign_direct(signer_address, sign_doc).await,
+        }
+    }
+}
+
+/// Signs a SNIP-24 permit through the active wallet's `signAmino`, with the options Keplr needs
+/// to show it as a permit rather than a transaction. Both offline signers know their chain id.
+async fn sign_permit<T: Serialize + DeserializeOwned>(
+    offline_signer: &JsValue,
+    signer_address: &str,
+    sign_doc: StdSignDoc<T>,
+) -> Result<AminoSignResponse<T>, SignerError> {
+    let chain_id = js_sys::Reflect::get(offline_signer, &"chainId".into())
+        .ok()
+        .and_then(|chain_id| chain_id.as_string())
+        .ok_or_else(|| SignerError::custom(Error::generic("offline signer has no chain id")))?;
+
+    let sign_options = to_js(&serde_json::json!({
+        "preferNoSetFee": true,
+        "preferNoSetMemo": true,
+        "disableBalanceCheck": true,
+    }))?;
+
+    let provider = wallet::active().provider().map_err(SignerError::custom)?;
+
+    let response = call_async(
+        &provider,
+        "signAmino",
+        &[
+            chain_id.into(),
+            signer_address.into(),
+            to_js(&sign_doc)?,
+            sign_options,
+        ],
+    )
+    .await
+    .map_err(SignerError::custom)?;
+
+    from_js(response)
+}
+
+/// Converts `value` into a plain JS object, with maps as objects rather than `Map`s.
+pub(crate) fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, SignerError> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(Error::from)
+        .map_err(SignerError::custom)
+}
+
+pub(crate) fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, SignerError> {
+    serde_wasm_bindgen::from_value(value)
+        .map_err(Error::from)
+        .map_err(SignerError::custom)
 }
 
 /// Sorts a JSON object by its keys recursively.
@@ -201,7 +333,7 @@ fn json_sorted_stringify(value: &Value) -> String {
 }
 
 /// Serializes a `StdSignDoc` object to a sorted and UTF-8 encoded JSON string
-fn serialize_std_sign_doc<T: Serialize>(sign_doc: &StdSignDoc<T>) -> Vec<u8> {
+pub(crate) fn serialize_std_sign_doc<T: Serialize>(sign_doc: &StdSignDoc<T>) -> Vec<u8> {
     let value = serde_json::to_value(sign_doc).unwrap();
     json_sorted_stringify(&value).as_bytes().to_vec()
 }
@@ -0,0 +1,359 @@
+//! Wallets that inject a Keplr-compatible API into the page.
+
+#[cfg(feature = "dev")]
+use super::dev::{DEV, DEV_CHAIN_ID};
+use super::{
+    EnigmaUtils, Error, Keplr, KeplrOfflineSigner, KeplrOfflineSignerOnlyAmino, KeplrSigner, Key,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{cell::Cell, fmt, str::FromStr};
+use tracing::debug;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    js_sys,
+    wasm_bindgen::{JsCast, JsValue},
+};
+
+#[async_trait(?Send)]
+pub trait Wallet: Sync {
+    /// The name shown to users.
+    fn name(&self) -> &'static str;
+
+    /// The object the extension injects into `window`.
+    fn provider(&self) -> Result<JsValue, Error>;
+
+    fn is_available(&self) -> bool {
+        self.provider().is_ok()
+    }
+
+    async fn enable(&self, chain_ids: Vec<String>) -> Result<(), Error>;
+    async fn get_key(&self, chain_id: &str) -> Result<Key, Error>;
+    fn get_offline_signer(&self, chain_id: &str) -> Result<KeplrOfflineSigner, Error>;
+    fn get_offline_signer_only_amino(
+        &self,
+        chain_id: &str,
+    ) -> Result<KeplrOfflineSignerOnlyAmino, Error>;
+    fn get_enigma_utils(&self, chain_id: &str) -> Result<EnigmaUtils, Error>;
+    async fn suggest_token(
+        &self,
+        chain_id: &str,
+        contract_address: &str,
+        viewing_key: Option<&str>,
+    ) -> Result<(), Error>;
+    async fn get_secret_20_viewing_key(
+        &self,
+        chain_id: &str,
+        contract_address: &str,
+    ) -> Result<String, Error>;
+    fn disable(&self, chain_id: &str);
+
+    /// Returns the amino signer for Ledger and Keystone accounts, and the direct signer
+    /// otherwise.
+    fn get_signer(&self, chain_id: &str, key: &Key) -> Result<KeplrSigner, Error> {
+        if key.is_nano_ledger || key.is_keystone {
+            debug!("using the amino signer for {}", key.bech32_address);
+            self.get_offline_signer_only_amino(chain_id)
+                .map(KeplrSigner::Amino)
+        } else {
+            self.get_offline_signer(chain_id).map(KeplrSigner::Direct)
+        }
+    }
+
+    async fn get_offline_signer_auto(&self, chain_id: &str) -> Result<KeplrSigner, Error> {
+        let key = self.get_key(chain_id).await?;
+        self.get_signer(chain_id, &key)
+    }
+}
+
+#[async_trait(?Send)]
+impl Wallet for Keplr {
+    fn name(&self) -> &'static str {
+        "Keplr"
+    }
+
+    fn provider(&self) -> Result<JsValue, Error> {
+        injected("keplr", self.name())
+    }
+
+    async fn enable(&self, chain_ids: Vec<String>) -> Result<(), Error> {
+        Keplr::enable(chain_ids).await
+    }
+
+    async fn get_key(&self, chain_id: &str) -> Result<Key, Error> {
+        Keplr::get_key(chain_id).await
+    }
+
+    fn get_offline_signer(&self, chain_id: &str) -> Result<KeplrOfflineSigner, Error> {
+        Ok(Keplr::get_offline_signer(chain_id))
+    }
+
+    fn get_offline_signer_only_amino(
+        &self,
+        chain_id: &str,
+    ) -> Result<KeplrOfflineSignerOnlyAmino, Error> {
+        Ok(Keplr::get_offline_signer_only_amino(chain_id))
+    }
+
+    fn get_enigma_utils(&self, chain_id: &str) -> Result<EnigmaUtils, Error> {
+        Ok(Keplr::get_enigma_utils(chain_id))
+    }
+
+    async fn suggest_token(
+        &self,
+        chain_id: &str,
+        contract_address: &str,
+        viewing_key: Option<&str>,
+    ) -> Result<(), Error> {
+        Keplr::suggest_token(chain_id, contract_address, viewing_key).await
+    }
+
+    async fn get_secret_20_viewing_key(
+        &self,
+        chain_id: &str,
+        contract_address: &str,
+    ) -> Result<String, Error> {
+        Keplr::get_secret_20_viewing_key(chain_id, contract_address).await
+    }
+
+    fn disable(&self, chain_id: &str) {
+        Keplr::disable(chain_id)
+    }
+}
+
+/// A wallet that injects a copy of the Keplr API under its own name, like Leap and Fina.
+///
+/// keplr-sys only binds `window.keplr`, so the calls are made by name on the injected object.
+/// The returned signers and enigma utils have the same shape as Keplr's, which lets them reuse
+/// the Keplr wrappers.
+pub struct InjectedWallet {
+    name: &'static str,
+    property: &'static str,
+}
+
+pub static LEAP: InjectedWallet = InjectedWallet {
+    name: "Leap",
+    property: "leap",
+};
+
+pub static FINA: InjectedWallet = InjectedWallet {
+    name: "Fina",
+    property: "fina",
+};
+
+#[async_trait(?Send)]
+impl Wallet for InjectedWallet {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn provider(&self) -> Result<JsValue, Error> {
+        injected(self.property, self.name)
+    }
+
+    async fn enable(&self, chain_ids: Vec<String>) -> Result<(), Error> {
+        let chain_ids = chain_ids
+            .into_iter()
+            .map(JsValue::from)
+            .collect::<js_sys::Array>();
+
+        call_async(&self.provider()?, "enable", &[chain_ids.into()]).await?;
+
+        Ok(())
+    }
+
+    async fn get_key(&self, chain_id: &str) -> Result<Key, Error> {
+        let key = call_async(&self.provider()?, "getKey", &[chain_id.into()]).await?;
+
+        Ok(serde_wasm_bindgen::from_value(key)?)
+    }
+
+    fn get_offline_signer(&self, chain_id: &str) -> Result<KeplrOfflineSigner, Error> {
+        call(&self.provider()?, "getOfflineSigner", &[chain_id.into()]).map(|signer| {
+            signer
+                .unchecked_into::<keplr_sys::KeplrOfflineSigner>()
+                .into()
+        })
+    }
+
+    fn get_offline_signer_only_amino(
+        &self,
+        chain_id: &str,
+    ) -> Result<KeplrOfflineSignerOnlyAmino, Error> {
+        call(
+            &self.provider()?,
+            "getOfflineSignerOnlyAmino",
+            &[chain_id.into()],
+        )
+        .map(|signer| {
+            signer
+                .unchecked_into::<keplr_sys::KeplrOfflineSignerOnlyAmino>()
+                .into()
+        })
+    }
+
+    fn get_enigma_utils(&self, chain_id: &str) -> Result<EnigmaUtils, Error> {
+        call(&self.provider()?, "getEnigmaUtils", &[chain_id.into()])
+            .map(|utils| utils.unchecked_into::<keplr_sys::EnigmaUtils>().into())
+    }
+
+    async fn suggest_token(
+        &self,
+        chain_id: &str,
+        contract_address: &str,
+        viewing_key: Option<&str>,
+    ) -> Result<(), Error> {
+        let viewing_key = viewing_key.map_or(JsValue::UNDEFINED, JsValue::from);
+
+        call_async(
+            &self.provider()?,
+            "suggestToken",
+            &[chain_id.into(), contract_address.into(), viewing_key],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_secret_20_viewing_key(
+        &self,
+        chain_id: &str,
+        contract_address: &str,
+    ) -> Result<String, Error> {
+        call_async(
+            &self.provider()?,
+            "getSecret20ViewingKey",
+            &[chain_id.into(), contract_address.into()],
+        )
+        .await?
+        .as_string()
+        .ok_or(Error::generic("viewing key is not a string"))
+    }
+
+    fn disable(&self, chain_id: &str) {
+        if let Ok(provider) = self.provider() {
+            _ = call(&provider, "disable", &[chain_id.into()]);
+        }
+    }
+}
+
+/// The wallets the app knows how to talk to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WalletKind {
+    #[default]
+    Keplr,
+    Leap,
+    Fina,
+    /// The in-browser mnemonic wallet, see [`crate::dev`]. Only built with the `dev` feature.
+    #[cfg(feature = "dev")]
+    Dev,
+}
+
+impl WalletKind {
+    #[cfg(not(feature = "dev"))]
+    pub const ALL: [WalletKind; 3] = [WalletKind::Keplr, WalletKind::Leap, WalletKind::Fina];
+
+    #[cfg(feature = "dev")]
+    pub const ALL: [WalletKind; 4] = [
+        WalletKind::Keplr,
+        WalletKind::Leap,
+        WalletKind::Fina,
+        WalletKind::Dev,
+    ];
+
+    /// The wallets that can be selected on `chain_id`. The dev wallet is only offered on
+    /// `secretdev-1`.
+    #[cfg_attr(not(feature = "dev"), allow(unused_variables))]
+    pub fn available_on(chain_id: &str) -> impl Iterator<Item = WalletKind> + '_ {
+        WalletKind::ALL.into_iter().filter(move |kind| match kind {
+            #[cfg(feature = "dev")]
+            WalletKind::Dev => chain_id == DEV_CHAIN_ID,
+            _ => true,
+        })
+    }
+
+    pub fn wallet(self) -> &'static dyn Wallet {
+        static KEPLR: Keplr = Keplr {};
+
+        match self {
+            WalletKind::Keplr => &KEPLR,
+            WalletKind::Leap => &LEAP,
+            WalletKind::Fina => &FINA,
+            #[cfg(feature = "dev")]
+            WalletKind::Dev => &DEV,
+        }
+    }
+}
+
+impl fmt::Display for WalletKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.wallet().name())
+    }
+}
+
+impl FromStr for WalletKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        WalletKind::ALL
+            .into_iter()
+            .find(|kind| kind.to_string() == s)
+            .ok_or_else(|| Error::generic(format!("unknown wallet: {s}")))
+    }
+}
+
+thread_local! {
+    static ACTIVE: Cell<WalletKind> = const { Cell::new(WalletKind::Keplr) };
+}
+
+/// Returns the wallet the user connected with.
+pub fn active() -> &'static dyn Wallet {
+    active_kind().wallet()
+}
+
+pub fn active_kind() -> WalletKind {
+    ACTIVE.with(Cell::get)
+}
+
+pub fn set_active(kind: WalletKind) {
+    debug!("using {kind}");
+    ACTIVE.with(|active| active.set(kind))
+}
+
+/// Returns `window[property]`, if the extension injected it.
+pub(crate) fn injected(property: &str, name: &str) -> Result<JsValue, Error> {
+    web_sys::window()
+        .and_then(|window| js_sys::Reflect::get(&window, &property.into()).ok())
+        .filter(|provider| !provider.is_undefined() && !provider.is_null())
+        .ok_or_else(|| Error::WalletUnavailable(name.to_string()))
+}
+
+fn method(target: &JsValue, method: &str) -> Result<js_sys::Function, Error> {
+    js_sys::Reflect::get(target, &method.into())
+        .ok()
+        .and_then(|function| function.dyn_into::<js_sys::Function>().ok())
+        .ok_or_else(|| Error::generic(format!("{method} is not supported by this wallet")))
+}
+
+/// Calls `method` on `target`.
+fn call(target: &JsValue, name: &str, args: &[JsValue]) -> Result<JsValue, Error> {
+    method(target, name)?
+        .apply(target, &args.iter().collect::<js_sys::Array>())
+        .map_err(Error::js)
+}
+
+/// Calls `method` on `target` and awaits the promise it returns.
+///
+/// Used for methods that keplr-sys doesn't bind. Wallets that lack the method produce an error
+/// saying so, rather than a JS exception.
+pub(crate) async fn call_async(
+    target: &JsValue,
+    name: &str,
+    args: &[JsValue],
+) -> Result<JsValue, Error> {
+    let promise = call(target, name, args)?;
+
+    JsFuture::from(js_sys::Promise::from(promise))
+        .await
+        .map_err(Error::js)
+}
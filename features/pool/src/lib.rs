@@ -3,8 +3,8 @@ mod routes;
 mod state;
 
 pub use pool::{
-    AddLiquidity, Pool, PoolAnalytics, PoolBrowser, PoolCreator, PoolManager, Pools,
-    RemoveLiquidity,
+    remove_liquidity_from_pools, AddLiquidity, Pool, PoolAnalytics, PoolBrowser, PoolCreator,
+    PoolManager, Pools, RemoveLiquidity, Withdrawal,
 };
 pub use routes::PoolRoutes;
 pub use state::PoolState;
@@ -1,5 +1,8 @@
 use ammber_core::support::chain_query;
-use ammber_core::{prelude::*, state::*, support::ILbPair, utils::addr_2_symbol, Error, BASE_URL};
+use ammber_core::{
+    position, prelude::*, state::*, support::ILbPair, utils::addr_2_symbol, Account, Error,
+    BASE_URL,
+};
 use ammber_sdk::{
     contract_interfaces::lb_pair::{
         self, BinResponse, BinsResponse, LbPair, ReservesResponse, StaticFeeParametersResponse,
@@ -7,8 +10,8 @@ use ammber_sdk::{
     utils::u128_to_string_with_precision,
 };
 use codee::string::FromToStringCodec;
-use cosmwasm_std::{Uint128, Uint256};
-use keplr::Keplr;
+use cosmwasm_std::Uint128;
+use keplr::wallet;
 use leptos::{ev, html, prelude::*, task::spawn_local};
 use leptos_router::{components::A, hooks::use_params_map, nested_router::Outlet};
 use leptos_use::storage::use_local_storage;
@@ -24,7 +27,9 @@ mod pool_manager;
 pub use pool_analytics::PoolAnalytics;
 pub use pool_browser::PoolBrowser;
 pub use pool_creator::PoolCreator;
-pub use pool_manager::{AddLiquidity, PoolManager, RemoveLiquidity};
+pub use pool_manager::{
+    remove_liquidity_from_pools, AddLiquidity, PoolManager, RemoveLiquidity, Withdrawal,
+};
 
 use crate::state::PoolStateStoreFields;
 
@@ -53,7 +58,9 @@ pub fn Pool() -> impl IntoView {
     });
 
     let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
-    let keplr = use_context::<KeplrSignals>().expect("keplr signals context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let balances = use_context::<Balances>().expect("balances context missing!");
+    let account = use_context::<Account>().expect("account context missing!");
 
     let params = use_params_map();
 
@@ -312,6 +319,13 @@ pub fn Pool() -> impl IntoView {
         // we have to access this signal sychronously to prevent the query from happening twice
         let active_id = active_id.get();
 
+        // refetch after any transaction that changes the balance of either token
+        balances.track(&token_a.get());
+        balances.track(&token_b.get());
+
+        // and after switching accounts
+        account.track();
+
         async move {
             debug!("getting my_liquidity");
 
@@ -339,28 +353,13 @@ pub fn Pool() -> impl IntoView {
                 }
             };
 
-            let mut ids = vec![];
-            let radius = 49;
-
-            for i in 0..(radius * 2 + 1) {
-                let offset_id = if i < radius {
-                    id - (radius - i) as u32 // Subtract for the first half
-                } else {
-                    id + (i - radius) as u32 // Add for the second half
-                };
-
-                ids.push(offset_id);
-            }
-
-            let account = Keplr::get_key(&chain_id)
+            let account = wallet::active()
+                .get_key(&chain_id)
                 .await
                 .map(|key| key.bech32_address)?;
 
-            let accounts = vec![account; ids.len()];
-
-            let balances: Vec<Uint256> = ILbPair(lb_pair)
-                .balance_of_batch(accounts, ids.clone())
-                .await?;
+            // finds every bin with a balance, not just the ones near the active bin
+            let (ids, balances) = position::discover(&lb_pair, &account, *id).await?;
 
             let combined: Vec<(u32, String)> = ids
                 .iter()
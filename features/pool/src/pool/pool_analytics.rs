@@ -1,6 +1,7 @@
 use crate::state::{PoolState, PoolStateStoreFields};
 use ammber_charts::{PoolDistributionChart, ReserveData};
 use ammber_core::{
+    price::{display_usd, Prices},
     utils::{display_token_amount, shorten_address},
     Error,
 };
@@ -24,13 +25,31 @@ pub fn PoolAnalytics() -> impl IntoView {
     // benefit of using Store. no Option or Result types to deal with
     let pool = use_context::<Store<PoolState>>().expect("missing the Store<PoolState> context");
 
+    let prices =
+        use_context::<LocalResource<Prices>>().expect("missing the prices resource context");
+
     // TODO:
-    // - Liquidity
     // - Volume
     // - Fees
     // - APR
     // - +/- 2% depth
 
+    let liquidity = move || {
+        let token_x = pool.token_x().get();
+        let token_y = pool.token_y().get();
+        let reserves = pool.total_reserves().get();
+
+        prices
+            .get()
+            .and_then(|prices| {
+                let value_x = prices.value(&token_x.contract_address, reserves.reserve_x.u128())?;
+                let value_y = prices.value(&token_y.contract_address, reserves.reserve_y.u128())?;
+                Some(value_x + value_y)
+            })
+            .map(display_usd)
+            .unwrap_or("–".to_string())
+    };
+
     let reserve_x = move || {
         let token_x = pool.token_x().get();
         let reserve_x = pool.total_reserves().get().reserve_x.u128();
@@ -174,12 +193,10 @@ pub fn PoolAnalytics() -> impl IntoView {
                 <div class="bg-card px-4 sm:px-8 py-4 rounded-lg">
                     <dl class="m-0">
                         <dt class="text-sm text-muted-foreground font-medium">"Liquidity"</dt>
-                        <div class="flex items-start flex-col sm:flex-row sm:items-center sm:gap-2">
-                            <dd class="py-0.5 sm:pb-0 text-2xl font-semibold align-baseline proportional-nums">
-                                "$0.00"
-                            </dd>
-                            <dd class="sm:pt-0.5 text-foam text-sm font-semibold">"0%"</dd>
-                        </div>
+                        // there is no liquidity history to show a change against
+                        <dd class="pt-0.5 text-2xl font-semibold align-baseline proportional-nums">
+                            {liquidity}
+                        </dd>
                     </dl>
                 </div>
                 <div class="bg-card px-4 sm:px-8 py-4 rounded-lg">
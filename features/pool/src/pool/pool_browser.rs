@@ -1,9 +1,218 @@
-use ammber_core::{prelude::*, support::chain_query, Error};
-use ammber_sdk::contract_interfaces::lb_pair::LbPair;
+use ammber_core::{
+    prelude::*,
+    price::{display_usd, Prices},
+    support::chain_batch_query,
+    Error,
+};
+use ammber_sdk::{
+    contract_interfaces::lb_pair::{
+        self, ActiveIdResponse, LbPair, ReservesResponse, StaticFeeParametersResponse,
+    },
+    utils::get_price_from_id,
+};
+use batch_query::{BatchItemResponseStatus, BatchQueryParams};
 use leptos::prelude::*;
 use leptos_router::components::A;
-use liquidity_book::core::TokenType;
-use tracing::{debug, info};
+use std::{cmp::Ordering, collections::HashMap};
+use tracing::{error, info};
+
+const PAGE_SIZE: usize = 10;
+
+/// What the browser shows for one pair, from a single batch of queries.
+#[derive(Clone)]
+struct PairStats {
+    lb_pair: LbPair,
+    address_x: String,
+    address_y: String,
+    symbol_x: String,
+    symbol_y: String,
+    active_id: u32,
+    reserve_x: f64,
+    reserve_y: f64,
+    /// In basis points. Not rounded, so fees below one basis point still show.
+    base_fee: f64,
+    /// The protocol's cut of the fees, in basis points.
+    protocol_share: f64,
+    /// Trading volume over the last 24 hours, in USD. The pairs don't record their volume and
+    /// there is no indexer to ask yet, so this is `None` until one exists.
+    volume_usd: Option<f64>,
+}
+
+impl PairStats {
+    fn name(&self) -> String {
+        format!("{} – {}", self.symbol_x, self.symbol_y)
+    }
+
+    /// Human units of token Y per human unit of token X, at the active bin.
+    fn price(&self) -> f64 {
+        let decimals_x = get_token_decimals(&self.address_x).unwrap_or(0) as i32;
+        let decimals_y = get_token_decimals(&self.address_y).unwrap_or(0) as i32;
+
+        get_price_from_id(self.active_id.to_string(), self.lb_pair.bin_step)
+            * 10f64.powi(decimals_x - decimals_y)
+    }
+
+    fn has_token(&self, address: &str) -> bool {
+        self.address_x == address || self.address_y == address
+    }
+
+    fn matches(&self, search: &str) -> bool {
+        let search = search.to_lowercase();
+        [
+            &self.symbol_x,
+            &self.symbol_y,
+            &self.address_x,
+            &self.address_y,
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(&search))
+            || self.lb_pair.contract.address.as_str().contains(&search)
+    }
+
+    /// The base fee as a percentage, with enough decimals for fees below a basis point.
+    fn base_fee_percent(&self) -> String {
+        let percent = format!("{:.4}", self.base_fee / 100.0);
+        let percent = percent.trim_end_matches('0');
+        let percent = percent.strip_suffix('.').unwrap_or(percent);
+
+        format!("{percent}%")
+    }
+
+    /// The yearly return to liquidity providers from the base fee, as a percentage of `tvl_usd`.
+    /// `None` without the volume or a TVL.
+    fn apr(&self, tvl_usd: Option<f64>) -> Option<f64> {
+        let tvl_usd = tvl_usd.filter(|tvl| *tvl > 0.0)?;
+        let fees =
+            self.volume_usd? * self.base_fee / 10_000.0 * (1.0 - self.protocol_share / 10_000.0);
+
+        Some(fees * 365.0 / tvl_usd * 100.0)
+    }
+
+    fn href(&self) -> String {
+        format!(
+            "/liquidity-book-leptos/pool/{}/{}/{}/manage/add",
+            self.address_x, self.address_y, self.lb_pair.bin_step
+        )
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(
+    responses: &HashMap<String, String>,
+    id: &str,
+) -> Option<T> {
+    serde_json::from_str(responses.get(id)?).ok()
+}
+
+/// Queries the active id, reserves and static fee parameters of every pair in one batch. Pairs
+/// that fail to answer any of them are left out.
+async fn pair_stats(lb_pairs: Vec<LbPair>) -> Result<Vec<PairStats>, Error> {
+    let queries = lb_pairs
+        .iter()
+        .enumerate()
+        .flat_map(|(index, lb_pair)| {
+            [
+                ("active", lb_pair::QueryMsg::GetActiveId {}),
+                ("reserves", lb_pair::QueryMsg::GetReserves {}),
+                ("fees", lb_pair::QueryMsg::GetStaticFeeParameters {}),
+            ]
+            .map(|(kind, query_msg)| BatchQueryParams {
+                id: format!("{index}:{kind}"),
+                contract: lb_pair.contract.clone(),
+                query_msg,
+            })
+        })
+        .collect();
+
+    let responses: HashMap<String, String> = chain_batch_query(queries)
+        .await?
+        .items
+        .into_iter()
+        .filter(|item| item.status == BatchItemResponseStatus::SUCCESS)
+        .map(|item| (item.id, item.response))
+        .collect();
+
+    let stats = lb_pairs
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, lb_pair)| {
+            let active = parse::<ActiveIdResponse>(&responses, &format!("{index}:active"))?;
+            let reserves = parse::<ReservesResponse>(&responses, &format!("{index}:reserves"))?;
+            let fees = parse::<StaticFeeParametersResponse>(&responses, &format!("{index}:fees"))?;
+
+            let address_x = lb_pair.token_x.address().to_string();
+            let address_y = lb_pair.token_y.address().to_string();
+            let decimals_x = get_token_decimals(&address_x).unwrap_or(0) as i32;
+            let decimals_y = get_token_decimals(&address_y).unwrap_or(0) as i32;
+
+            Some(PairStats {
+                symbol_x: get_token_symbol(&address_x),
+                symbol_y: get_token_symbol(&address_y),
+                active_id: active.active_id,
+                reserve_x: reserves.reserve_x.u128() as f64 / 10f64.powi(decimals_x),
+                reserve_y: reserves.reserve_y.u128() as f64 / 10f64.powi(decimals_y),
+                base_fee: fees.base_factor as f64 * lb_pair.bin_step as f64 / 10_000.0,
+                protocol_share: fees.protocol_share as f64,
+                volume_usd: None,
+                address_x,
+                address_y,
+                lb_pair,
+            })
+        })
+        .collect();
+
+    Ok(stats)
+}
+
+/// The price of one `token` in `quote`, from the deepest pair that trades them directly.
+fn quote_price(stats: &[PairStats], token: &str, quote: &str) -> Option<f64> {
+    if token == quote {
+        return Some(1.0);
+    }
+
+    stats
+        .iter()
+        .filter(|pair| pair.has_token(token) && pair.has_token(quote))
+        .max_by(|a, b| {
+            let depth = |pair: &PairStats| {
+                if pair.address_x == quote {
+                    pair.reserve_x
+                } else {
+                    pair.reserve_y
+                }
+            };
+            depth(a).partial_cmp(&depth(b)).unwrap_or(Ordering::Equal)
+        })
+        .map(|pair| {
+            if pair.address_x == token {
+                pair.price()
+            } else {
+                1.0 / pair.price()
+            }
+        })
+        .filter(|price| price.is_finite())
+}
+
+/// Total value locked in `pair`, in `quote`, or in USD if `quote` is empty. `None` if either
+/// token has no price.
+fn tvl(stats: &[PairStats], prices: &Prices, pair: &PairStats, quote: &str) -> Option<f64> {
+    let (price_x, price_y) = if quote.is_empty() {
+        (prices.get(&pair.address_x)?, prices.get(&pair.address_y)?)
+    } else {
+        (
+            quote_price(stats, &pair.address_x, quote)?,
+            quote_price(stats, &pair.address_y, quote)?,
+        )
+    };
+
+    Some(pair.reserve_x * price_x + pair.reserve_y * price_y)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SortBy {
+    Liquidity,
+    Fee,
+    Name,
+}
 
 #[component]
 pub fn PoolBrowser() -> impl IntoView {
@@ -30,29 +239,124 @@ pub fn PoolBrowser() -> impl IntoView {
     //     pub depth_y: u32,
     //     pub fees: u32,
     // }
-    //
-    // let rows = all_lb_pairs
-    //     .get()
-    //     .map(|vec| {
-    //         vec.iter()
-    //             .map(|n| Pool {
-    //                 pool_name: n.contract.address.to_string(),
-    //                 ..Default::default()
-    //             })
-    //             .collect::<Vec<Pool>>()
-    //     })
-    //     .unwrap_or_default();
-
-    // Effect::new(move || debug!("{:?}", rows));
+
+    let prices =
+        use_context::<LocalResource<Prices>>().expect("missing the prices resource context");
+
+    // an empty quote means USD
+    let quote = RwSignal::new(String::new());
+    let token_filter = RwSignal::new(String::new());
+    let search = RwSignal::new(String::new());
+    let sort_by = RwSignal::new(SortBy::Liquidity);
+    let descending = RwSignal::new(true);
+    let page = RwSignal::new(0usize);
+
+    let stats = LocalResource::new(move || async move {
+        let lb_pairs = all_lb_pairs.await;
+        pair_stats(lb_pairs).await.unwrap_or_else(|error| {
+            error!("{error}");
+            vec![]
+        })
+    });
+
+    // every listed pair with its TVL and APR, filtered and sorted
+    let rows = Signal::derive(move || {
+        let Some(stats) = stats.get() else {
+            return vec![];
+        };
+        let prices = prices.get().as_deref().cloned().unwrap_or_default();
+        let quote = quote.get();
+        let token_filter = token_filter.get();
+        let search = search.get();
+
+        let mut rows: Vec<(PairStats, Option<f64>, Option<f64>)> = stats
+            .iter()
+            .filter(|pair| token_filter.is_empty() || pair.has_token(&token_filter))
+            .filter(|pair| search.is_empty() || pair.matches(&search))
+            .map(|pair| {
+                let apr = pair.apr(tvl(&stats, &prices, pair, ""));
+                (pair.clone(), tvl(&stats, &prices, pair, &quote), apr)
+            })
+            .collect();
+
+        rows.sort_by(|(a, tvl_a, _), (b, tvl_b, _)| match sort_by.get() {
+            SortBy::Liquidity => tvl_a
+                .unwrap_or(-1.0)
+                .partial_cmp(&tvl_b.unwrap_or(-1.0))
+                .unwrap_or(Ordering::Equal),
+            SortBy::Fee => a.base_fee.total_cmp(&b.base_fee),
+            SortBy::Name => a.name().cmp(&b.name()),
+        });
+        if descending.get() {
+            rows.reverse();
+        }
+
+        rows
+    });
+
+    let page_count = move || rows.with(|rows| rows.len().div_ceil(PAGE_SIZE).max(1));
+    let visible = move || {
+        rows.with(|rows| {
+            rows.iter()
+                .skip(page.get() * PAGE_SIZE)
+                .take(PAGE_SIZE)
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    };
+
+    let format_tvl = move |tvl: Option<f64>| match tvl {
+        Some(tvl) if quote.get().is_empty() => display_usd(tvl),
+        Some(tvl) => format!("{tvl:.2} {}", get_token_symbol(&quote.get())),
+        None => "–".to_string(),
+    };
+    let format_volume =
+        |pair: &PairStats| pair.volume_usd.map(display_usd).unwrap_or("–".to_string());
+    let format_apr = |apr: Option<f64>| {
+        apr.map(|apr| format!("{apr:.2}%"))
+            .unwrap_or("–".to_string())
+    };
+
+    let sort_header = move |label: &'static str, column: SortBy| {
+        view! {
+            <button
+                class="p-0 bg-transparent border-none font-medium text-muted-foreground"
+                on:click=move |_| {
+                    if sort_by.get() == column {
+                        descending.update(|descending| *descending = !*descending);
+                    } else {
+                        sort_by.set(column);
+                        descending.set(column != SortBy::Name);
+                    }
+                }
+            >
+                {label}
+                {move || match (sort_by.get() == column, descending.get()) {
+                    (false, _) => "",
+                    (true, true) => " ↓",
+                    (true, false) => " ↑",
+                }}
+            </button>
+        }
+    };
+
+    let token_options = move || {
+        TOKEN_MAP
+            .values()
+            .map(|token| {
+                view! {
+                    <option value=token.contract_address.clone()>
+                        {token.display_name.clone().unwrap_or(token.symbol.clone())}
+                    </option>
+                }
+            })
+            .collect_view()
+    };
 
     view! {
         <div class="text-3xl font-bold">"Pool"</div>
         <p class="text-sm text-muted-foreground">"Provide liquidity and earn fees."</p>
 
-        // <table>
-        // <TableContent rows=rows scroll_container="html"/>
-        // </table>
-
         <div class="flex items-center justify-between">
             <h3 class="mb-3">
                 "All Pools - " {move || number_of_lb_pairs.get().as_deref().cloned()}
@@ -68,91 +372,74 @@ pub fn PoolBrowser() -> impl IntoView {
             </div>
         </div>
 
-        <div class="flex flex-col gap-2 md:hidden">
-            <Suspense fallback=|| {
-                view! { <div>"Loading..."</div> }
-            }>
-                {move || Suspend::new(async move {
-                    all_lb_pairs
-                        .await
-                        .into_iter()
-                        .map(|n| {
-                            view! {
-                                <a
-                                    class="no-underline hover:!no-underline"
-                                    href=format!(
-                                        "/liquidity-book-leptos/pool/{}/{}/{}/manage/add",
-                                        match n.token_x {
-                                            TokenType::CustomToken { ref contract_addr, .. } => {
-                                                contract_addr.to_string()
-                                            }
-                                            TokenType::NativeToken { ref denom } => denom.to_string(),
-                                        },
-                                        match n.token_y {
-                                            TokenType::CustomToken { ref contract_addr, .. } => {
-                                                contract_addr.to_string()
-                                            }
-                                            TokenType::NativeToken { ref denom } => denom.to_string(),
-                                        },
-                                        n.bin_step,
-                                    )
-                                >
-                                    <div class="block bg-card rounded-lg space-y-4 border border-solid p-4">
-                                        <div class="flex items-center gap-4 text-base font-semibold">
-                                            <div class="text-neutral-50">
-                                                {format!(
-                                                    "{} – {}",
-                                                    TOKEN_MAP
-                                                        .get(&n.token_x.unique_key())
-                                                        .map(|t| {
-                                                            if let Some(ref display_name) = t.display_name {
-                                                                display_name.clone()
-                                                            } else {
-                                                                t.symbol.clone()
-                                                            }
-                                                        })
-                                                        .unwrap_or_default(),
-                                                    TOKEN_MAP
-                                                        .get(&n.token_y.unique_key())
-                                                        .map(|t| {
-                                                            if let Some(ref display_name) = t.display_name {
-                                                                display_name.clone()
-                                                            } else {
-                                                                t.symbol.clone()
-                                                            }
-                                                        })
-                                                        .unwrap_or_default(),
-                                                )}
-                                            </div>
-                                            <div class="text-white text-xs py-1 px-2 rounded-md border border-solid">
-                                                {format!("{} bps", n.bin_step)}
-                                            </div>
+        <div class="flex flex-wrap items-center gap-2 mb-3">
+            <input
+                class="flex-1 min-w-40 px-3 py-1 h-9 text-sm rounded-md"
+                type="search"
+                placeholder="Search by symbol or address"
+                on:input=move |ev| {
+                    search.set(event_target_value(&ev));
+                    page.set(0);
+                }
+            />
+            <select
+                class="py-2 px-4 h-9 text-sm bg-card rounded-md"
+                title="Only show pools with this token"
+                on:change=move |ev| {
+                    token_filter.set(event_target_value(&ev));
+                    page.set(0);
+                }
+            >
+                <option value="">"All tokens"</option>
+                {token_options}
+            </select>
+            <select
+                class="py-2 px-4 h-9 text-sm bg-card rounded-md"
+                title="Show liquidity in this token"
+                prop:value=move || quote.get()
+                on:change=move |ev| quote.set(event_target_value(&ev))
+            >
+                <option value="">"USD"</option>
+                {token_options}
+            </select>
+        </div>
 
+        <Show when=move || stats.get().is_none()>
+            <div>"Loading..."</div>
+        </Show>
+
+        <div class="flex flex-col gap-2 md:hidden">
+            {move || {
+                visible()
+                    .into_iter()
+                    .map(|(pair, tvl, _)| {
+                        view! {
+                            <a class="no-underline hover:!no-underline" href=pair.href()>
+                                <div class="block bg-card rounded-lg space-y-4 border border-solid p-4">
+                                    <div class="flex items-center gap-4 text-base font-semibold">
+                                        <div class="text-neutral-50">{pair.name()}</div>
+                                        <div class="text-white text-xs py-1 px-2 rounded-md border border-solid">
+                                            {format!("{} bps", pair.lb_pair.bin_step)}
                                         </div>
-                                        // TODO: how would I get this data while inside of the iterator?
-                                        <div class="flex flex-row justify-between text-sm">
-                                            <div class="flex flex-col">
-                                                <p class="mb-1 mt-0 text-muted-foreground">"Liquidity"</p>
-                                                <p class="my-0 font-semibold">"$0.00"</p>
-                                            </div>
-                                            <div class="flex flex-col">
-                                                <p class="mb-1 mt-0 text-muted-foreground">
-                                                    "Volume (24H)"
-                                                </p>
-                                                <p class="my-0 font-semibold">"$0.00"</p>
-                                            </div>
-                                            <div class="flex flex-col">
-                                                <p class="mb-1 mt-0 text-muted-foreground">"Fees (24H)"</p>
-                                                <p class="my-0 font-semibold">"$0.00"</p>
-                                            </div>
+                                    </div>
+                                    <div class="flex flex-row justify-between text-sm">
+                                        <div class="flex flex-col">
+                                            <p class="mb-1 mt-0 text-muted-foreground">"Liquidity"</p>
+                                            <p class="my-0 font-semibold">{format_tvl(tvl)}</p>
+                                        </div>
+                                        <div class="flex flex-col">
+                                            <p class="mb-1 mt-0 text-muted-foreground">"Base Fee"</p>
+                                            <p class="my-0 font-semibold">
+                                                {pair.base_fee_percent()}
+                                            </p>
                                         </div>
                                     </div>
-                                </a>
-                            }
-                        })
-                        .collect_view()
-                })}
-            </Suspense>
+                                </div>
+                            </a>
+                        }
+                    })
+                    .collect_view()
+            }}
         </div>
 
         <div class="hidden md:block box-border p-2 min-w-full border border-solid rounded-lg bg-card">
@@ -160,91 +447,82 @@ pub fn PoolBrowser() -> impl IntoView {
                 <thead class="box-border border-0 border-solid border-spacing-x-0 border-spacing-y-2">
                     <tr class="box-content text-muted-foreground">
                         <th class="px-4 py-2 font-medium text-left rounded-l-sm box-content">
-                            "Pool Name"
+                            {sort_header("Pool Name", SortBy::Name)}
+                        </th>
+                        <th class="px-4 py-2 font-medium text-right box-content">"Price"</th>
+                        <th class="px-4 py-2 font-medium text-right box-content">
+                            {sort_header("Liquidity", SortBy::Liquidity)}
+                        </th>
+                        <th class="px-4 py-2 font-medium text-right box-content">
+                            {sort_header("Base Fee", SortBy::Fee)}
                         </th>
-                        <th class="px-4 py-2 font-medium text-right box-content">"Volume (24H)"</th>
-                        <th class="px-4 py-2 font-medium text-right box-content">"Liquidity"</th>
-                        <th class="px-4 py-2 font-medium text-right rounded-r-sm box-content">
-                            "Fees (24H)"
+                        <th
+                            class="px-4 py-2 font-medium text-right box-content"
+                            title="There is no source of volume data yet"
+                        >
+                            "Volume (24H)"
+                        </th>
+                        <th
+                            class="px-4 py-2 font-medium text-right rounded-r-sm box-content"
+                            title="From the base fee on the last 24 hours of volume, after the protocol's share"
+                        >
+                            "APR"
                         </th>
                     </tr>
                 </thead>
-                // crazy, but it works
-                <Suspense fallback=|| view! { <div>"Loading..."</div> }>
-                    <tbody>
-                        {move || Suspend::new(async move {
-                            all_lb_pairs
-                                .await
-                                .into_iter()
-                                .map(|n| {
-                                    view! {
-                                        <tr>
-                                            <td class="px-4 py-2">
-                                                <div class="flex items-center gap-4 text-sm font-semibold">
-                                                    <a
-                                                        class="no-underline text-white"
-                                                        href=format!(
-                                                            "/liquidity-book-leptos/pool/{}/{}/{}/manage/add",
-                                                            match n.token_x {
-                                                                TokenType::CustomToken { ref contract_addr, .. } => {
-                                                                    contract_addr.to_string()
-                                                                }
-                                                                TokenType::NativeToken { ref denom } => denom.to_string(),
-                                                            },
-                                                            match n.token_y {
-                                                                TokenType::CustomToken { ref contract_addr, .. } => {
-                                                                    contract_addr.to_string()
-                                                                }
-                                                                TokenType::NativeToken { ref denom } => denom.to_string(),
-                                                            },
-                                                            n.bin_step,
-                                                        )
-                                                    >
-                                                        <div class="">
-                                                            {format!(
-                                                                "{} – {}",
-                                                                TOKEN_MAP
-                                                                    .get(&n.token_x.unique_key())
-                                                                    .map(|t| {
-                                                                        if let Some(ref display_name) = t.display_name {
-                                                                            display_name.clone()
-                                                                        } else {
-                                                                            t.symbol.clone()
-                                                                        }
-                                                                    })
-                                                                    .unwrap_or_default(),
-                                                                TOKEN_MAP
-                                                                    .get(&n.token_y.unique_key())
-                                                                    .map(|t| {
-                                                                        if let Some(ref display_name) = t.display_name {
-                                                                            display_name.clone()
-                                                                        } else {
-                                                                            t.symbol.clone()
-                                                                        }
-                                                                    })
-                                                                    .unwrap_or_default(),
-                                                            )}
-                                                        </div>
-                                                    </a>
-                                                    <div class="cursor-default text-foreground text-xs py-1 px-2 rounded-full border border-solid">
-                                                        {format!("{} bps", n.bin_step)}
-                                                    </div>
-
+                <tbody>
+                    {move || {
+                        visible()
+                            .into_iter()
+                            .map(|(pair, tvl, apr)| {
+                                view! {
+                                    <tr>
+                                        <td class="px-4 py-2">
+                                            <div class="flex items-center gap-4 text-sm font-semibold">
+                                                <a class="no-underline text-white" href=pair.href()>
+                                                    {pair.name()}
+                                                </a>
+                                                <div class="cursor-default text-foreground text-xs py-1 px-2 rounded-full border border-solid">
+                                                    {format!("{} bps", pair.lb_pair.bin_step)}
                                                 </div>
-                                            </td>
-                                            // TODO: how would I get this data while inside of the iterator?
-                                            <td class="px-4 py-2 text-right">"$0.00"</td>
-                                            <td class="px-4 py-2 text-right">"$0.00"</td>
-                                            <td class="px-4 py-2 text-right">"$0.00"</td>
-                                        </tr>
-                                    }
-                                })
-                                .collect_view()
-                        })}
-                    </tbody>
-                </Suspense>
-
+                                            </div>
+                                        </td>
+                                        <td class="px-4 py-2 text-right">
+                                            {format!("{:.6} {}", pair.price(), pair.symbol_y)}
+                                        </td>
+                                        <td class="px-4 py-2 text-right">{format_tvl(tvl)}</td>
+                                        <td class="px-4 py-2 text-right">
+                                            {pair.base_fee_percent()}
+                                        </td>
+                                        <td class="px-4 py-2 text-right">{format_volume(&pair)}</td>
+                                        <td class="px-4 py-2 text-right">{format_apr(apr)}</td>
+                                    </tr>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                </tbody>
             </table>
         </div>
+
+        <div class="flex items-center justify-end gap-2 mt-3 text-sm">
+            <button
+                class="py-1 px-3 bg-secondary text-secondary-foreground rounded-md"
+                disabled=move || page.get() == 0
+                on:click=move |_| page.update(|page| *page = page.saturating_sub(1))
+            >
+                "Previous"
+            </button>
+            <span class="text-muted-foreground">
+                {move || format!("Page {} of {}", page.get() + 1, page_count())}
+            </span>
+            <button
+                class="py-1 px-3 bg-secondary text-secondary-foreground rounded-md"
+                disabled=move || page.get() + 1 >= page_count()
+                on:click=move |_| page.update(|page| *page += 1)
+            >
+                "Next"
+            </button>
+        </div>
     }
 }
@@ -1,25 +1,26 @@
+use ammber_components::ErrorMessage;
 use ammber_core::{
-    constants::contracts::*, constants::TOKEN_MAP, prelude::SYMBOL_TO_ADDR, ChainId, Endpoint,
-    Error, KeplrSignals, CHAIN_ID, NODE,
+    allowance,
+    constants::contracts::*,
+    constants::TOKEN_MAP,
+    prelude::SYMBOL_TO_ADDR,
+    utils::{get_token_decimals, parse_token_amount},
+    ChainId, Endpoint, Error, TxKind, TxManager, TxSummary, WalletSignals, CHAIN_ID,
 };
 use ammber_sdk::{
-    contract_interfaces::lb_router::{self, CreateLbPairResponse},
+    constants::liquidity_config::{LiquidityConfigurations, LiquidityShape},
+    contract_interfaces::lb_router::{self, CreateLbPairResponse, LiquidityParameters},
     utils::get_id_from_price,
 };
-use cosmwasm_std::Addr;
-use keplr::Keplr;
+use cosmwasm_std::{Addr, ContractInfo, Uint128, Uint64};
+use keplr::wallet;
 use leptos::html;
 use leptos::prelude::*;
 use liquidity_book::core::TokenType;
 use lucide_leptos::ArrowLeft;
-use rsecret::{
-    secret_client::CreateTxSenderOptions,
-    tx::{compute::MsgExecuteContractRaw, ComputeServiceClient},
-    TxOptions,
-};
-use secretrs::{compute::MsgExecuteContractResponse, tx::Msg, AccountId};
-use std::str::FromStr;
-use tracing::{debug, error, info};
+use rsecret::{query::tendermint::TendermintQuerier, TxOptions};
+use tonic_web_wasm_client::Client;
+use tracing::{debug, info};
 
 #[component]
 pub fn PoolCreator() -> impl IntoView {
@@ -31,15 +32,23 @@ pub fn PoolCreator() -> impl IntoView {
 
     let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
     let chain_id = use_context::<ChainId>().expect("chain_id context missing!");
-    let keplr = use_context::<KeplrSignals>().expect("keplr signals context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
 
     let (token_x, set_token_x) = signal("AMBER".to_string());
     let (token_y, set_token_y) = signal("SSCRT".to_string());
     let (bin_step, set_bin_step) = signal(100u16);
     let (active_price, set_active_price) = signal("1.0".to_string());
+    let (seed_x, set_seed_x) = signal(String::new());
+    let (seed_y, set_seed_y) = signal(String::new());
+
+    // the initial deposit is spread evenly over this many bins on each side of the active bin
+    const SEED_RADIUS: u32 = 5;
+    // slippage in basis points
+    const SEED_SLIPPAGE: u128 = 20;
 
     let create_lb_pair = Action::new_local(move |_: &()| {
-        let url = NODE;
+        let url = endpoint.get();
         let chain_id = CHAIN_ID;
 
         let token_x = token_x.get();
@@ -64,6 +73,9 @@ pub fn PoolCreator() -> impl IntoView {
 
         let active_id = get_id_from_price(price, bin_step);
 
+        let seed_x = seed_x.get();
+        let seed_y = seed_y.get();
+
         async move {
             if false {
                 return Err(Error::generic(
@@ -71,69 +83,111 @@ pub fn PoolCreator() -> impl IntoView {
                 ));
             }
 
-            let key = Keplr::get_key(&chain_id).await?;
+            let key = wallet::active().get_key(&chain_id).await?;
             keplr.enabled.set(true);
-            // let wallet = Keplr::get_offline_signer_only_amino(&chain_id);
-            let wallet = Keplr::get_offline_signer(&chain_id);
-            let enigma_utils = Keplr::get_enigma_utils(&chain_id).into();
-
-            // TODO: I guess I need to make this type use Strings instead of &'static str, because the
-            // values are not static in this application (user is able to set them to anything).
-            let options = CreateTxSenderOptions {
-                url,
-                chain_id,
-                wallet: wallet.into(),
-                wallet_address: key.bech32_address.clone().into(),
-                enigma_utils,
-            };
-            let wasm_web_client = tonic_web_wasm_client::Client::new(url.to_string());
-            let compute_service_client = ComputeServiceClient::new(wasm_web_client, options);
 
             let lb_router_contract = &LB_ROUTER;
+            let token_x_address = token_x.contract_address.clone();
+            let token_y_address = token_y.contract_address.clone();
+            let token_x_code_hash = token_x.code_hash.clone();
+            let token_y_code_hash = token_y.code_hash.clone();
+
+            let token_x_type = TokenType::CustomToken {
+                contract_addr: Addr::unchecked(token_x.contract_address),
+                token_code_hash: token_x.code_hash,
+            };
+            let token_y_type = TokenType::CustomToken {
+                contract_addr: Addr::unchecked(token_y.contract_address),
+                token_code_hash: token_y.code_hash,
+            };
 
-            let msg = MsgExecuteContractRaw {
-                sender: AccountId::from_str(key.bech32_address.as_ref())?,
-                contract: AccountId::from_str(lb_router_contract.address.as_ref())?,
-                msg: lb_router::ExecuteMsg::CreateLbPair {
-                    token_x: TokenType::CustomToken {
-                        contract_addr: Addr::unchecked(token_x.contract_address),
-                        token_code_hash: token_x.code_hash,
-                    },
-                    token_y: TokenType::CustomToken {
-                        contract_addr: Addr::unchecked(token_y.contract_address),
-                        token_code_hash: token_y.code_hash,
-                    },
-                    active_id,
-                    bin_step,
-                },
-                sent_funds: vec![],
+            let msg = lb_router::ExecuteMsg::CreateLbPair {
+                token_x: token_x_type.clone(),
+                token_y: token_y_type.clone(),
+                active_id,
+                bin_step,
             };
 
             debug!("{:#?}", msg);
 
-            let tx_options = TxOptions {
-                gas_limit: 1_000_000,
-                ..Default::default()
-            };
-
-            let tx = compute_service_client
-                .execute_contract(msg, lb_router_contract.code_hash.clone(), tx_options)
-                .await
-                .map_err(Error::from)
-                .inspect(|tx_response| info!("{tx_response:?}"))
-                .inspect_err(|error| error!("{error}"))?;
+            let amount_x = get_token_decimals(&token_x_address)
+                .map(|decimals| parse_token_amount(&seed_x, decimals))
+                .unwrap_or_default();
+            let amount_y = get_token_decimals(&token_y_address)
+                .map(|decimals| parse_token_amount(&seed_y, decimals))
+                .unwrap_or_default();
+
+            let summary = TxSummary::new(TxKind::CreatePool);
+            let mut batch = tx_manager
+                .batch(&key.bech32_address, summary)
+                .with_token(token_x_address.clone(), amount_x)
+                .with_token(token_y_address.clone(), amount_y);
+
+            let create_lb_pair_index = batch.push(lb_router_contract, &msg).await?;
+
+            // the messages run in order, so the new pair already exists when the router adds the
+            // initial liquidity, and a failed deposit reverts the creation too
+            if amount_x > 0 || amount_y > 0 {
+                let token_x = ContractInfo {
+                    address: Addr::unchecked(token_x_address),
+                    code_hash: token_x_code_hash,
+                };
+                let token_y = ContractInfo {
+                    address: Addr::unchecked(token_y_address),
+                    code_hash: token_y_code_hash,
+                };
+
+                let tendermint = TendermintQuerier::new(Client::new(url.to_string()));
+                let latest_block_time = tendermint
+                    .get_latest_block()
+                    .await
+                    .map(|block| block.header.time.unix_timestamp() as u64)
+                    .map_err(Error::from)?;
+
+                let liq = LiquidityConfigurations::by_radius(
+                    active_id,
+                    SEED_RADIUS,
+                    LiquidityShape::SpotUniform,
+                );
 
-            if tx.code != 0 {
-                error!("{}", tx.raw_log);
+                let liquidity_parameters = LiquidityParameters {
+                    token_x: token_x_type,
+                    token_y: token_y_type,
+                    bin_step,
+                    amount_x: Uint128::new(amount_x),
+                    amount_y: Uint128::new(amount_y),
+                    amount_x_min: Uint128::new(amount_x * (10_000 - SEED_SLIPPAGE) / 10_000),
+                    amount_y_min: Uint128::new(amount_y * (10_000 - SEED_SLIPPAGE) / 10_000),
+                    active_id_desired: active_id,
+                    id_slippage: 0,
+                    delta_ids: liq.delta_ids(),
+                    distribution_x: liq.distribution_x(),
+                    distribution_y: liq.distribution_y(),
+                    to: key.bech32_address.clone(),
+                    refund_to: key.bech32_address.clone(),
+                    deadline: Uint64::new(latest_block_time + 100),
+                };
+
+                allowance::ensure(&mut batch, &token_x, lb_router_contract, amount_x).await?;
+                allowance::ensure(&mut batch, &token_y, lb_router_contract, amount_y).await?;
+                batch
+                    .push(
+                        lb_router_contract,
+                        &lb_router::ExecuteMsg::AddLiquidity {
+                            liquidity_parameters,
+                        },
+                    )
+                    .await?;
             }
 
-            debug!("hello");
-            let data = MsgExecuteContractResponse::from_any(&tx.data[0])
-                .inspect_err(|e| error! {"{e}"})?
-                .data;
-            debug!("hello");
+            let tx = batch.submit(TxOptions::default()).await?;
+
+            let data = tx
+                .data
+                .get(create_lb_pair_index)
+                .cloned()
+                .unwrap_or_default();
             let create_lb_pair_response = serde_json::from_slice::<CreateLbPairResponse>(&data)?;
-            debug!("hello");
 
             debug!("LbPair: {:?}", create_lb_pair_response.lb_pair);
 
@@ -299,6 +353,33 @@ pub fn PoolCreator() -> impl IntoView {
                     />
                 </label>
 
+                <div class="flex flex-col gap-2 w-full font-medium">
+                    <p class="text-sm">"Initial Liquidity (optional)"</p>
+                    <input
+                        name="seed_x"
+                        title="Enter Amount of the Base Token"
+                        type="number"
+                        inputmode="decimal"
+                        min="0"
+                        placeholder="0.0"
+                        class="px-3 py-2 h-9 text-sm font-medium bg-transparent text-white rounded-md"
+                        on:input=move |ev| set_seed_x.set(event_target_value(&ev))
+                    />
+                    <input
+                        name="seed_y"
+                        title="Enter Amount of the Quote Asset"
+                        type="number"
+                        inputmode="decimal"
+                        min="0"
+                        placeholder="0.0"
+                        class="px-3 py-2 h-9 text-sm font-medium bg-transparent text-white rounded-md"
+                        on:input=move |ev| set_seed_y.set(event_target_value(&ev))
+                    />
+                    <p class="text-xs text-muted-foreground">
+                        "Deposited around the active price in the same transaction as the new pool."
+                    </p>
+                </div>
+
                 <button
                     type="submit"
                     class="w-full px-3 h-10 bg-primary text-primary-foreground text-sm rounded-md border-none"
@@ -306,6 +387,15 @@ pub fn PoolCreator() -> impl IntoView {
                 >
                     Create Pool
                 </button>
+                {move || {
+                    create_lb_pair
+                        .value()
+                        .get()
+                        .and_then(Result::err)
+                        .map(|error| {
+                            view! { <ErrorMessage error /> }
+                        })
+                }}
             </div>
 
         </form>
@@ -19,17 +19,19 @@ use lucide_leptos::Plus;
 use tracing::{debug, error, info};
 
 mod add_liquidity;
+mod rebalance;
 mod remove_liquidity;
 
 pub use add_liquidity::AddLiquidity;
-pub use remove_liquidity::RemoveLiquidity;
+pub use rebalance::Rebalance;
+pub use remove_liquidity::{remove_liquidity_from_pools, RemoveLiquidity, Withdrawal};
 
 #[component]
 pub fn PoolManager() -> impl IntoView {
     info!("rendering <PoolManager/>");
 
     let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
-    let keplr = use_context::<KeplrSignals>().expect("keplr signals context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
     let token_map = use_context::<TokenMap>().expect("tokens context missing!");
 
     let lb_pair = use_context::<LocalResource<Result<LbPair, Error>>>()
@@ -257,6 +259,20 @@ pub fn PoolManager() -> impl IntoView {
                     >
                         "Remove Liquidity"
                     </button>
+                    <button
+                        class="py-1.5 px-3 rounded-sm border-none h-8 w-full"
+                        class=(
+                            ["bg-background", "text-foreground"],
+                            move || selected_tab.get() == "rebalance",
+                        )
+                        class=(
+                            ["bg-muted", "text-muted-foreground"],
+                            move || selected_tab.get() != "rebalance",
+                        )
+                        on:click=move |_| selected_tab.set("rebalance")
+                    >
+                        "Rebalance"
+                    </button>
                 </div>
                 // Container for the component based on selected tab
                 <div class="liquidity-group">
@@ -264,6 +280,7 @@ pub fn PoolManager() -> impl IntoView {
                     {move || match selected_tab.get() {
                         "add" => view! { <AddLiquidity /> }.into_any(),
                         "remove" => view! { <RemoveLiquidity /> }.into_any(),
+                        "rebalance" => view! { <Rebalance /> }.into_any(),
                         _ => {
                             view! {
                                 // Default case
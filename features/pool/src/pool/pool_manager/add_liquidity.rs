@@ -1,7 +1,8 @@
 // #![allow(unused)]
 
 use crate::state::{PoolState, PoolStateStoreFields};
-use ammber_core::{prelude::*, state::*, Error};
+use ammber_components::ErrorMessage;
+use ammber_core::{allowance, position, prelude::*, state::*, Error, TxKind, TxManager, TxSummary};
 use ammber_sdk::{
     constants::liquidity_config::{
         LiquidityConfigurations, LiquidityShape, BID_ASK, CURVE, SPOT_UNIFORM, WIDE,
@@ -14,7 +15,7 @@ use ammber_sdk::{
     utils::*,
 };
 use cosmwasm_std::{Addr, ContractInfo, Uint128, Uint64};
-use keplr::Keplr;
+use keplr::wallet;
 use leptos::{logging::*, prelude::*};
 use leptos_router::{
     hooks::{query_signal_with_options, use_params_map},
@@ -22,18 +23,7 @@ use leptos_router::{
 };
 use liquidity_book::libraries::{PriceHelper, U128x128Math};
 use reactive_stores::Store;
-use rsecret::{
-    query::tendermint::TendermintQuerier,
-    secret_client::{CreateTxSenderOptions, TxDecrypter},
-    tx::ComputeServiceClient,
-    TxOptions,
-};
-use secretrs::{
-    compute::{MsgExecuteContract, MsgExecuteContractResponse},
-    tx::Msg,
-    AccountId,
-};
-use std::str::FromStr;
+use rsecret::{query::tendermint::TendermintQuerier, TxOptions};
 use tonic_web_wasm_client::Client;
 use tracing::{debug, info};
 use web_sys::MouseEvent;
@@ -50,7 +40,8 @@ pub fn AddLiquidity() -> impl IntoView {
 
     let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
     let chain_id = use_context::<ChainId>().expect("chain_id context missing!");
-    let keplr = use_context::<KeplrSignals>().expect("keplr signals context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
     let token_map = use_context::<TokenMap>().expect("tokens context missing!");
 
     let token_a = move || pool.token_x().get().contract_address;
@@ -233,22 +224,7 @@ pub fn AddLiquidity() -> impl IntoView {
                     return Err(Error::generic("Amounts must not be 0!"));
                 }
 
-                let key = Keplr::get_key(&chain_id).await?;
-                // let wallet = Keplr::get_offline_signer_only_amino(&chain_id);
-                let wallet = Keplr::get_offline_signer(&chain_id);
-                let enigma_utils = Keplr::get_enigma_utils(&chain_id).into();
-
-                // TODO: I need to make this type use Strings instead of &'static str, because the
-                // values are not static in this application (user is able to set them to anything).
-                let options = CreateTxSenderOptions {
-                    url,
-                    chain_id,
-                    wallet: wallet.into(),
-                    wallet_address: key.bech32_address.clone().into(),
-                    enigma_utils,
-                };
-                let wasm_web_client = tonic_web_wasm_client::Client::new(url.to_string());
-                let compute_service_client = ComputeServiceClient::new(wasm_web_client, options);
+                let key = wallet::active().get_key(&chain_id).await?;
 
                 // Recheck the latest block height to update the deadline.
                 let tendermint = TendermintQuerier::new(Client::new(url.to_string()));
@@ -268,86 +244,42 @@ pub fn AddLiquidity() -> impl IntoView {
 
                 debug!("{lb_router_contract:?}");
 
-                // NOTE: here we are encrypting the messages manually so we can broadcast them all
-                // together. (The client doesn't have a way to handle this internally yet)
-
-                let increase_x_allowance_msg = MsgExecuteContract {
-                    sender: AccountId::from_str(key.bech32_address.as_ref())?,
-                    contract: AccountId::from_str(liquidity_parameters.token_x.address().as_str())?,
-                    msg: compute_service_client
-                        .encrypt(
-                            &liquidity_parameters.token_x.code_hash(),
-                            &secret_toolkit_snip20::HandleMsg::IncreaseAllowance {
-                                spender: lb_router_contract.address.to_string(),
-                                amount: liquidity_parameters.amount_x.clone(),
-                                expiration: None,
-                                padding: None,
-                            },
-                        )
-                        .await?
-                        .into_inner(),
-                    sent_funds: vec![],
-                };
-
-                let increase_y_allowance_msg = MsgExecuteContract {
-                    sender: AccountId::from_str(key.bech32_address.as_ref())?,
-                    contract: AccountId::from_str(liquidity_parameters.token_y.address().as_str())?,
-                    msg: compute_service_client
-                        .encrypt(
-                            &liquidity_parameters.token_y.code_hash(),
-                            &secret_toolkit_snip20::HandleMsg::IncreaseAllowance {
-                                spender: lb_router_contract.address.to_string(),
-                                amount: liquidity_parameters.amount_y.clone(),
-                                expiration: None,
-                                padding: None,
-                            },
-                        )
-                        .await?
-                        .into_inner(),
-                    sent_funds: vec![],
-                };
-
-                let add_liquidity_msg = MsgExecuteContract {
-                    sender: AccountId::from_str(key.bech32_address.as_ref())?,
-                    contract: AccountId::from_str(lb_router_contract.address.as_ref())?,
-                    msg: compute_service_client
-                        .encrypt(
-                            &lb_router_contract.code_hash,
-                            &lb_router::ExecuteMsg::AddLiquidity {
-                                liquidity_parameters,
-                            },
-                        )
-                        .await?
-                        .into_inner(),
-                    sent_funds: vec![],
-                };
-
-                let tx_options = TxOptions {
-                    gas_limit: 1_000_000,
-                    ..Default::default()
-                };
-
-                let tx = compute_service_client
-                    .broadcast(
-                        vec![
-                            increase_x_allowance_msg,
-                            increase_y_allowance_msg,
-                            add_liquidity_msg,
-                        ],
-                        tx_options,
+                let token_x = token_contract(&liquidity_parameters.token_x)?;
+                let token_y = token_contract(&liquidity_parameters.token_y)?;
+                let amount_x = liquidity_parameters.amount_x;
+                let amount_y = liquidity_parameters.amount_y;
+
+                let summary = TxSummary::new(TxKind::AddLiquidity);
+                let mut batch = tx_manager
+                    .batch(&key.bech32_address, summary)
+                    .with_token(token_x.address.to_string(), amount_x)
+                    .with_token(token_y.address.to_string(), amount_y);
+
+                // the allowances and the deposit are signed together, so the router is never left
+                // with an allowance it didn't use
+                allowance::ensure(&mut batch, &token_x, lb_router_contract, amount_x).await?;
+                allowance::ensure(&mut batch, &token_y, lb_router_contract, amount_y).await?;
+                let add_liquidity_index = batch
+                    .push(
+                        lb_router_contract,
+                        &lb_router::ExecuteMsg::AddLiquidity {
+                            liquidity_parameters,
+                        },
                     )
-                    .await
-                    .map_err(Error::from)
-                    .inspect(|tx_response| info!("{tx_response:?}"))
-                    .inspect_err(|error| error!("{error}"))?;
+                    .await?;
+
+                let tx = batch.submit(TxOptions::default()).await?;
 
-                if tx.code != 0 {
-                    error!("{}", tx.raw_log);
+                // the new bins may be outside the area the cached search covers
+                if let Ok(lb_pair) = lb_pair.await {
+                    position::forget(&lb_pair.contract, &key.bech32_address);
                 }
 
-                let data = MsgExecuteContractResponse::from_any(&tx.data[0])
-                    .inspect_err(|e| error! {"{e}"})?
-                    .data;
+                let data = tx
+                    .data
+                    .get(add_liquidity_index)
+                    .cloned()
+                    .unwrap_or_default();
                 let add_liquidity_response = serde_json::from_slice::<AddLiquidityResponse>(&data)?;
 
                 debug!("X: {}", add_liquidity_response.amount_x_added);
@@ -559,6 +491,15 @@ pub fn AddLiquidity() -> impl IntoView {
                 >
                     "Add Liquidity"
                 </button>
+                {move || {
+                    add_liquidity_action
+                        .value()
+                        .get()
+                        .and_then(Result::err)
+                        .map(|error| {
+                            view! { <ErrorMessage error /> }
+                        })
+                }}
             </Show>
         </div>
     }
@@ -0,0 +1,361 @@
+use ammber_components::ErrorMessage;
+use ammber_core::{
+    allowance, limit_order,
+    position::{self, Position},
+    prelude::*,
+    state::*,
+    support::ILbPair,
+    Error, TxKind, TxManager, TxSummary,
+};
+use ammber_sdk::{
+    constants::liquidity_config::{LiquidityConfigurations, LiquidityShape},
+    contract_interfaces::{
+        lb_pair::LbPair,
+        lb_router::{self, AddLiquidityResponse, LiquidityParameters, RemoveLiquidityResponse},
+    },
+};
+use cosmwasm_std::Uint256;
+use keplr::wallet;
+use leptos::prelude::*;
+use rsecret::{query::tendermint::TendermintQuerier, TxOptions};
+use tonic_web_wasm_client::Client;
+use tracing::{debug, info};
+
+/// The widest deposit offered, in bins on either side of the active bin.
+const MAX_RADIUS: u32 = 49;
+
+/// Withdraws `position` and deposits it again around the active bin, in a single transaction.
+///
+/// `amount_slippage` is in basis points and applies to both legs: the withdrawal must return at
+/// least that much less than expected, and only that minimum is deposited again, so the deposit
+/// can never ask for more than the withdrawal returned. Whatever is left over stays in the wallet.
+pub async fn rebalance(
+    tx_manager: TxManager,
+    url: &str,
+    lb_pair: LbPair,
+    position: Position,
+    liq: LiquidityConfigurations,
+    amount_slippage: u128,
+    id_slippage: u32,
+) -> Result<(RemoveLiquidityResponse, AddLiquidityResponse), Error> {
+    if position.ids.is_empty() {
+        return Err(Error::generic("You have no liquidity in this pool"));
+    }
+
+    let key = wallet::active().get_key(CHAIN_ID).await?;
+
+    // the deposit is centred on the bin that is active now, not when the page loaded
+    let active_id = ILbPair(lb_pair.contract.clone()).get_active_id().await?;
+
+    // Recheck the latest block height to update the deadline.
+    let tendermint = TendermintQuerier::new(Client::new(url.to_string()));
+    let latest_block_time = tendermint
+        .get_latest_block()
+        .await
+        .map(|block| block.header.time.unix_timestamp() as u64)
+        .map_err(Error::from)?;
+
+    let token_x = token_contract(&lb_pair.token_x)?;
+    let token_y = token_contract(&lb_pair.token_y)?;
+
+    // slippage is expressed in basis points (1 = 0.01%)
+    let amount_x_min = position
+        .amount_x
+        .multiply_ratio(10_000 - amount_slippage, 10_000u128);
+    let amount_y_min = position
+        .amount_y
+        .multiply_ratio(10_000 - amount_slippage, 10_000u128);
+
+    let remove = lb_router::ExecuteMsg::RemoveLiquidity {
+        token_x: token_x.clone(),
+        token_y: token_y.clone(),
+        bin_step: lb_pair.bin_step,
+        amount_x_min,
+        amount_y_min,
+        ids: position.ids,
+        amounts: position.amounts,
+        to: key.bech32_address.clone(),
+        deadline: (latest_block_time + 100).into(),
+    };
+
+    let liquidity_parameters = LiquidityParameters {
+        token_x: lb_pair.token_x.clone(),
+        token_y: lb_pair.token_y.clone(),
+        bin_step: lb_pair.bin_step,
+        amount_x: amount_x_min,
+        amount_y: amount_y_min,
+        amount_x_min: amount_x_min.multiply_ratio(10_000 - amount_slippage, 10_000u128),
+        amount_y_min: amount_y_min.multiply_ratio(10_000 - amount_slippage, 10_000u128),
+        active_id_desired: active_id,
+        id_slippage,
+        delta_ids: liq.delta_ids(),
+        distribution_x: liq.distribution_x(),
+        distribution_y: liq.distribution_y(),
+        to: key.bech32_address.clone(),
+        refund_to: key.bech32_address.clone(),
+        deadline: (latest_block_time + 100).into(),
+    };
+
+    debug!("{remove:#?}");
+    debug!("{liquidity_parameters:#?}");
+
+    let lb_router_contract = &LB_ROUTER;
+
+    let summary = TxSummary::new(TxKind::Rebalance);
+    let mut batch = tx_manager
+        .batch(&key.bech32_address, summary)
+        .with_token(token_x.address.to_string(), amount_x_min)
+        .with_token(token_y.address.to_string(), amount_y_min);
+
+    // the withdrawal runs first, so the deposit spends the tokens it returned
+    let remove_index = batch.push(lb_router_contract, &remove).await?;
+    allowance::ensure(&mut batch, &token_x, lb_router_contract, amount_x_min).await?;
+    allowance::ensure(&mut batch, &token_y, lb_router_contract, amount_y_min).await?;
+    let add_index = batch
+        .push(
+            lb_router_contract,
+            &lb_router::ExecuteMsg::AddLiquidity {
+                liquidity_parameters,
+            },
+        )
+        .await?;
+
+    let tx = batch.submit(TxOptions::default()).await?;
+
+    // the position moved, so the cached bins would send the next search to the old ones
+    position::forget(&lb_pair.contract, &key.bech32_address);
+
+    let data = |index: usize| tx.data.get(index).cloned().unwrap_or_default();
+    let removed = serde_json::from_slice::<RemoveLiquidityResponse>(&data(remove_index))?;
+    let added = serde_json::from_slice::<AddLiquidityResponse>(&data(add_index))?;
+
+    Ok((removed, added))
+}
+
+#[component]
+pub fn Rebalance() -> impl IntoView {
+    info!("rendering <Rebalance/>");
+
+    on_cleanup(move || {
+        info!("cleaning up <Rebalance/>");
+    });
+
+    let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
+
+    let lb_pair = use_context::<LocalResource<Result<LbPair, Error>>>()
+        .expect("missing the LbPair resource context");
+    let active_id = use_context::<LocalResource<Result<u32, Error>>>()
+        .expect("missing the active_id resource context");
+    let my_liquidity = use_context::<LocalResource<Result<(Vec<u32>, Vec<Uint256>), Error>>>()
+        .expect("missing the my_liquidity context");
+
+    let (liquidity_shape, set_liquidity_shape) = signal(LiquidityShape::SpotUniform);
+    let (radius, set_radius) = signal(5);
+    let (amount_slippage, set_amount_slippage) = signal(50u128);
+    let (price_slippage, set_price_slippage) = signal(100u32);
+
+    let current = LocalResource::new(move || async move {
+        let key = keplr.key.await?;
+        let lb_pair = lb_pair.await?;
+        let (ids, amounts) = my_liquidity.await?;
+        // limit orders in the pair stay where they are, and are withdrawn from the trade page
+        let (ids, amounts) =
+            limit_order::exclude_orders(&key.bech32_address, &lb_pair.contract, ids, amounts);
+
+        position::from_balances(&lb_pair.contract, ids, amounts).await
+    });
+
+    let rebalance_action = Action::new_local(move |_: &()| {
+        let url = endpoint.get();
+        let shape = liquidity_shape.get_untracked();
+        let radius = radius.get_untracked().min(MAX_RADIUS);
+        let amount_slippage = amount_slippage.get_untracked();
+        let price_slippage = price_slippage.get_untracked();
+
+        async move {
+            let lb_pair = lb_pair.await?;
+            let active_id = active_id.await?;
+            let position = current.await?;
+
+            // the delta ids are relative, so the target only matters for display
+            let liq = LiquidityConfigurations::by_radius(active_id, radius, shape);
+            let id_slippage = price_slippage / lb_pair.bin_step as u32;
+
+            let (removed, added) = rebalance(
+                tx_manager,
+                &url,
+                lb_pair,
+                position,
+                liq,
+                amount_slippage,
+                id_slippage,
+            )
+            .await?;
+
+            debug!(
+                "X received: {}, Y received: {}",
+                removed.amount_x, removed.amount_y
+            );
+            debug!("X: {}, Y: {}", added.amount_x_added, added.amount_y_added);
+
+            Ok::<(), Error>(())
+        }
+    });
+
+    let summary = move || {
+        Suspend::new(async move {
+            let (position, active_id, lb_pair) =
+                match (current.await, active_id.await, lb_pair.await) {
+                    (Ok(position), Ok(active_id), Ok(lb_pair)) => (position, active_id, lb_pair),
+                    (Err(error), ..) | (_, Err(error), _) | (.., Err(error)) => {
+                        return view! { <ErrorMessage error /> }.into_any();
+                    }
+                };
+
+            if position.ids.is_empty() {
+                return view! {
+                    <p class="text-sm text-muted-foreground">"You have no liquidity in this pool"</p>
+                }
+                .into_any();
+            }
+
+            let decimals_x = get_token_decimals(lb_pair.token_x.address().as_str()).unwrap_or(0);
+            let decimals_y = get_token_decimals(lb_pair.token_y.address().as_str()).unwrap_or(0);
+            let status = if position.is_in_range(active_id) {
+                "In range"
+            } else {
+                "Out of range"
+            };
+
+            view! {
+                <div class="text-sm space-y-1">
+                    <div class="flex justify-between">
+                        <span class="text-muted-foreground">"Current bins"</span>
+                        <span>
+                            {format!(
+                                "{} - {} ({status})",
+                                position.ids.first().unwrap(),
+                                position.ids.last().unwrap(),
+                            )}
+                        </span>
+                    </div>
+                    <div class="flex justify-between">
+                        <span class="text-muted-foreground">"New bins"</span>
+                        <span>
+                            {move || {
+                                format!(
+                                    "{} - {}",
+                                    active_id.saturating_sub(radius.get()),
+                                    active_id.saturating_add(radius.get()),
+                                )
+                            }}
+                        </span>
+                    </div>
+                    <div class="flex justify-between">
+                        <span class="text-muted-foreground">"Withdraws about"</span>
+                        <span>
+                            {format!(
+                                "{} X + {} Y",
+                                display_token_amount(position.amount_x.u128(), decimals_x),
+                                display_token_amount(position.amount_y.u128(), decimals_y),
+                            )}
+                        </span>
+                    </div>
+                </div>
+            }
+            .into_any()
+        })
+    };
+
+    view! {
+        <div class="space-y-2">
+            <div class="text-base font-semibold">"Rebalance"</div>
+            <p class="m-0 text-xs text-muted-foreground">
+                "Withdraws your position and deposits it again around the current price, in one
+                transaction."
+            </p>
+
+            <Suspense fallback=|| view! { <div class="text-sm">"Loading..."</div> }>{summary}</Suspense>
+
+            <div class="text-base font-semibold !mt-6">Choose Liquidity Shape</div>
+            <select
+                class="font-medium py-2 px-4 text-sm bg-card rounded-md"
+                on:change=move |ev| set_liquidity_shape.set(event_target_value(&ev).into())
+            >
+                <option value="uniform">"Spot/Uniform"</option>
+                <option value="curve">"Curve"</option>
+            </select>
+
+            <div class="grid grid-cols-3 gap-2 !mt-6">
+                <div>
+                    <label class="block mb-1 text-xs" for="rebalance-radius">
+                        "Radius"
+                    </label>
+                    <input
+                        class="w-full px-3 py-2 h-9 bg-transparent rounded-md"
+                        id="rebalance-radius"
+                        type="number"
+                        min="0"
+                        max=MAX_RADIUS
+                        prop:value=move || radius.get()
+                        on:change=move |ev| {
+                            set_radius
+                                .set(event_target_value(&ev).parse::<u32>().unwrap_or(5).min(MAX_RADIUS))
+                        }
+                    />
+                </div>
+                <div>
+                    <label class="block mb-1 text-xs" for="rebalance-amount-slippage">
+                        "Amount slippage (bps)"
+                    </label>
+                    <input
+                        class="w-full px-3 py-2 h-9 bg-transparent rounded-md"
+                        id="rebalance-amount-slippage"
+                        type="number"
+                        min="0"
+                        max="10000"
+                        prop:value=move || amount_slippage.get()
+                        on:change=move |ev| {
+                            set_amount_slippage
+                                .set(event_target_value(&ev).parse::<u128>().unwrap_or(50).min(10_000))
+                        }
+                    />
+                </div>
+                <div>
+                    <label class="block mb-1 text-xs" for="rebalance-price-slippage">
+                        "Price slippage (bps)"
+                    </label>
+                    <input
+                        class="w-full px-3 py-2 h-9 bg-transparent rounded-md"
+                        id="rebalance-price-slippage"
+                        type="number"
+                        min="0"
+                        prop:value=move || price_slippage.get()
+                        on:change=move |ev| {
+                            set_price_slippage.set(event_target_value(&ev).parse::<u32>().unwrap_or(100))
+                        }
+                    />
+                </div>
+            </div>
+
+            <button
+                class="w-full py-2 px-6 !mt-6 bg-primary active:brightness-90 text-primary-foreground text-sm font-medium rounded-md"
+                disabled=move || rebalance_action.pending().get()
+                on:click=move |_| _ = rebalance_action.dispatch(())
+            >
+                "Rebalance"
+            </button>
+            {move || {
+                rebalance_action
+                    .value()
+                    .get()
+                    .and_then(Result::err)
+                    .map(|error| {
+                        view! { <ErrorMessage error /> }
+                    })
+            }}
+        </div>
+    }
+}
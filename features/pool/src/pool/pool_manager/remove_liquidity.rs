@@ -1,15 +1,16 @@
 #![allow(unused)]
 
+use ammber_components::ErrorMessage;
 use ammber_core::state::*;
 use ammber_core::support::{chain_query, ILbPair, Querier, COMPUTE_QUERIER};
-use ammber_core::{prelude::*, Error};
+use ammber_core::{permit, position, prelude::*, Error, TxKind, TxManager, TxSummary};
 use ammber_sdk::contract_interfaces::{
     lb_pair::{self, LbPair},
     lb_router,
 };
 use cosmwasm_std::{Addr, ContractInfo, Uint128, Uint256, Uint64};
 use ethnum::U256;
-use keplr::Keplr;
+use keplr::wallet;
 use leptos::prelude::*;
 use leptos_router::{
     hooks::{use_params, use_params_map},
@@ -22,17 +23,8 @@ use liquidity_book::libraries::{
     },
     PriceHelper,
 };
-use rsecret::{
-    query::tendermint::TendermintQuerier,
-    secret_client::{CreateTxSenderOptions, TxDecrypter},
-    tx::ComputeServiceClient,
-    TxOptions,
-};
+use rsecret::{query::tendermint::TendermintQuerier, TxOptions};
 use secret_toolkit_snip20::TokenInfoResponse;
-use secretrs::compute::MsgExecuteContractResponse;
-use secretrs::tx::Msg;
-use secretrs::{compute::MsgExecuteContract, AccountId};
-use std::str::FromStr;
 use tonic_web_wasm_client::Client;
 use tracing::{debug, error, info};
 
@@ -46,7 +38,8 @@ pub fn RemoveLiquidity() -> impl IntoView {
 
     let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
     let chain_id = use_context::<ChainId>().expect("chain_id context missing!");
-    let keplr = use_context::<KeplrSignals>().expect("keplr signals context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
     let token_map = use_context::<TokenMap>().expect("tokens context missing!");
 
     let params = use_params_map();
@@ -128,47 +121,10 @@ pub fn RemoveLiquidity() -> impl IntoView {
                 return Err(Error::generic("active id is missing!"));
             };
 
-            let mut ids = vec![];
-            let radius = 49;
-
-            for i in 0..(radius * 2 + 1) {
-                let offset_id = if i < radius {
-                    id - (radius - i) as u32 // Subtract for the first half
-                } else {
-                    id + (i - radius) as u32 // Add for the second half
-                };
-
-                ids.push(offset_id);
-            }
-
-            debug!("{:?}", ids);
-
-            let key = Keplr::get_key(&chain_id).await?;
+            let key = wallet::active().get_key(&chain_id).await?;
             let account = key.bech32_address;
 
-            let accounts = vec![account.clone(); ids.len()];
-
-            let balances: Vec<Uint256> = ILbPair(lb_pair.contract.clone())
-                .balance_of_batch(accounts, ids.clone())
-                .await?;
-            // .and_then(|vec| {
-            //     vec.iter()
-            //         .map(|el| {
-            //             PriceHelper::convert128x128_price_to_decimal(el.uint256_to_u256())
-            //         })
-            //         .collect::<Result<Vec<U256>, U256x256MathError>>()
-            //         .map_err(|e| Error::generic(e.to_string()))
-            // .map(|hmm| {
-            //     hmm.into_iter()
-            //         .map(|el| el.u256_to_uint256())
-            //         .collect::<Vec<Uint256>>()
-            // })
-            // .map(|hmm| {
-            //     hmm.into_iter()
-            //         .map(|el| el.to_string())
-            //         .collect::<Vec<String>>()
-            // })
-            // });
+            let (ids, balances) = position::discover(&lb_pair.contract, &account, id).await?;
 
             debug!("{:?}", balances);
 
@@ -214,8 +170,8 @@ pub fn RemoveLiquidity() -> impl IntoView {
         // and the total supply of lb-tokens.
 
         let message = lb_router::ExecuteMsg::RemoveLiquidity {
-            token_x: token_x.into_contract_info().unwrap(),
-            token_y: token_y.into_contract_info().unwrap(),
+            token_x: token_contract(&token_x)?,
+            token_y: token_contract(&token_y)?,
             bin_step,
             amount_x_min: amount_x.into(),
             amount_y_min: amount_y.into(),
@@ -235,8 +191,6 @@ pub fn RemoveLiquidity() -> impl IntoView {
         // let url = endpoint.get();
         // let chain_id = chain_id.get();
         let url = NODE;
-        let chain_id = CHAIN_ID;
-        let lb_router_contract = &LB_ROUTER;
 
         async move {
             let amount_x = amount_x.get_untracked();
@@ -246,12 +200,8 @@ pub fn RemoveLiquidity() -> impl IntoView {
                 return Err(Error::generic("lb pair information is missing!"));
             };
 
-            let token_x = lb_pair.token_x;
-            let token_y = lb_pair.token_y;
-            let bin_step = lb_pair.bin_step;
-
-            let decimals_x = get_token_decimals(token_x.address().as_str())?;
-            let decimals_y = get_token_decimals(token_y.address().as_str())?;
+            let decimals_x = get_token_decimals(lb_pair.token_x.address().as_str())?;
+            let decimals_y = get_token_decimals(lb_pair.token_y.address().as_str())?;
 
             let amount_x = parse_token_amount(amount_x, decimals_x);
             let amount_y = parse_token_amount(amount_y, decimals_y);
@@ -269,87 +219,21 @@ pub fn RemoveLiquidity() -> impl IntoView {
                 .filter(|(_, amount)| *amount != Uint256::zero())
                 .unzip();
 
-            // let Ok(liquidity_parameters) = liquidity_parameters else {
-            //     return Err(Error::generic(
-            //         "Something is wrong with the liquidity parameters!",
-            //     ));
-            // };
-
-            let key = Keplr::get_key(&chain_id).await?;
-            // let wallet = Keplr::get_offline_signer_only_amino(&chain_id);
-            let wallet = Keplr::get_offline_signer(&chain_id);
-            let enigma_utils = Keplr::get_enigma_utils(&chain_id).into();
-
-            // TODO: I need to make this type use Strings instead of &'static str, because the
-            // values are not static in this application (user is able to set them to anything).
-            let options = CreateTxSenderOptions {
-                url,
-                chain_id,
-                wallet: wallet.into(),
-                wallet_address: key.bech32_address.clone().into(),
-                enigma_utils,
-            };
-            let wasm_web_client = tonic_web_wasm_client::Client::new(url.to_string());
-            let compute_service_client = ComputeServiceClient::new(wasm_web_client, options);
-
-            // Recheck the latest block height to update the deadline.
-            let tendermint = TendermintQuerier::new(Client::new(url.to_string()));
-            let latest_block_time = tendermint
-                .get_latest_block()
-                .await
-                .map(|block| block.header.time.unix_timestamp() as u64)
-                .map_err(Error::from)?;
-
-            let msg = lb_router::ExecuteMsg::RemoveLiquidity {
-                token_x: token_x.into_contract_info().unwrap(),
-                token_y: token_y.into_contract_info().unwrap(),
-                bin_step,
-                amount_x_min: amount_x.into(),
-                amount_y_min: amount_y.into(),
+            let withdrawal = Withdrawal {
+                lb_pair,
                 ids,
                 amounts,
-                to: key.bech32_address.clone(),
-                deadline: (latest_block_time + 100).into(),
-            };
-
-            debug!("{msg:#?}");
-
-            let remove_liquidity_msg = MsgExecuteContract {
-                sender: AccountId::from_str(key.bech32_address.as_ref())?,
-                contract: AccountId::from_str(lb_router_contract.address.as_ref())?,
-                msg: compute_service_client
-                    .encrypt(&lb_router_contract.code_hash, &msg)
-                    .await?
-                    .into_inner(),
-                sent_funds: vec![],
-            };
-
-            let tx_options = TxOptions {
-                gas_limit: 1_000_000,
-                ..Default::default()
+                amount_x_min: amount_x.into(),
+                amount_y_min: amount_y.into(),
             };
 
-            let tx = compute_service_client
-                .broadcast(vec![remove_liquidity_msg], tx_options)
-                .await
-                .map_err(Error::from)
-                .inspect(|tx_response| info!("{tx_response:?}"))
-                .inspect_err(|error| error!("{error}"))?;
+            let responses = remove_liquidity_from_pools(tx_manager, url, vec![withdrawal]).await?;
 
-            if tx.code != 0 {
-                error!("{}", tx.raw_log);
+            for response in responses {
+                debug!("X received: {}", response.amount_x);
+                debug!("Y received: {}", response.amount_y);
             }
 
-            let data = MsgExecuteContractResponse::from_any(&tx.data[0])
-                .inspect_err(|e| error! {"{e}"})?
-                .data;
-
-            let remove_liquidity_response =
-                serde_json::from_slice::<lb_router::RemoveLiquidityResponse>(&data)?;
-
-            debug!("X received: {}", remove_liquidity_response.amount_x);
-            debug!("Y received: {}", remove_liquidity_response.amount_y);
-
             Ok(())
         }
     });
@@ -369,6 +253,89 @@ pub fn RemoveLiquidity() -> impl IntoView {
             >
                 "Remove Liquidity"
             </button>
+            {move || {
+                remove_liquidity
+                    .value()
+                    .get()
+                    .and_then(Result::err)
+                    .map(|error| {
+                        view! { <ErrorMessage error /> }
+                    })
+            }}
         </div>
     }
 }
+
+/// Liquidity to withdraw from one pool.
+#[derive(Clone, Debug)]
+pub struct Withdrawal {
+    pub lb_pair: LbPair,
+    pub ids: Vec<u32>,
+    /// Expressed in LB tokens, one per id.
+    pub amounts: Vec<Uint256>,
+    pub amount_x_min: Uint128,
+    pub amount_y_min: Uint128,
+}
+
+/// Withdraws from every pool in `withdrawals` in a single transaction. Either all of them go
+/// through or none do.
+///
+/// Returns what the router sent back for each pool, in the same order.
+pub async fn remove_liquidity_from_pools(
+    tx_manager: TxManager,
+    url: &str,
+    withdrawals: Vec<Withdrawal>,
+) -> Result<Vec<lb_router::RemoveLiquidityResponse>, Error> {
+    let lb_router_contract = &LB_ROUTER;
+
+    let key = wallet::active().get_key(CHAIN_ID).await?;
+
+    // Recheck the latest block height to update the deadline.
+    let tendermint = TendermintQuerier::new(Client::new(url.to_string()));
+    let latest_block_time = tendermint
+        .get_latest_block()
+        .await
+        .map(|block| block.header.time.unix_timestamp() as u64)
+        .map_err(Error::from)?;
+
+    let mut batch = tx_manager.batch(&key.bech32_address, TxSummary::new(TxKind::RemoveLiquidity));
+
+    for withdrawal in withdrawals {
+        let lb_pair = withdrawal.lb_pair;
+
+        batch = batch
+            .with_token(
+                lb_pair.token_x.address().to_string(),
+                withdrawal.amount_x_min,
+            )
+            .with_token(
+                lb_pair.token_y.address().to_string(),
+                withdrawal.amount_y_min,
+            );
+
+        let msg = lb_router::ExecuteMsg::RemoveLiquidity {
+            token_x: token_contract(&lb_pair.token_x)?,
+            token_y: token_contract(&lb_pair.token_y)?,
+            bin_step: lb_pair.bin_step,
+            amount_x_min: withdrawal.amount_x_min,
+            amount_y_min: withdrawal.amount_y_min,
+            ids: withdrawal.ids,
+            amounts: withdrawal.amounts,
+            to: key.bech32_address.clone(),
+            deadline: (latest_block_time + 100).into(),
+        };
+
+        debug!("{msg:#?}");
+
+        batch.push(lb_router_contract, &msg).await?;
+    }
+
+    let tx = batch.submit(TxOptions::default()).await?;
+
+    tx.data
+        .iter()
+        .map(|data| {
+            serde_json::from_slice::<lb_router::RemoveLiquidityResponse>(data).map_err(Error::from)
+        })
+        .collect()
+}
@@ -1,9 +1,11 @@
 mod keyboard_shortcuts;
+mod limit;
 mod routes;
 mod swap;
 mod swap_details;
 mod swap_settings;
 
+pub use limit::Limit;
 pub use routes::SwapRoutes;
 pub use swap::Swap;
 pub use swap_details::SwapDetails;
@@ -0,0 +1,501 @@
+use ammber_components::ErrorMessage;
+use ammber_core::{
+    allowance,
+    constants::{contracts::*, CHAIN_ID, SYMBOL_TO_ADDR, TOKEN_MAP},
+    limit_order::{self, FillStatus, LimitOrder},
+    position,
+    state::WalletSignals,
+    support::ILbPair,
+    utils::{
+        display_token_amount, get_token_decimals, get_token_symbol, parse_token_amount,
+        token_contract,
+    },
+    Account, Error, TxKind, TxManager, TxSummary,
+};
+use ammber_sdk::{
+    constants::liquidity_config::LiquidityConfigurations,
+    contract_interfaces::lb_router::{self, AddLiquidityResponse, LiquidityParameters},
+    utils::get_id_from_price,
+};
+use cosmwasm_std::{ContractInfo, Uint128, Uint64};
+use keplr::wallet;
+use leptos::prelude::*;
+use leptos_router::components::A;
+use liquidity_book::core::TokenType;
+use rsecret::TxOptions;
+use tracing::{debug, info};
+use web_sys::js_sys::Date;
+
+/// Orders expire from the router's point of view after this many seconds.
+const DEADLINE: u64 = 300;
+/// Single-bin deposits and withdrawals only lose to rounding, in basis points.
+const SLIPPAGE: u128 = 10;
+
+/// Switches between market swaps and limit orders.
+#[component]
+pub fn TradeTabs(limit: bool) -> impl IntoView {
+    let tab_class = move |selected: bool| {
+        if selected {
+            "w-full py-1.5 px-3 rounded-sm bg-background text-foreground border-none h-8 text-center no-underline"
+        } else {
+            "w-full py-1.5 px-3 rounded-sm bg-muted text-muted-foreground border-none h-8 text-center no-underline"
+        }
+    };
+
+    view! {
+        <div class="flex items-center justify-evenly gap-0.5 p-[5px] bg-muted rounded-md">
+            <A href="/liquidity-book-leptos/trade" attr:class=tab_class(!limit)>
+                "Swap"
+            </A>
+            <A href="/liquidity-book-leptos/trade/limit" attr:class=tab_class(limit)>
+                "Limit"
+            </A>
+        </div>
+    }
+}
+
+fn token_type(token: &ContractInfo) -> TokenType {
+    TokenType::CustomToken {
+        contract_addr: token.address.clone(),
+        token_code_hash: token.code_hash.clone(),
+    }
+}
+
+/// Places `amount` of `sell` in the bin closest to `price`, given in `buy` per `sell`.
+async fn place_order(
+    tx_manager: TxManager,
+    sell: ContractInfo,
+    buy: ContractInfo,
+    price: f64,
+    amount: Uint128,
+) -> Result<LimitOrder, Error> {
+    if price.is_nan() || price <= 0.0 {
+        return Err(Error::generic("Enter a price above 0"));
+    }
+    if amount.is_zero() {
+        return Err(Error::generic("Enter an amount to sell"));
+    }
+
+    let key = wallet::active().get_key(CHAIN_ID).await?;
+
+    // the finest bin step gives the closest price
+    let lb_pair = LB_FACTORY
+        .get_all_lb_pairs(token_type(&sell), token_type(&buy))
+        .await?
+        .into_iter()
+        .map(|information| information.lb_pair)
+        .min_by_key(|lb_pair| lb_pair.bin_step)
+        .ok_or(Error::generic("There is no pool for this pair"))?;
+
+    let token_x = token_contract(&lb_pair.token_x)?;
+    let token_y = token_contract(&lb_pair.token_y)?;
+    let sell_x = token_x.address == sell.address;
+
+    let decimals_x = get_token_decimals(token_x.address.as_str()).unwrap_or(0);
+    let decimals_y = get_token_decimals(token_y.address.as_str()).unwrap_or(0);
+    let decimals = 10f64.powi(decimals_y as i32 - decimals_x as i32);
+
+    // the pair prices token X in raw units of token Y
+    let pair_price = if sell_x { price } else { 1.0 / price } * decimals;
+    let bin_id = get_id_from_price(pair_price, lb_pair.bin_step);
+
+    let active_id = ILbPair(lb_pair.contract.clone()).get_active_id().await?;
+
+    if sell_x && bin_id <= active_id {
+        return Err(Error::generic(
+            "The price must be above the current price, or it would fill right away",
+        ));
+    }
+    if !sell_x && bin_id >= active_id {
+        return Err(Error::generic(
+            "The price must be below the current price, or it would fill right away",
+        ));
+    }
+
+    let (distribution_x, distribution_y) = if sell_x {
+        (vec![1.0], vec![0.0])
+    } else {
+        (vec![0.0], vec![1.0])
+    };
+    let liq = LiquidityConfigurations::new(
+        vec![bin_id as i64 - active_id as i64],
+        distribution_x,
+        distribution_y,
+    );
+
+    let (amount_x, amount_y) = if sell_x {
+        (amount, Uint128::zero())
+    } else {
+        (Uint128::zero(), amount)
+    };
+
+    let liquidity_parameters = LiquidityParameters {
+        token_x: lb_pair.token_x.clone(),
+        token_y: lb_pair.token_y.clone(),
+        bin_step: lb_pair.bin_step,
+        amount_x,
+        amount_y,
+        amount_x_min: amount_x.multiply_ratio(10_000 - SLIPPAGE, 10_000u128),
+        amount_y_min: amount_y.multiply_ratio(10_000 - SLIPPAGE, 10_000u128),
+        active_id_desired: active_id,
+        // the bin is relative to the active id, so it must not move
+        id_slippage: 0,
+        delta_ids: liq.delta_ids(),
+        distribution_x: liq.distribution_x(),
+        distribution_y: liq.distribution_y(),
+        to: key.bech32_address.clone(),
+        refund_to: key.bech32_address.clone(),
+        deadline: Uint64::new((Date::now() / 1000.0) as u64 + DEADLINE),
+    };
+
+    debug!("{liquidity_parameters:#?}");
+
+    let summary = TxSummary::new(TxKind::LimitOrder);
+    let mut batch = tx_manager
+        .batch(&key.bech32_address, summary)
+        .with_token(sell.address.to_string(), amount);
+
+    allowance::ensure(&mut batch, &sell, &LB_ROUTER, amount).await?;
+    let add_index = batch
+        .push(
+            &LB_ROUTER,
+            &lb_router::ExecuteMsg::AddLiquidity {
+                liquidity_parameters,
+            },
+        )
+        .await?;
+
+    let tx = batch.submit(TxOptions::default()).await?;
+
+    // the order owns only what it minted, not anything else the user holds in the bin
+    let data = tx.data.get(add_index).cloned().unwrap_or_default();
+    let added = serde_json::from_slice::<AddLiquidityResponse>(&data)?;
+    let liquidity = added
+        .liquidity_minted
+        .first()
+        .copied()
+        .ok_or(Error::generic("The deposit minted no liquidity"))?;
+
+    let order = LimitOrder {
+        lb_pair: lb_pair.contract,
+        token_x,
+        token_y,
+        bin_step: lb_pair.bin_step,
+        bin_id,
+        sell_x,
+        amount,
+        liquidity,
+        placed_at: Date::now(),
+    };
+    limit_order::remember(&key.bech32_address, order.clone());
+    // the order's bin may be outside the area the cached search covers
+    position::forget(&order.lb_pair, &key.bech32_address);
+
+    Ok(order)
+}
+
+/// Withdraws the liquidity of `order`, filled or not, and nothing else the user holds in its bin.
+async fn withdraw_order(tx_manager: TxManager, order: LimitOrder) -> Result<(), Error> {
+    let key = wallet::active().get_key(CHAIN_ID).await?;
+    let state = limit_order::state(&key.bech32_address, &order).await?;
+
+    if state.status == FillStatus::Closed {
+        limit_order::forget(&key.bech32_address, &order);
+        return Ok(());
+    }
+
+    let msg = lb_router::ExecuteMsg::RemoveLiquidity {
+        token_x: order.token_x.clone(),
+        token_y: order.token_y.clone(),
+        bin_step: order.bin_step,
+        amount_x_min: state.amount_x.multiply_ratio(10_000 - SLIPPAGE, 10_000u128),
+        amount_y_min: state.amount_y.multiply_ratio(10_000 - SLIPPAGE, 10_000u128),
+        ids: vec![order.bin_id],
+        amounts: vec![state.liquidity],
+        to: key.bech32_address.clone(),
+        deadline: Uint64::new((Date::now() / 1000.0) as u64 + DEADLINE),
+    };
+
+    let summary = TxSummary::new(TxKind::RemoveLiquidity);
+    let mut batch = tx_manager
+        .batch(&key.bech32_address, summary)
+        .with_token(order.token_x.address.to_string(), state.amount_x)
+        .with_token(order.token_y.address.to_string(), state.amount_y);
+    batch.push(&LB_ROUTER, &msg).await?;
+    batch.submit(TxOptions::default()).await?;
+
+    limit_order::forget(&key.bech32_address, &order);
+
+    Ok(())
+}
+
+#[component]
+pub fn Limit() -> impl IntoView {
+    info!("rendering <Limit/>");
+
+    on_cleanup(move || {
+        info!("cleaning up <Limit/>");
+    });
+
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
+    let account = use_context::<Account>().expect("account context missing!");
+
+    let sell_token = RwSignal::new(SYMBOL_TO_ADDR.get("AMBER").cloned().unwrap_or_default());
+    let buy_token = RwSignal::new(SYMBOL_TO_ADDR.get("SSCRT").cloned().unwrap_or_default());
+    let (price, set_price) = signal(String::new());
+    let (amount, set_amount) = signal(String::new());
+
+    let place = Action::new_local(move |_: &()| {
+        let sell = TOKEN_MAP.get(&sell_token.get()).map(ContractInfo::from);
+        let buy = TOKEN_MAP.get(&buy_token.get()).map(ContractInfo::from);
+        let price = price.get().parse::<f64>().unwrap_or_default();
+        let amount = amount.get();
+
+        async move {
+            let (Some(sell), Some(buy)) = (sell, buy) else {
+                return Err(Error::generic("Select two tokens"));
+            };
+            if sell == buy {
+                return Err(Error::generic("Select two different tokens"));
+            }
+
+            let decimals = get_token_decimals(sell.address.as_str()).unwrap_or(0);
+            let amount = Uint128::new(parse_token_amount(amount, decimals));
+
+            place_order(tx_manager, sell, buy, price, amount).await
+        }
+    });
+
+    let withdraw =
+        Action::new_local(move |order: &LimitOrder| withdraw_order(tx_manager, order.clone()));
+
+    let orders = LocalResource::new(move || {
+        // reload after an order is placed or withdrawn
+        place.version().track();
+        withdraw.version().track();
+        account.track();
+
+        async move {
+            match keplr.key.await {
+                Ok(key) => limit_order::load(&key.bech32_address),
+                Err(_) => vec![],
+            }
+        }
+    });
+
+    let price_label = move || {
+        let sell = get_token_symbol(&sell_token.get());
+        let buy = get_token_symbol(&buy_token.get());
+        format!("Price ({buy} per {sell})")
+    };
+
+    let token_options = move || {
+        view! {
+            <option value=SYMBOL_TO_ADDR.get("SSCRT")>sSCRT</option>
+            <option value=SYMBOL_TO_ADDR.get("STKDSCRT")>"stkd-SCRT"</option>
+            <option value=SYMBOL_TO_ADDR.get("AMBER")>AMBER</option>
+            <option value=SYMBOL_TO_ADDR.get("SHD")>SHD</option>
+        }
+    };
+
+    view! {
+        <div class="max-w-sm mx-auto space-y-3">
+            <TradeTabs limit=true />
+
+            <div class="rounded-lg shadow-sm bg-card text-card-foreground border border-solid border-border">
+                <div class="p-6">
+                    <h2 class="m-0">"Limit Order"</h2>
+                    <p class="mb-0 text-xs text-muted-foreground">
+                        "Your tokens sit in a single bin at the chosen price, and convert as the
+                        market trades through it."
+                    </p>
+                </div>
+                <div class="px-6 pb-6 space-y-4">
+                    <div class="space-y-2">
+                        <label class="block text-sm font-medium">"Sell"</label>
+                        <div class="flex justify-between gap-4 h-9">
+                            <input
+                                type="text"
+                                pattern="^[0-9]*[.,]?[0-9]*$"
+                                inputmode="decimal"
+                                placeholder="0.0"
+                                autocomplete="off"
+                                class="px-3 py-1 w-full text-sm rounded-md font-normal"
+                                on:input=move |ev| set_amount.set(event_target_value(&ev))
+                            />
+                            <select
+                                class="w-[135px] font-medium py-2 px-4 bg-card rounded-md"
+                                title="Select the token to sell"
+                                prop:value=move || sell_token.get()
+                                on:change=move |ev| sell_token.set(event_target_value(&ev))
+                            >
+                                {token_options}
+                            </select>
+                        </div>
+                    </div>
+                    <div class="space-y-2">
+                        <label class="block text-sm font-medium">"For"</label>
+                        <select
+                            class="w-full font-medium py-2 px-4 bg-card rounded-md"
+                            title="Select the token to buy"
+                            prop:value=move || buy_token.get()
+                            on:change=move |ev| buy_token.set(event_target_value(&ev))
+                        >
+                            {token_options}
+                        </select>
+                    </div>
+                    <div class="space-y-2">
+                        <label class="block text-sm font-medium">{price_label}</label>
+                        <input
+                            type="text"
+                            pattern="^[0-9]*[.,]?[0-9]*$"
+                            inputmode="decimal"
+                            placeholder="0.0"
+                            autocomplete="off"
+                            class="px-3 py-1 w-full h-9 text-sm rounded-md font-normal"
+                            on:input=move |ev| set_price.set(event_target_value(&ev))
+                        />
+                    </div>
+                </div>
+                <div class="px-6 pb-6">
+                    <button
+                        class="w-full py-2 px-6 bg-primary active:brightness-90 text-primary-foreground text-sm font-medium rounded-md"
+                        disabled=move || {
+                            !keplr.enabled.get() || place.pending().get() || price.get().is_empty()
+                                || amount.get().is_empty()
+                        }
+                        on:click=move |_| _ = place.dispatch(())
+                    >
+                        "Place Order"
+                    </button>
+                    {move || {
+                        place
+                            .value()
+                            .get()
+                            .and_then(Result::err)
+                            .map(|error| {
+                                view! { <ErrorMessage error class="mt-2" /> }
+                            })
+                    }}
+                </div>
+            </div>
+
+            <Show when=move || keplr.enabled.get()>
+                <div class="text-base font-semibold">"Your Orders"</div>
+                <Suspense fallback=|| view! { <div>"Loading..."</div> }>
+                    <ul class="m-0 p-0 list-none space-y-2">
+                        {move || Suspend::new(async move {
+                            let orders = orders.await;
+                            if orders.is_empty() {
+                                return view! {
+                                    <p class="text-sm text-muted-foreground">"No open orders."</p>
+                                }
+                                    .into_any();
+                            }
+                            orders
+                                .into_iter()
+                                .map(|order| view! { <OrderRow order withdraw /> })
+                                .collect_view()
+                                .into_any()
+                        })}
+                    </ul>
+                </Suspense>
+                {move || {
+                    withdraw
+                        .value()
+                        .get()
+                        .and_then(Result::err)
+                        .map(|error| {
+                            view! { <ErrorMessage error /> }
+                        })
+                }}
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn OrderRow(
+    order: LimitOrder,
+    withdraw: Action<LimitOrder, Result<(), Error>, LocalStorage>,
+) -> impl IntoView {
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+
+    let symbol_x = get_token_symbol(order.token_x.address.as_str());
+    let symbol_y = get_token_symbol(order.token_y.address.as_str());
+    let decimals_x = get_token_decimals(order.token_x.address.as_str()).unwrap_or(0);
+    let decimals_y = get_token_decimals(order.token_y.address.as_str()).unwrap_or(0);
+
+    // back to human units of the bought token per sold token
+    let price_x = order.price() * 10f64.powi(decimals_x as i32 - decimals_y as i32);
+    let description = if order.sell_x {
+        format!(
+            "Sell {} {symbol_x} at {price_x:.6} {symbol_y}",
+            display_token_amount(order.amount.u128(), decimals_x),
+        )
+    } else {
+        format!(
+            "Sell {} {symbol_y} at {:.6} {symbol_x}",
+            display_token_amount(order.amount.u128(), decimals_y),
+            1.0 / price_x,
+        )
+    };
+
+    let order = StoredValue::new(order);
+
+    let state = LocalResource::new(move || async move {
+        let key = keplr.key.await?;
+        limit_order::state(&key.bech32_address, &order.read_value()).await
+    });
+
+    view! {
+        <li class="p-4 bg-card rounded-lg border border-solid space-y-2">
+            <div class="font-semibold text-sm">{description}</div>
+            <Suspense fallback=|| view! { <div class="text-xs">"Loading..."</div> }>
+                {move || Suspend::new(async move {
+                    match state.await {
+                        Ok(state) => {
+                            let status = match state.status {
+                                FillStatus::Open => "Open",
+                                FillStatus::PartiallyFilled => "Partially filled",
+                                FillStatus::Filled => "Filled",
+                                FillStatus::Closed => "Closed",
+                            };
+                            let filled = format!("{:.0}%", state.filled * 100.0);
+                            let label = if state.status == FillStatus::Filled {
+                                "Withdraw"
+                            } else {
+                                "Cancel"
+                            };
+
+                            view! {
+                                <div class="flex items-center justify-between gap-2 text-xs">
+                                    <span>{format!("{status}, {filled} filled")}</span>
+                                    <button
+                                        class="py-1 px-2 text-xs bg-secondary text-secondary-foreground rounded-md"
+                                        disabled=move || withdraw.pending().get()
+                                        on:click=move |_| _ = withdraw.dispatch(order.get_value())
+                                    >
+                                        {label}
+                                    </button>
+                                </div>
+                                <div class="h-1.5 w-full bg-muted rounded-full overflow-hidden">
+                                    <div
+                                        class="h-full bg-primary"
+                                        style=format!("width: {filled}")
+                                    ></div>
+                                </div>
+                            }
+                                .into_any()
+                        }
+                        Err(error) => {
+                            view! { <div class="text-xs text-destructive">{error.to_string()}</div> }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Suspense>
+        </li>
+    }
+}
@@ -1,10 +1,19 @@
-use crate::swap::Swap;
+use crate::{limit::Limit, swap::Swap};
 use leptos::prelude::{component, view};
-use leptos_router::{components::Route, MatchNestedRoutes};
+use leptos_router::{
+    components::{ParentRoute, Route},
+    nested_router::Outlet,
+    MatchNestedRoutes,
+};
 use leptos_router_macro::path;
 
 #[component]
 pub fn SwapRoutes() -> impl MatchNestedRoutes + Clone {
-    view! { <Route path=path!("/liquidity-book-leptos/trade") view=Swap /> }
+    view! {
+        <ParentRoute path=path!("/liquidity-book-leptos/trade") view=Outlet>
+            <Route path=path!("") view=Swap />
+            <Route path=path!("/limit") view=Limit />
+        </ParentRoute>
+    }
     .into_inner()
 }
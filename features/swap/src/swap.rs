@@ -1,10 +1,10 @@
-use crate::{SwapDetails, SwapSettings};
-use ammber_components::{LoadingModal, Secret20Balance, Spinner2};
+use crate::{limit::TradeTabs, SwapDetails, SwapSettings};
+use ammber_components::{ErrorMessage, Secret20Balance, Spinner2};
 use ammber_core::{
-    constants::{contracts::*, CHAIN_ID, NODE, SYMBOL_TO_ADDR, TOKEN_MAP},
-    state::{Endpoint, KeplrSignals, TokenMap},
-    utils::{display_token_amount, parse_token_amount},
-    Error,
+    constants::{contracts::*, CHAIN_ID, SYMBOL_TO_ADDR, TOKEN_MAP},
+    state::{TokenMap, WalletSignals},
+    utils::{display_token_amount, parse_token_amount, token_contract},
+    Error, TxKind, TxManager, TxSummary,
 };
 use ammber_sdk::contract_interfaces::{
     lb_quoter::Quote,
@@ -12,19 +12,13 @@ use ammber_sdk::contract_interfaces::{
 };
 use codee::string::FromToStringCodec;
 use cosmwasm_std::{to_binary, Addr, Uint128, Uint64};
-use keplr::Keplr;
+use keplr::wallet;
 use leptos::{ev, html, logging::*, prelude::*, tachys::dom::window};
 use leptos_router::{hooks::query_signal_with_options, NavigateOptions};
 use leptos_use::storage::use_local_storage;
 use liquidity_book::core::TokenType;
 use lucide_leptos::{ArrowDownUp, Settings2};
-use rsecret::{
-    secret_client::CreateTxSenderOptions,
-    tx::{compute::MsgExecuteContractRaw, ComputeServiceClient},
-    TxOptions,
-};
-use secretrs::AccountId;
-use std::str::FromStr;
+use rsecret::TxOptions;
 use tracing::{debug, info};
 use web_sys::js_sys::Date;
 
@@ -36,8 +30,8 @@ pub fn Swap() -> impl IntoView {
         info!("cleaning up <Swap/>");
     });
 
-    let endpoint = use_context::<Endpoint>().expect("endpoint context missing!");
-    let keplr = use_context::<KeplrSignals>().expect("keplr signals context missing!");
+    let keplr = use_context::<WalletSignals>().expect("wallet signals context missing!");
+    let tx_manager = use_context::<TxManager>().expect("tx manager context missing!");
     let _token_map = use_context::<TokenMap>().expect("tokens context missing!");
 
     // prevents scrolling to the top of the page each time a query param changes
@@ -212,16 +206,12 @@ pub fn Swap() -> impl IntoView {
             })
     };
 
-    // TODO: how will we recheck the balances after a swap?
     let swap = Action::new_local(move |quote: &Quote| {
-        let url = endpoint.get();
-        let chain_id = CHAIN_ID;
-
         let quote = quote.clone();
 
         async move {
-            let Ok(key) = Keplr::get_key(CHAIN_ID).await else {
-                return Err(Error::generic("Could not get key from Keplr"));
+            let Ok(key) = wallet::active().get_key(CHAIN_ID).await else {
+                return Err(Error::generic("Could not get key from the wallet"));
             };
 
             let slippage = 10_000 - slippage.get();
@@ -244,22 +234,6 @@ pub fn Swap() -> impl IntoView {
             let to = key.bech32_address.clone();
             let deadline = deadline.get() * 60 + (Date::now() / 1000.0) as u64;
 
-            // let wallet = Keplr::get_offline_signer_only_amino(CHAIN_ID);
-            let wallet = Keplr::get_offline_signer(chain_id);
-            let enigma_utils = Keplr::get_enigma_utils(chain_id).into();
-
-            let options = CreateTxSenderOptions {
-                url: NODE,
-                chain_id: CHAIN_ID,
-                wallet: wallet.into(),
-                wallet_address: key.bech32_address.clone().into(),
-                enigma_utils,
-            };
-
-            // TODO: this isn't making sense... why am I providing a url both here and in the options?
-            let wasm_web_client = tonic_web_wasm_client::Client::new(url.to_string());
-            let compute_service_client = ComputeServiceClient::new(wasm_web_client, options);
-
             let swap_msg = lb_router::ExecuteMsg::SwapExactTokensForTokens {
                 amount_in: Uint128::from(amount_in),
                 amount_out_min: Uint128::from(amount_out_min),
@@ -279,32 +253,22 @@ pub fn Swap() -> impl IntoView {
                 padding: None,
             };
 
-            let sender = AccountId::new("secret", &key.address)?;
-            let contract = AccountId::from_str(path.token_path[0].address().as_str())?;
-
-            let msg = MsgExecuteContractRaw {
-                sender,
-                contract: contract.clone(),
-                msg: send_msg,
-                sent_funds: vec![],
-            };
+            let token_in = token_contract(&path.token_path[0])?;
 
-            let tx_options = TxOptions {
-                gas_limit: 500_000,
-                ..Default::default()
-            };
+            let msg = tx_manager
+                .execute_msg(&key.bech32_address, &token_in, &send_msg)
+                .await?;
 
-            let tx = compute_service_client
-                .execute_contract(msg, path.token_path[0].code_hash(), tx_options)
-                .await
-                .inspect(|tx_response| info!("{tx_response:?}"))
-                .inspect_err(|error| error!("{error}"))?;
+            let tx_options = TxOptions::default();
 
-            if tx.code != 0 {
-                error!("{}", tx.raw_log);
-            }
+            let summary = TxSummary::new(TxKind::Swap)
+                .with_token(token_in.address.to_string(), amount_in)
+                .with_token(
+                    path.token_path.last().unwrap().address().to_string(),
+                    amount_out_min,
+                );
 
-            // TODO: trigger rechecking of balances here somehow
+            tx_manager.submit(summary, vec![msg], tx_options).await?;
 
             Ok(())
         }
@@ -380,32 +344,13 @@ pub fn Swap() -> impl IntoView {
     let price_impact = RwSignal::new(2.00);
     // absolute inset-0 m-auto -translate-y-[54px]
     view! {
-        <LoadingModal when=swap.pending() message="Processing Transaction... (watch the console)" />
         <div class="absolute inset-0 m-auto flex items-center justify-center">
             // <div class="grid gap-4 sm:grid-cols-[minmax(0px,7fr)_minmax(0px,5fr)] grid-cols-1 grid-rows-2 sm:grid-rows-1">
             // <div class="grid gap-4 grid-cols-1 max-w-[550px] w-full">
             <div class="grid gap-4 grid-cols-1 max-w-sm md:-translate-y-[54px]">
                 <div class="flex flex-col space-y-3">
                     // buttons above the main swap box
-                    <div class="flex items-center justify-evenly gap-0.5 p-[5px] bg-muted rounded-md">
-                        <button class="w-full py-1.5 px-3 rounded-sm bg-background text-foreground border-none h-8
-                        ">"Swap"</button>
-                        <div class="w-full group relative">
-                            <button
-                                disabled
-                                class="!opacity-75 w-full py-1.5 px-3 rounded-sm bg-muted text-muted-foreground border-none h-8
-                                "
-                            >
-                                "Place Order"
-                            </button>
-                            <div class="w-[100px] text-center absolute bottom-1/2 md:bottom-full left-1/2 -translate-x-1/2 md:mb-2 px-2 py-1 z-50 translate-y-1/2 md:translate-y-0
-                            invisible group-hover:visible opacity-0 group-hover:opacity-100 transition-opacity duration-100 ease-in
-                            border border-solid border-border
-                            bg-popover text-popover-foreground text-xs font-semibold rounded-md whitespace-nowrap">
-                                "soon"
-                            </div>
-                        </div>
-                    </div>
+                    <TradeTabs limit=false />
 
                     // TODO: toggle button to show chart or something else. when that's on, switch to grid
                     // layout with grid-cols-[minmax(0px,7fr)_minmax(0px,5fr)]
@@ -587,13 +532,22 @@ pub fn Swap() -> impl IntoView {
                             <button
                                 class="w-full py-2 px-6 bg-primary active:brightness-90 text-primary-foreground text-sm font-medium rounded-md"
                                 disabled=move || {
-                                    !keplr.enabled.get()
+                                    !keplr.enabled.get() || swap.pending().get()
                                         || get_quote.value().get().and_then(Result::ok).is_none()
                                 }
                                 on:click=handle_swap
                             >
-                                "Swap"
+                                // the transaction's progress shows in the wallet menu
+                                {move || if swap.pending().get() { "Swapping..." } else { "Swap" }}
                             </button>
+                            {move || {
+                                swap.value()
+                                    .get()
+                                    .and_then(Result::err)
+                                    .map(|error| {
+                                        view! { <ErrorMessage error class="mt-2" /> }
+                                    })
+                            }}
                         </div>
                     </div>
                 </div>